@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::{
+    match_api::{match_request::MatchRequest, match_result::MatchResult},
+    nearest_api::{nearest_request::NearestRequest, nearest_result::NearestResult},
+    osrm_error::OsrmError,
+    route_api::{route_request::RouteRequest, route_result::RouteResult},
+    table_api::{table_request::TableRequest, table_result::TableResult},
+    tile_api::{tile_request::TileRequest, tile_result::TileResult},
+    trip_api::{trip_request::TripRequest, trip_result::TripResult},
+    Osrm,
+};
+
+/// Runs requests on tokio's blocking pool so the FFI calls never block the executor.
+///
+/// At most `max_concurrency` requests run at once. Dropping one of the returned futures
+/// after it has been dispatched does not abort the engine call; it finishes on the blocking
+/// pool and keeps its permit until then, so the limit holds for cancelled requests too.
+#[derive(Clone)]
+pub struct AsyncOsrm {
+    osrm: Arc<Osrm>,
+    permits: Arc<Semaphore>,
+}
+
+impl AsyncOsrm {
+    pub fn new(osrm: Osrm, max_concurrency: usize) -> AsyncOsrm {
+        AsyncOsrm::from_arc(Arc::new(osrm), max_concurrency)
+    }
+
+    pub fn from_arc(osrm: Arc<Osrm>, max_concurrency: usize) -> AsyncOsrm {
+        AsyncOsrm {
+            osrm,
+            permits: Arc::new(Semaphore::new(max_concurrency.max(1))),
+        }
+    }
+
+    pub fn osrm(&self) -> &Arc<Osrm> {
+        &self.osrm
+    }
+
+    pub async fn route_async(&self, mut request: RouteRequest) -> Result<RouteResult, OsrmError> {
+        self.dispatch(move |osrm| request.run(osrm)).await
+    }
+
+    pub async fn table_async(&self, mut request: TableRequest) -> Result<TableResult, OsrmError> {
+        self.dispatch(move |osrm| request.run(osrm)).await
+    }
+
+    pub async fn match_async(&self, mut request: MatchRequest) -> Result<MatchResult, OsrmError> {
+        self.dispatch(move |osrm| request.run(osrm)).await
+    }
+
+    pub async fn trip_async(&self, mut request: TripRequest) -> Result<TripResult, OsrmError> {
+        self.dispatch(move |osrm| request.run(osrm)).await
+    }
+
+    pub async fn nearest_async(
+        &self,
+        mut request: NearestRequest,
+    ) -> Result<NearestResult, OsrmError> {
+        self.dispatch(move |osrm| request.run(osrm)).await
+    }
+
+    pub async fn tile_async(&self, mut request: TileRequest) -> Result<TileResult, OsrmError> {
+        self.dispatch(move |osrm| request.run(osrm)).await
+    }
+
+    async fn dispatch<T, F>(&self, call: F) -> Result<T, OsrmError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Osrm) -> Result<T, OsrmError> + Send + 'static,
+    {
+        limited(&self.permits, self.osrm.clone(), call).await
+    }
+}
+
+// Runs `call` on the blocking pool once a permit is free and holds the permit until it returns.
+async fn limited<S, T, F>(permits: &Arc<Semaphore>, state: Arc<S>, call: F) -> Result<T, OsrmError>
+where
+    S: Send + Sync + 'static,
+    T: Send + 'static,
+    F: FnOnce(&S) -> Result<T, OsrmError> + Send + 'static,
+{
+    let permit = match permits.clone().acquire_owned().await {
+        Ok(permit) => permit,
+        Err(e) => return Err(OsrmError::Ffi(e.to_string())),
+    };
+
+    let handle = tokio::task::spawn_blocking(move || {
+        let result = call(&state);
+        drop(permit);
+        result
+    });
+
+    match handle.await {
+        Ok(result) => result,
+        Err(e) => Err(OsrmError::Ffi(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use tokio::sync::Semaphore;
+
+    use super::limited;
+
+    #[test]
+    fn no_more_than_max_concurrency_requests_run_at_once() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let permits = Arc::new(Semaphore::new(2));
+        // Requests currently running and the most that ever ran together.
+        let counters = Arc::new((AtomicUsize::new(0), AtomicUsize::new(0)));
+
+        let finished = runtime.block_on(async {
+            let tasks: Vec<_> = (0..8)
+                .map(|index| {
+                    let (permits, counters) = (permits.clone(), counters.clone());
+                    tokio::spawn(async move {
+                        limited(&permits, counters, move |(running, peak)| {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            thread::sleep(Duration::from_millis(20));
+                            running.fetch_sub(1, Ordering::SeqCst);
+                            Ok(index)
+                        })
+                        .await
+                    })
+                })
+                .collect();
+
+            let mut finished = Vec::new();
+            for task in tasks {
+                finished.push(task.await.unwrap().unwrap());
+            }
+            finished
+        });
+
+        assert_eq!(finished, (0..8).collect::<Vec<usize>>());
+        assert_eq!(counters.1.load(Ordering::SeqCst), 2);
+        assert_eq!(permits.available_permits(), 2);
+    }
+}
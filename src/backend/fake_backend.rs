@@ -0,0 +1,127 @@
+use crate::{
+    engine_config::engine_limits::EngineLimits,
+    match_api::{match_request::MatchRequest, match_result::MatchResult},
+    nearest_api::{nearest_request::NearestRequest, nearest_result::NearestResult},
+    osrm_error::OsrmError,
+    route_api::{route_request::RouteRequest, route_result::RouteResult},
+    table_api::{table_request::TableRequest, table_result::TableResult},
+    tile_api::{tile_request::TileRequest, tile_result::TileResult},
+    trip_api::{trip_request::TripRequest, trip_result::TripResult},
+};
+
+use super::RoutingBackend;
+
+type Handler<'a, Q, R> = Box<dyn Fn(&mut Q) -> Result<R, OsrmError> + Send + Sync + 'a>;
+
+// Answers each service with the closure set for it and panics on services a test did not expect.
+// Closures may borrow state from the test, and the backend can be shared between threads.
+#[derive(Default)]
+pub(crate) struct FakeBackend<'a> {
+    route: Option<Handler<'a, RouteRequest, RouteResult>>,
+    table: Option<Handler<'a, TableRequest, TableResult>>,
+    match_trace: Option<Handler<'a, MatchRequest, MatchResult>>,
+    trip: Option<Handler<'a, TripRequest, TripResult>>,
+    nearest: Option<Handler<'a, NearestRequest, NearestResult>>,
+    tile: Option<Handler<'a, TileRequest, TileResult>>,
+    limits: Option<EngineLimits>,
+}
+
+impl<'a> FakeBackend<'a> {
+    pub(crate) fn new() -> FakeBackend<'a> {
+        FakeBackend::default()
+    }
+
+    pub(crate) fn on_route(
+        mut self,
+        handler: impl Fn(&mut RouteRequest) -> Result<RouteResult, OsrmError> + Send + Sync + 'a,
+    ) -> FakeBackend<'a> {
+        self.route = Some(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn on_table(
+        mut self,
+        handler: impl Fn(&mut TableRequest) -> Result<TableResult, OsrmError> + Send + Sync + 'a,
+    ) -> FakeBackend<'a> {
+        self.table = Some(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn on_match(
+        mut self,
+        handler: impl Fn(&mut MatchRequest) -> Result<MatchResult, OsrmError> + Send + Sync + 'a,
+    ) -> FakeBackend<'a> {
+        self.match_trace = Some(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn on_trip(
+        mut self,
+        handler: impl Fn(&mut TripRequest) -> Result<TripResult, OsrmError> + Send + Sync + 'a,
+    ) -> FakeBackend<'a> {
+        self.trip = Some(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn on_nearest(
+        mut self,
+        handler: impl Fn(&mut NearestRequest) -> Result<NearestResult, OsrmError> + Send + Sync + 'a,
+    ) -> FakeBackend<'a> {
+        self.nearest = Some(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn on_tile(
+        mut self,
+        handler: impl Fn(&mut TileRequest) -> Result<TileResult, OsrmError> + Send + Sync + 'a,
+    ) -> FakeBackend<'a> {
+        self.tile = Some(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn with_limits(mut self, limits: EngineLimits) -> FakeBackend<'a> {
+        self.limits = Some(limits);
+        self
+    }
+}
+
+impl RoutingBackend for FakeBackend<'_> {
+    fn route(&self, request: &mut RouteRequest) -> Result<RouteResult, OsrmError> {
+        answer(&self.route, "route", request)
+    }
+
+    fn table(&self, request: &mut TableRequest) -> Result<TableResult, OsrmError> {
+        answer(&self.table, "table", request)
+    }
+
+    fn match_trace(&self, request: &mut MatchRequest) -> Result<MatchResult, OsrmError> {
+        answer(&self.match_trace, "match", request)
+    }
+
+    fn trip(&self, request: &mut TripRequest) -> Result<TripResult, OsrmError> {
+        answer(&self.trip, "trip", request)
+    }
+
+    fn nearest(&self, request: &mut NearestRequest) -> Result<NearestResult, OsrmError> {
+        answer(&self.nearest, "nearest", request)
+    }
+
+    fn tile(&self, request: &mut TileRequest) -> Result<TileResult, OsrmError> {
+        answer(&self.tile, "tile", request)
+    }
+
+    fn engine_limits(&self) -> EngineLimits {
+        self.limits.clone().unwrap_or_else(EngineLimits::unlimited)
+    }
+}
+
+fn answer<Q, R>(
+    handler: &Option<Handler<'_, Q, R>>,
+    service: &str,
+    request: &mut Q,
+) -> Result<R, OsrmError> {
+    match handler {
+        Some(handler) => handler(request),
+        None => panic!("the fake backend has no {} handler", service),
+    }
+}
@@ -0,0 +1,632 @@
+use std::{io::Read, time::Duration};
+
+use crate::{
+    general::{
+        c_structs::{c_approach::Approach, c_coordinate::COsrmCoordinate},
+        json,
+        rs_structs::general_options::GeneralOptions,
+    },
+    match_api::{match_request::MatchRequest, match_result::MatchResult, Gap},
+    nearest_api::{nearest_request::NearestRequest, nearest_result::NearestResult},
+    osrm_error::OsrmError,
+    route_api::{
+        route_request::RouteRequest, route_result::RouteResult, AnnotationsType, GeometriesType,
+        OverviewType,
+    },
+    table_api::{
+        table_request::TableRequest, table_result::TableResult, Annotations, FallbackCoordinate,
+    },
+    tile_api::{tile_request::TileRequest, tile_result::TileResult},
+    trip_api::{trip_end, trip_request::TripRequest, trip_result::TripResult, trip_start},
+};
+
+use super::RoutingBackend;
+
+pub struct HttpBackend {
+    base_url: String,
+    profile: String,
+    agent: ureq::Agent,
+}
+
+impl HttpBackend {
+    pub fn new(base_url: &str, profile: &str) -> HttpBackend {
+        HttpBackend::with_timeout(base_url, profile, Duration::from_secs(30))
+    }
+
+    pub fn with_timeout(base_url: &str, profile: &str, timeout: Duration) -> HttpBackend {
+        HttpBackend {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            profile: profile.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    fn url(&self, service: &str, coordinates: &str, query: &[(&str, String)]) -> String {
+        let mut url = format!(
+            "{}/{}/v1/{}/{}",
+            self.base_url, service, self.profile, coordinates
+        );
+
+        if !query.is_empty() {
+            let query: Vec<String> = query
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+                .collect();
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+
+        url
+    }
+
+    fn get(&self, url: &str) -> Result<Vec<u8>, OsrmError> {
+        match self.agent.get(url).call() {
+            Ok(response) => read_body(response),
+            Err(ureq::Error::Status(status, response)) => {
+                Err(status_error(status, &read_body(response)?))
+            }
+            Err(e) => Err(OsrmError::Http(e.to_string())),
+        }
+    }
+
+    fn get_text(&self, url: &str) -> Result<String, OsrmError> {
+        Ok(String::from_utf8_lossy(&self.get(url)?).into_owned())
+    }
+}
+
+impl RoutingBackend for HttpBackend {
+    fn route(&self, request: &mut RouteRequest) -> Result<RouteResult, OsrmError> {
+        let mut query = general_query(&request.general_options);
+        query.push(("steps", request.steps.to_string()));
+        query.push((
+            "alternatives",
+            if request.alternatives && request.number_of_alternatives > 0 {
+                request.number_of_alternatives.to_string()
+            } else {
+                request.alternatives.to_string()
+            },
+        ));
+        query.push((
+            "annotations",
+            annotations_value(request.annotations, &request.annotations_type),
+        ));
+        query.push(("geometries", geometries_value(&request.geometries)));
+        query.push(("overview", overview_value(&request.overview)));
+        if let Some(continue_straight) = request.continue_straight {
+            query.push(("continue_straight", continue_straight.to_string()));
+        }
+        if let Some(waypoints) = &request.waypoints {
+            query.push(("waypoints", join(waypoints)));
+        }
+
+        let url = self.url(
+            "route",
+            &coordinates_value(&request.general_options.coordinate),
+            &query,
+        );
+        RouteResult::from_osrm_json(&self.get_text(&url)?, &request.geometries)
+    }
+
+    fn table(&self, request: &mut TableRequest) -> Result<TableResult, OsrmError> {
+        let mut query = general_query(&request.general_options);
+        if let Some(sources) = &request.sources {
+            query.push(("sources", join(sources)));
+        }
+        if let Some(destinations) = &request.destinations {
+            query.push(("destinations", join(destinations)));
+        }
+        query.push((
+            "annotations",
+            match request.annotations {
+                Annotations::NONE | Annotations::DURATION => "duration",
+                Annotations::DISTANCE => "distance",
+                Annotations::ALL => "duration,distance",
+            }
+            .to_string(),
+        ));
+        if request.fallback_speed != f64::MAX {
+            query.push(("fallback_speed", request.fallback_speed.to_string()));
+            query.push((
+                "fallback_coordinate",
+                match request.fallback_coordinate {
+                    FallbackCoordinate::INPUT => "input",
+                    FallbackCoordinate::SNAPPED => "snapped",
+                }
+                .to_string(),
+            ));
+        }
+        if request.scale_factor != 1.0 {
+            query.push(("scale_factor", request.scale_factor.to_string()));
+        }
+
+        let url = self.url(
+            "table",
+            &coordinates_value(&request.general_options.coordinate),
+            &query,
+        );
+        TableResult::from_osrm_json(&self.get_text(&url)?)
+    }
+
+    fn match_trace(&self, request: &mut MatchRequest) -> Result<MatchResult, OsrmError> {
+        let mut query = general_query(&request.general_options);
+        query.push(("steps", request.steps.to_string()));
+        query.push(("geometries", geometries_value(&request.geometries)));
+        query.push((
+            "annotations",
+            annotations_value(request.annotations, &request.annotations_type),
+        ));
+        query.push(("overview", overview_value(&request.overview)));
+        if let Some(timestamps) = &request.timestamps {
+            query.push(("timestamps", join(timestamps)));
+        }
+        query.push((
+            "gaps",
+            match request.gaps {
+                Gap::Split => "split",
+                Gap::Ignore => "ignore",
+            }
+            .to_string(),
+        ));
+        query.push(("tidy", request.tidy.to_string()));
+        if let Some(waypoints) = &request.waypoints {
+            query.push(("waypoints", join(waypoints)));
+        }
+
+        let url = self.url(
+            "match",
+            &coordinates_value(&request.general_options.coordinate),
+            &query,
+        );
+        MatchResult::from_osrm_json(&self.get_text(&url)?, &request.geometries)
+    }
+
+    fn trip(&self, request: &mut TripRequest) -> Result<TripResult, OsrmError> {
+        let mut query = general_query(&request.general_options);
+        query.push(("roundtrip", request.roundtrip.to_string()));
+        query.push((
+            "source",
+            match request.source {
+                trip_start::StartAny => "any",
+                trip_start::First => "first",
+            }
+            .to_string(),
+        ));
+        query.push((
+            "destination",
+            match request.destination {
+                trip_end::EndAny => "any",
+                trip_end::Last => "last",
+            }
+            .to_string(),
+        ));
+        query.push(("steps", request.steps.to_string()));
+        query.push((
+            "annotations",
+            annotations_value(request.annotations, &request.annotations_type),
+        ));
+        query.push(("geometries", geometries_value(&request.geometries)));
+        query.push(("overview", overview_value(&request.overview)));
+
+        let url = self.url(
+            "trip",
+            &coordinates_value(&request.general_options.coordinate),
+            &query,
+        );
+        TripResult::from_osrm_json(&self.get_text(&url)?, &request.geometries)
+    }
+
+    fn nearest(&self, request: &mut NearestRequest) -> Result<NearestResult, OsrmError> {
+        let mut query = general_query(&request.general_options);
+        query.push(("number", request.number_of_results.to_string()));
+
+        let url = self.url(
+            "nearest",
+            &coordinates_value(&request.general_options.coordinate),
+            &query,
+        );
+        NearestResult::from_osrm_json(&self.get_text(&url)?)
+    }
+
+    fn tile(&self, request: &mut TileRequest) -> Result<TileResult, OsrmError> {
+        let url = self.url(
+            "tile",
+            &format!("tile({},{},{}).mvt", request.x, request.y, request.z),
+            &[],
+        );
+        Ok(TileResult {
+            result: self.get(&url)?,
+        })
+    }
+}
+
+fn read_body(response: ureq::Response) -> Result<Vec<u8>, OsrmError> {
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut body)
+        .map_err(|e| OsrmError::Http(e.to_string()))?;
+
+    Ok(body)
+}
+
+// osrm-routed answers failed requests with a JSON `code` and `message`; anything else in front of
+// it, such as a proxy's HTML error page, only leaves the status to report.
+fn status_error(status: u16, body: &[u8]) -> OsrmError {
+    let error = json::parse(&String::from_utf8_lossy(body))
+        .ok()
+        .filter(|value| value.get("code").is_some())
+        .and_then(|value| json::check_code(&value).err());
+
+    error.unwrap_or_else(|| OsrmError::Http(format!("HTTP status {}", status)))
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+// Keeps OSRM's `,` and `;` separators readable and escapes everything else a hint or an exclude
+// class could contain, such as `+`, `/`, `=`, `&` or `#`.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b',' | b';' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn coordinates_value(coordinates: &[COsrmCoordinate]) -> String {
+    coordinates
+        .iter()
+        .map(|coordinate| format!("{},{}", coordinate.longitude, coordinate.latitude))
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+fn general_query(general_options: &GeneralOptions) -> Vec<(&'static str, String)> {
+    let mut query = Vec::new();
+
+    if let Some(bearings) = &general_options.bearings {
+        query.push((
+            "bearings",
+            bearings
+                .iter()
+                .map(|bearing| match bearing {
+                    Some(bearing) => format!("{},{}", bearing.bearing, bearing.range),
+                    None => String::new(),
+                })
+                .collect::<Vec<String>>()
+                .join(";"),
+        ));
+    }
+
+    if let Some(radiuses) = &general_options.radiuses {
+        query.push((
+            "radiuses",
+            radiuses
+                .iter()
+                .map(|radius| match radius {
+                    Some(radius) => radius.to_string(),
+                    None => "unlimited".to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join(";"),
+        ));
+    }
+
+    query.push(("generate_hints", general_options.generate_hints.to_string()));
+
+    if general_options.skip_waypoints {
+        query.push(("skip_waypoints", "true".to_string()));
+    }
+
+    if let Some(hints) = &general_options.hints {
+        query.push((
+            "hints",
+            hints
+                .iter()
+                .map(|hint| hint.to_string_lossy().into_owned())
+                .collect::<Vec<String>>()
+                .join(";"),
+        ));
+    }
+
+    if let Some(approach) = &general_options.approach {
+        query.push((
+            "approaches",
+            approach
+                .iter()
+                .map(|approach| match approach {
+                    Some(Approach::CURB) => "curb",
+                    Some(Approach::UNRESTRICTED) => "unrestricted",
+                    None => "",
+                })
+                .collect::<Vec<&str>>()
+                .join(";"),
+        ));
+    }
+
+    if let Some(exclude) = &general_options.exclude {
+        query.push((
+            "exclude",
+            exclude
+                .iter()
+                .map(|exclude| exclude.to_string_lossy().into_owned())
+                .collect::<Vec<String>>()
+                .join(","),
+        ));
+    }
+
+    query
+}
+
+fn annotations_value(annotations: bool, annotations_type: &AnnotationsType) -> String {
+    if !annotations {
+        return "false".to_string();
+    }
+
+    match annotations_type {
+        AnnotationsType::None | AnnotationsType::All => "true",
+        AnnotationsType::Duration => "duration",
+        AnnotationsType::Nodes => "nodes",
+        AnnotationsType::Distance => "distance",
+        AnnotationsType::Weight => "weight",
+        AnnotationsType::Datasources => "datasources",
+        AnnotationsType::Speed => "speed",
+    }
+    .to_string()
+}
+
+fn geometries_value(geometries: &GeometriesType) -> String {
+    match geometries {
+        GeometriesType::Polyline => "polyline",
+        GeometriesType::Polyline6 => "polyline6",
+        GeometriesType::GeoJSON => "geojson",
+    }
+    .to_string()
+}
+
+fn overview_value(overview: &OverviewType) -> String {
+    match overview {
+        OverviewType::Simplified => "simplified",
+        OverviewType::Full => "full",
+        OverviewType::False => "false",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use std::ffi::CString;
+
+    use crate::{
+        general::rs_structs::{
+            coordinate::Coordinate, general_options::GeneralOptionsTrait, geometry::Geometry,
+        },
+        match_api::match_request_builder::MatchRequestBuilder,
+        nearest_api::nearest_request_builder::NearestRequestBuilder,
+        osrm_error::OsrmError,
+        route_api::{route_request_builder::RouteRequestBuilder, GeometriesType},
+        table_api::{table_request_builder::TableRequestBuilder, Annotations},
+        tile_api::tile_request::TileRequest,
+        trip_api::trip_request_builder::TripRequestBuilder,
+    };
+
+    use super::HttpBackend;
+
+    fn berlin() -> Vec<Coordinate> {
+        vec![
+            Coordinate::new(52.517037, 13.38886),
+            Coordinate::new(52.529432, 13.397631),
+            Coordinate::new(52.523239, 13.428554),
+        ]
+    }
+
+    fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0u8; 4096];
+            let read = stream.read(&mut buffer).unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).into_owned();
+
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            request.lines().next().unwrap_or_default().to_string()
+        });
+
+        (address, handle)
+    }
+
+    #[test]
+    fn route_against_stand_in_server() {
+        let (address, handle) = serve_once(
+            "200 OK",
+            r#"{"code":"Ok","waypoints":[{"hint":"abc","distance":1.5,"name":"Main Street","location":[13.4,57.8]},{"distance":0.5,"name":"","location":[13.5,57.9]}],"routes":[{"duration":60.0,"distance":1000.0,"weight_name":"routability","weight":61.0,"geometry":"_ibE_seK","legs":[{"duration":60.0,"distance":1000.0,"weight":61.0,"summary":"Main Street","steps":[]}]}]}"#,
+        );
+
+        let backend = HttpBackend::new(&address, "driving");
+        let result = RouteRequestBuilder::new(&[
+            Coordinate::new(57.8, 13.4),
+            Coordinate::new(57.9, 13.5),
+        ])
+        .build()
+        .unwrap()
+        .run(&backend)
+        .unwrap();
+
+        let request_line = handle.join().unwrap();
+        assert!(request_line.starts_with("GET /route/v1/driving/13.4,57.8;13.5,57.9?"));
+        assert!(request_line.contains("geometries=polyline"));
+
+        assert_eq!(result.code.as_deref(), Some("Ok"));
+        assert_eq!(result.waypoints.len(), 2);
+        assert_eq!(result.waypoints[0].name, "Main Street");
+        assert_eq!(result.waypoints[0].location, [13.4, 57.8]);
+        assert_eq!(
+            result.routes[0].legs[0].summary.as_deref(),
+            Some("Main Street")
+        );
+        assert_eq!(result.routes[0].distance, 1000.0);
+    }
+
+    #[test]
+    fn error_code_from_stand_in_server() {
+        let (address, handle) = serve_once(
+            "400 Bad Request",
+            r#"{"code":"NoSegment","message":"Could not find a matching segment for any coordinate."}"#,
+        );
+
+        let backend = HttpBackend::new(&address, "driving");
+        let result = NearestRequestBuilder::new(57.8, 13.4)
+            .set_number_of_results(3)
+            .build()
+            .unwrap()
+            .run(&backend);
+
+        let request_line = handle.join().unwrap();
+        assert!(request_line.starts_with("GET /nearest/v1/driving/13.4,57.8?"));
+        assert!(request_line.contains("number=3"));
+
+        match result {
+            Err(OsrmError::NoSegment(message)) => assert!(message.starts_with("Could not find")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn table_against_stand_in_server() {
+        let (address, handle) = serve_once("200 OK", include_str!("../../tests/golden/table.json"));
+
+        let backend = HttpBackend::new(&address, "driving");
+        let result = TableRequestBuilder::new(&berlin())
+            .set_annotations(Annotations::ALL)
+            .set_hints(Some(vec![
+                CString::new("a+b/c=").unwrap(),
+                CString::new("").unwrap(),
+                CString::new("d&e#f").unwrap(),
+            ]))
+            .build()
+            .unwrap()
+            .run(&backend)
+            .unwrap();
+
+        let request_line = handle.join().unwrap();
+        assert!(request_line.starts_with("GET /table/v1/driving/13.38886,52.517037;"));
+        assert!(request_line.contains("annotations=duration,distance"));
+        assert!(request_line.contains("hints=a%2Bb%2Fc%3D;;d%26e%23f"));
+
+        let durations = result.durations.unwrap();
+        assert_eq!((durations.rows(), durations.columns()), (3, 3));
+        assert_eq!(durations.get(0, 1), Some(1286.7));
+        assert_eq!(durations.get(0, 2), None);
+        assert_eq!(result.distances.unwrap().get(1, 2), Some(12701.3));
+    }
+
+    #[test]
+    fn match_against_stand_in_server() {
+        let (address, handle) = serve_once("200 OK", include_str!("../../tests/golden/match.json"));
+
+        let backend = HttpBackend::new(&address, "driving");
+        let result = MatchRequestBuilder::new(&berlin())
+            .set_geometries(GeometriesType::GeoJSON)
+            .set_timestamps(Some(vec![0, 10, 20]))
+            .build()
+            .unwrap()
+            .run(&backend)
+            .unwrap();
+
+        let request_line = handle.join().unwrap();
+        assert!(request_line.starts_with("GET /match/v1/driving/"));
+        assert!(request_line.contains("geometries=geojson"));
+        assert!(request_line.contains("timestamps=0;10;20"));
+
+        assert_eq!(result.matchings.len(), 1);
+        assert!(matches!(
+            result.matchings[0].geometry,
+            Some(Geometry::GeoJson(_))
+        ));
+        assert!(result.tracepoints[1].is_none());
+        assert_eq!(
+            result.tracepoints[0].as_ref().unwrap().name,
+            "Friedrichstraße"
+        );
+    }
+
+    #[test]
+    fn trip_against_stand_in_server() {
+        let (address, handle) = serve_once("200 OK", include_str!("../../tests/golden/trip.json"));
+
+        let backend = HttpBackend::new(&address, "driving");
+        let result = TripRequestBuilder::new(&berlin())
+            .set_roundtrip(true)
+            .build()
+            .unwrap()
+            .run(&backend)
+            .unwrap();
+
+        let request_line = handle.join().unwrap();
+        assert!(request_line.starts_with("GET /trip/v1/driving/"));
+        assert!(request_line.contains("roundtrip=true"));
+        assert!(request_line.contains("source=any"));
+
+        assert_eq!(result.trips.len(), 1);
+        assert_eq!(result.trips[0].legs.len(), 3);
+        assert_eq!(result.waypoints[1].waypoint_index, 2);
+    }
+
+    #[test]
+    fn tile_against_stand_in_server() {
+        let (address, handle) = serve_once("200 OK", "\u{1a}\u{2}mvt");
+
+        let backend = HttpBackend::new(&address, "driving");
+        let result = TileRequest::new(4400, 2686, 13).run(&backend).unwrap();
+
+        let request_line = handle.join().unwrap();
+        assert!(request_line.starts_with("GET /tile/v1/driving/tile(4400,2686,13).mvt "));
+        assert_eq!(result.result, b"\x1a\x02mvt".to_vec());
+
+        let (address, handle) = serve_once(
+            "400 Bad Request",
+            r#"{"code":"InvalidValue","message":"Invalid zoom level"}"#,
+        );
+        let result = TileRequest::new(4400, 2686, 13).run(&HttpBackend::new(&address, "driving"));
+        handle.join().unwrap();
+        assert!(matches!(result, Err(OsrmError::InvalidValue(_))));
+
+        let (address, handle) = serve_once("502 Bad Gateway", "<html>Bad Gateway</html>");
+        let result = TileRequest::new(4400, 2686, 13).run(&HttpBackend::new(&address, "driving"));
+        handle.join().unwrap();
+        assert_eq!(
+            result.err().unwrap(),
+            OsrmError::Http("HTTP status 502".to_string())
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    engine_config::engine_limits::EngineLimits,
+    match_api::{match_request::MatchRequest, match_result::MatchResult},
+    nearest_api::{nearest_request::NearestRequest, nearest_result::NearestResult},
+    osrm_error::OsrmError,
+    route_api::{route_request::RouteRequest, route_result::RouteResult},
+    table_api::{table_request::TableRequest, table_result::TableResult},
+    tile_api::{tile_request::TileRequest, tile_result::TileResult},
+    trip_api::{trip_request::TripRequest, trip_result::TripResult},
+    Osrm,
+};
+
+#[cfg(feature = "http")]
+pub mod http_backend;
+
+#[cfg(test)]
+pub(crate) mod fake_backend;
+
+pub trait RoutingBackend {
+    fn route(&self, request: &mut RouteRequest) -> Result<RouteResult, OsrmError>;
+
+    fn table(&self, request: &mut TableRequest) -> Result<TableResult, OsrmError>;
+
+    fn match_trace(&self, request: &mut MatchRequest) -> Result<MatchResult, OsrmError>;
+
+    fn trip(&self, request: &mut TripRequest) -> Result<TripResult, OsrmError>;
+
+    fn nearest(&self, request: &mut NearestRequest) -> Result<NearestResult, OsrmError>;
+
+    fn tile(&self, request: &mut TileRequest) -> Result<TileResult, OsrmError>;
+
+    // The limits requests are built against; a remote osrm-routed enforces its own.
+    fn engine_limits(&self) -> EngineLimits {
+        EngineLimits::unlimited()
+    }
+}
+
+impl RoutingBackend for Osrm {
+    fn route(&self, request: &mut RouteRequest) -> Result<RouteResult, OsrmError> {
+        request.run_osrm(self)
+    }
+
+    fn table(&self, request: &mut TableRequest) -> Result<TableResult, OsrmError> {
+        request.run_osrm(self)
+    }
+
+    fn match_trace(&self, request: &mut MatchRequest) -> Result<MatchResult, OsrmError> {
+        request.run_osrm(self)
+    }
+
+    fn trip(&self, request: &mut TripRequest) -> Result<TripResult, OsrmError> {
+        request.run_osrm(self)
+    }
+
+    fn nearest(&self, request: &mut NearestRequest) -> Result<NearestResult, OsrmError> {
+        request.run_osrm(self)
+    }
+
+    fn tile(&self, request: &mut TileRequest) -> Result<TileResult, OsrmError> {
+        request.run_osrm(self)
+    }
+
+    fn engine_limits(&self) -> EngineLimits {
+        self.limits().clone()
+    }
+}
@@ -0,0 +1,46 @@
+use std::{process, str::FromStr};
+
+use rs_osrm::Algorithm;
+
+// Argument handling shared by the binaries; every error exits with the binary's own usage text.
+pub struct Cli {
+    pub usage: &'static str,
+}
+
+impl Cli {
+    pub fn value(&self, option: &str, value: Option<String>) -> String {
+        match value {
+            Some(value) => value,
+            None => self.exit(&format!("missing value for {}", option)),
+        }
+    }
+
+    pub fn number<T: FromStr>(&self, option: &str, value: Option<String>) -> T {
+        match self.value(option, value).parse() {
+            Ok(number) => number,
+            Err(_) => self.exit(&format!("invalid number for {}", option)),
+        }
+    }
+
+    // A thread or worker count, which has to be at least one.
+    pub fn count(&self, option: &str, value: Option<String>) -> usize {
+        match self.number(option, value) {
+            0 => self.exit(&format!("{} must be at least 1", option)),
+            count => count,
+        }
+    }
+
+    pub fn algorithm(&self, option: &str, value: Option<String>) -> Algorithm {
+        match self.value(option, value).to_uppercase().as_str() {
+            "CH" => Algorithm::CH,
+            "CORECH" => Algorithm::CoreCH,
+            "MLD" => Algorithm::MLD,
+            other => self.exit(&format!("unknown algorithm: {}", other)),
+        }
+    }
+
+    pub fn exit(&self, message: &str) -> ! {
+        eprintln!("{}\n\n{}", message, self.usage);
+        process::exit(1)
+    }
+}
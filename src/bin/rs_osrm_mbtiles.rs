@@ -0,0 +1,116 @@
+mod common;
+
+use std::{env, process};
+
+use common::Cli;
+use rs_osrm::{
+    engine_config::engine_config_builder::EngineConfigBuilder,
+    general::rs_structs::coordinate::Coordinate,
+    tile_api::{
+        mbtiles::MbtilesExport,
+        tile_range::{BoundingBox, MIN_ZOOM},
+    },
+    Algorithm,
+};
+
+const USAGE: &str = "Usage: rs_osrm_mbtiles [options] --bbox <west,south,east,north> <base.osrm>
+
+Options:
+  -b, --bbox <bbox>            Area to export as west,south,east,north in degrees
+  -o, --output <file>          MBTiles file to create [default: osrm.mbtiles]
+      --min-zoom <zoom>        Lowest zoom level to export [default: 12]
+      --max-zoom <zoom>        Highest zoom level to export [default: min zoom]
+  -n, --name <name>            Tileset name stored in the metadata
+  -t, --threads <threads>      Number of tiles fetched in parallel [default: 8]
+  -a, --algorithm <algorithm>  Routing algorithm, CH or MLD [default: CH]
+  -s, --shared-memory          Load data from shared memory";
+
+fn main() {
+    let mut bbox: Option<BoundingBox> = None;
+    let mut output = "osrm.mbtiles".to_string();
+    let mut min_zoom = MIN_ZOOM;
+    let mut max_zoom: Option<i32> = None;
+    let mut name: Option<String> = None;
+    let mut threads = 8;
+    let mut algorithm = Algorithm::CH;
+    let mut use_shared_memory = false;
+    let mut path: Option<String> = None;
+
+    let cli = Cli { usage: USAGE };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-b" | "--bbox" => bbox = Some(bounding_box(&cli, &cli.value(&arg, args.next()))),
+            "-o" | "--output" => output = cli.value(&arg, args.next()),
+            "--min-zoom" => min_zoom = cli.number(&arg, args.next()),
+            "--max-zoom" => max_zoom = Some(cli.number(&arg, args.next())),
+            "-n" | "--name" => name = Some(cli.value(&arg, args.next())),
+            "-t" | "--threads" => threads = cli.count(&arg, args.next()),
+            "-a" | "--algorithm" => algorithm = cli.algorithm(&arg, args.next()),
+            "-s" | "--shared-memory" => use_shared_memory = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => cli.exit(&format!("unknown option: {}", arg)),
+            _ => path = Some(arg),
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None if use_shared_memory => String::new(),
+        None => cli.exit("missing path to .osrm file"),
+    };
+    let bbox = match bbox {
+        Some(bbox) => bbox,
+        None => cli.exit("missing --bbox"),
+    };
+
+    let mut export = MbtilesExport::new(bbox, min_zoom, max_zoom.unwrap_or(min_zoom));
+    export.threads = threads;
+    if let Some(name) = name {
+        export.name = name;
+    }
+
+    let tiles = match export.ranges() {
+        Ok(ranges) => ranges.iter().map(|range| range.len()).sum::<usize>(),
+        Err(e) => cli.exit(&e.to_string()),
+    };
+
+    let osrm = match EngineConfigBuilder::new(&path)
+        .set_algorithm(algorithm)
+        .set_use_shared_memory(use_shared_memory)
+        .build()
+    {
+        Ok(osrm) => osrm,
+        Err(e) => cli.exit(&e),
+    };
+
+    println!("exporting {} tiles to {}", tiles, output);
+    match export.export(&osrm, &output) {
+        Ok(written) => println!("wrote {} non-empty tiles", written),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1)
+        }
+    }
+}
+
+fn bounding_box(cli: &Cli, value: &str) -> BoundingBox {
+    let numbers: Vec<f64> = value
+        .split(',')
+        .map(|number| match number.trim().parse() {
+            Ok(number) => number,
+            Err(_) => cli.exit(&format!("invalid bbox: {}", value)),
+        })
+        .collect();
+    if numbers.len() != 4 {
+        cli.exit(&format!("invalid bbox: {}", value));
+    }
+
+    BoundingBox::new(
+        Coordinate::new(numbers[1], numbers[0]),
+        Coordinate::new(numbers[3], numbers[2]),
+    )
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::env;
+
+use common::Cli;
+use rs_osrm::{engine_config::engine_config_builder::EngineConfigBuilder, server, Algorithm};
+
+const USAGE: &str = "Usage: rs_osrm_server [options] <base.osrm>
+
+Options:
+  -i, --ip <ip>                IP address to listen on [default: 0.0.0.0]
+  -p, --port <port>            TCP port to listen on [default: 5000]
+  -t, --threads <threads>      Number of worker threads [default: 8]
+  -a, --algorithm <algorithm>  Routing algorithm, CH or MLD [default: CH]
+  -s, --shared-memory          Load data from shared memory
+      --max-viaroute-size <n>  Max number of locations for route queries
+      --max-trip-size <n>      Max number of locations for trip queries
+      --max-table-size <n>     Max number of locations for table queries
+      --max-matching-size <n>  Max number of locations for match queries
+      --max-nearest-size <n>   Max number of results for nearest queries
+      --max-alternatives <n>   Max number of alternatives for route queries";
+
+fn main() {
+    let mut ip = "0.0.0.0".to_string();
+    let mut port = "5000".to_string();
+    let mut threads = 8;
+    let mut algorithm = Algorithm::CH;
+    let mut use_shared_memory = false;
+    let mut limits: Vec<(String, i32)> = Vec::new();
+    let mut path: Option<String> = None;
+
+    let cli = Cli { usage: USAGE };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--ip" => ip = cli.value(&arg, args.next()),
+            "-p" | "--port" => port = cli.value(&arg, args.next()),
+            "-t" | "--threads" => threads = cli.count(&arg, args.next()),
+            "-a" | "--algorithm" => algorithm = cli.algorithm(&arg, args.next()),
+            "-s" | "--shared-memory" => use_shared_memory = true,
+            "--max-viaroute-size"
+            | "--max-trip-size"
+            | "--max-table-size"
+            | "--max-matching-size"
+            | "--max-nearest-size"
+            | "--max-alternatives" => {
+                let limit = cli.number(&arg, args.next());
+                limits.push((arg, limit));
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => cli.exit(&format!("unknown option: {}", arg)),
+            _ => path = Some(arg),
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None if use_shared_memory => String::new(),
+        None => cli.exit("missing path to .osrm file"),
+    };
+
+    let mut builder = EngineConfigBuilder::new(&path);
+    builder
+        .set_algorithm(algorithm)
+        .set_use_shared_memory(use_shared_memory);
+
+    for (option, limit) in limits {
+        match option.as_str() {
+            "--max-viaroute-size" => builder.set_max_locations_viaroute(limit),
+            "--max-trip-size" => builder.set_max_locations_trip(limit),
+            "--max-table-size" => builder.set_max_locations_distance_table(limit),
+            "--max-matching-size" => builder.set_max_locations_map_matching(limit),
+            "--max-nearest-size" => builder.set_max_results_nearest(limit),
+            _ => builder.set_max_alternatives(limit),
+        };
+    }
+
+    let osrm = match builder.build() {
+        Ok(osrm) => osrm,
+        Err(e) => cli.exit(&e),
+    };
+
+    let address = format!("{}:{}", ip, port);
+    println!("running and waiting for requests on {}", address);
+
+    if let Err(e) = server::serve(osrm, &address, threads) {
+        cli.exit(&e);
+    }
+}
@@ -1,8 +1,8 @@
 use std::ffi::CString;
 
-use crate::{Algorithm, Boolean, Osrm};
+use crate::{Algorithm, Boolean, Osrm, StringDecoding};
 
-use super::c_engine_config::CEngineConfig;
+use super::{c_engine_config::CEngineConfig, engine_limits::EngineLimits};
 
 pub struct EngineConfigBuilder {
     storage_config: CString,
@@ -19,6 +19,7 @@ pub struct EngineConfigBuilder {
     algorithm: Algorithm,
     verbosity: Option<CString>,
     dataset_name: Option<CString>,
+    string_decoding: StringDecoding,
 }
 
 impl EngineConfigBuilder {
@@ -38,6 +39,7 @@ impl EngineConfigBuilder {
             algorithm: Algorithm::CH,
             verbosity: None,
             dataset_name: None,
+            string_decoding: StringDecoding::Lossy,
         }
     }
 
@@ -153,10 +155,19 @@ impl EngineConfigBuilder {
         self
     }
 
+    pub fn set_string_decoding<'i>(
+        &'i mut self,
+        string_decoding: StringDecoding,
+    ) -> &'i mut Self {
+        self.string_decoding = string_decoding;
+        self
+    }
+
     pub fn build(&mut self) -> Result<Osrm, String> {
         let c_storage_config = CString::new(self.storage_config.clone()).unwrap();
         let mut c_engine_config = CEngineConfig::new(&c_storage_config);
 
+        c_engine_config.max_locations_trip = self.max_locations_trip;
         c_engine_config.max_alternatives = self.max_alternatives;
         c_engine_config.max_locations_viaroute = self.max_locations_viaroute;
         c_engine_config.max_locations_distance_table = self.max_locations_distance_table;
@@ -203,6 +214,18 @@ impl EngineConfigBuilder {
             None => {}
         }
 
-        Osrm::new(c_engine_config)
+        Osrm::new(c_engine_config, self.string_decoding.clone(), self.limits())
+    }
+
+    pub fn limits(&self) -> EngineLimits {
+        EngineLimits {
+            max_locations_trip: self.max_locations_trip,
+            max_locations_viaroute: self.max_locations_viaroute,
+            max_locations_distance_table: self.max_locations_distance_table,
+            max_locations_map_matching: self.max_locations_map_matching,
+            max_radius_map_matching: self.max_radius_map_matching,
+            max_results_nearest: self.max_results_nearest,
+            max_alternatives: self.max_alternatives,
+        }
     }
 }
@@ -0,0 +1,27 @@
+// Values of zero or below mean no limit, except for `max_alternatives` where zero disables
+// alternatives and only negative values lift the limit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineLimits {
+    pub max_locations_trip: i32,
+    pub max_locations_viaroute: i32,
+    pub max_locations_distance_table: i32,
+    pub max_locations_map_matching: i32,
+    pub max_radius_map_matching: f64,
+    pub max_results_nearest: i32,
+    pub max_alternatives: i32,
+}
+
+impl EngineLimits {
+    pub fn unlimited() -> EngineLimits {
+        EngineLimits {
+            max_locations_trip: -1,
+            max_locations_viaroute: -1,
+            max_locations_distance_table: -1,
+            max_locations_map_matching: -1,
+            max_radius_map_matching: -1.0,
+            max_results_nearest: -1,
+            max_alternatives: -1,
+        }
+    }
+}
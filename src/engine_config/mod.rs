@@ -1,2 +1,3 @@
 pub mod c_engine_config;
-pub mod engine_config_builder;
\ No newline at end of file
+pub mod engine_config_builder;
+pub mod engine_limits;
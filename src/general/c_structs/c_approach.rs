@@ -1,5 +1,6 @@
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Approach {
     UNRESTRICTED,
     CURB,
@@ -2,6 +2,7 @@ use std::os::raw::c_short;
 
 #[repr(C)]
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bearing {
     pub bearing: c_short,
     pub range: c_short,
@@ -4,6 +4,7 @@ use crate::general::rs_structs::coordinate::Coordinate;
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct COsrmCoordinate {
     pub(crate) latitude: c_double,
     pub(crate) longitude: c_double,
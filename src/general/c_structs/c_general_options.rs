@@ -62,7 +62,14 @@ impl From<&mut GeneralOptions> for CGeneralOptions {
         }
 
         if let Some(hints) = &option.hints {
-           general_c_option.hints = hints.as_ptr() as *const *const c_char; 
+            option.hints_t = hints
+                .iter()
+                .map(|hint| match hint.as_bytes().is_empty() {
+                    true => std::ptr::null(),
+                    false => hint.as_ptr(),
+                })
+                .collect();
+            general_c_option.hints = option.hints_t.as_ptr();
         }
 
         if let Some(approach) = &option.approach {
@@ -78,8 +85,9 @@ impl From<&mut GeneralOptions> for CGeneralOptions {
         }
 
         if let Some(exclude) = &option.exclude {
-           general_c_option.exclude = exclude.as_ptr() as *const *const c_char;
-           general_c_option.number_of_excludes = exclude.len() as c_int; 
+            option.exclude_t = exclude.iter().map(|exclude| exclude.as_ptr()).collect();
+            general_c_option.exclude = option.exclude_t.as_ptr();
+            general_c_option.number_of_excludes = exclude.len() as c_int;
         }
 
         general_c_option
@@ -3,7 +3,15 @@ use std::{
     slice,
 };
 
-use crate::general::{c_string_to_option_string, rs_structs::route::Route};
+use crate::{
+    general::{
+        c_string_to_option_string,
+        rs_structs::{geometry::Geometry, route::Route, route_leg::RouteLeg},
+    },
+    osrm_error::OsrmError,
+    route_api::GeometriesType,
+    StringDecoding,
+};
 
 use super::c_route_leg::COsrmRouteLeg;
 
@@ -19,22 +27,27 @@ pub(crate) struct COsrmRoute {
     pub(crate) number_of_legs: c_int,
 }
 
-impl From<&COsrmRoute> for Route {
-    fn from(c_route: &COsrmRoute) -> Self {
-        Route {
+impl Route {
+    pub(crate) fn new(
+        c_route: &COsrmRoute,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<Route, OsrmError> {
+        Ok(Route {
             duration: c_route.duration,
             distance: c_route.distance,
-            weight_name: c_string_to_option_string(c_route.weight_name),
+            weight_name: c_string_to_option_string(c_route.weight_name, string_decoding)?,
             weight: c_route.weight,
-            geometry: c_string_to_option_string(c_route.geometry),
+            geometry: c_string_to_option_string(c_route.geometry, string_decoding)?
+                .map(|geometry| Geometry::new(geometry, geometries)),
             legs: if c_route.legs != std::ptr::null_mut() {
-                unsafe { slice::from_raw_parts(c_route.legs, c_route.number_of_legs as usize).to_vec() }
+                unsafe { slice::from_raw_parts(c_route.legs, c_route.number_of_legs as usize) }
                     .iter()
-                    .map(|leg| leg.into())
-                    .collect()
+                    .map(|leg| RouteLeg::new(leg, string_decoding, geometries))
+                    .collect::<Result<Vec<RouteLeg>, OsrmError>>()?
             } else {
                 Vec::new()
             },
-        }
+        })
     }
 }
\ No newline at end of file
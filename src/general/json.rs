@@ -0,0 +1,324 @@
+use serde_json::Value;
+
+use crate::{general::rs_structs::coordinate::Coordinate, osrm_error::OsrmError};
+
+pub(crate) fn parse(json: &str) -> Result<Value, OsrmError> {
+    serde_json::from_str(json).map_err(|e| OsrmError::Json(e.to_string()))
+}
+
+pub(crate) fn check_code(value: &Value) -> Result<(), OsrmError> {
+    let code = opt_string_field(value, "code");
+    if code.as_deref() == Some("Ok") {
+        Ok(())
+    } else {
+        Err(OsrmError::from_code(
+            Some(code.unwrap_or_default()),
+            opt_string_field(value, "message"),
+        ))
+    }
+}
+
+pub(crate) fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, OsrmError> {
+    match value.get(name) {
+        Some(field) if !field.is_null() => Ok(field),
+        _ => Err(OsrmError::Json(format!("missing field `{}`", name))),
+    }
+}
+
+pub(crate) fn f64_field(value: &Value, name: &str) -> Result<f64, OsrmError> {
+    field(value, name)?
+        .as_f64()
+        .ok_or_else(|| OsrmError::Json(format!("field `{}` is not a number", name)))
+}
+
+pub(crate) fn opt_f64_field(value: &Value, name: &str) -> Option<f64> {
+    value.get(name).and_then(|field| field.as_f64())
+}
+
+pub(crate) fn i32_field(value: &Value, name: &str) -> Result<i32, OsrmError> {
+    field(value, name)?
+        .as_i64()
+        .map(|number| number as i32)
+        .ok_or_else(|| OsrmError::Json(format!("field `{}` is not an integer", name)))
+}
+
+pub(crate) fn opt_i32_field(value: &Value, name: &str) -> Option<i32> {
+    value
+        .get(name)
+        .and_then(|field| field.as_i64())
+        .map(|number| number as i32)
+}
+
+pub(crate) fn string_field(value: &Value, name: &str) -> Result<String, OsrmError> {
+    field(value, name)?
+        .as_str()
+        .map(|string| string.to_owned())
+        .ok_or_else(|| OsrmError::Json(format!("field `{}` is not a string", name)))
+}
+
+pub(crate) fn opt_string_field(value: &Value, name: &str) -> Option<String> {
+    value
+        .get(name)
+        .and_then(|field| field.as_str())
+        .map(|string| string.to_owned())
+}
+
+pub(crate) fn array_field<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>, OsrmError> {
+    field(value, name)?
+        .as_array()
+        .ok_or_else(|| OsrmError::Json(format!("field `{}` is not an array", name)))
+}
+
+pub(crate) fn opt_array_field<'a>(value: &'a Value, name: &str) -> Option<&'a Vec<Value>> {
+    value.get(name).and_then(|field| field.as_array())
+}
+
+pub(crate) fn f64_array(values: &[Value]) -> Vec<f64> {
+    values
+        .iter()
+        .map(|value| value.as_f64().unwrap_or(f64::NAN))
+        .collect()
+}
+
+pub(crate) fn location_field(value: &Value, name: &str) -> Result<[f64; 2], OsrmError> {
+    let location = array_field(value, name)?;
+    if location.len() != 2 {
+        return Err(OsrmError::Json(format!(
+            "field `{}` is not a [lon, lat] pair",
+            name
+        )));
+    }
+
+    match (location[0].as_f64(), location[1].as_f64()) {
+        (Some(longitude), Some(latitude)) => Ok([longitude, latitude]),
+        _ => Err(OsrmError::Json(format!(
+            "field `{}` is not a [lon, lat] pair",
+            name
+        ))),
+    }
+}
+
+pub(crate) fn coordinate_field(value: &Value, name: &str) -> Result<Coordinate, OsrmError> {
+    let location = location_field(value, name)?;
+    Ok(Coordinate::new(location[1], location[0]))
+}
+
+pub(crate) fn geometry_field(value: &Value, name: &str) -> Option<String> {
+    match value.get(name) {
+        Some(Value::String(geometry)) => Some(geometry.clone()),
+        Some(Value::Object(_)) => value.get(name).map(|geometry| geometry.to_string()),
+        _ => None,
+    }
+}
+
+pub(crate) fn coordinate_value(coordinate: &Coordinate) -> Value {
+    serde_json::json!([coordinate.longitude, coordinate.latitude])
+}
+
+pub(crate) fn insert_opt_string(object: &mut Value, name: &str, value: &Option<String>) {
+    if let (Some(value), Some(object)) = (value, object.as_object_mut()) {
+        object.insert(name.to_string(), Value::String(value.clone()));
+    }
+}
+
+pub(crate) fn insert_non_empty_string(object: &mut Value, name: &str, value: &Option<String>) {
+    match value {
+        Some(value) if !value.is_empty() => insert_opt_string(object, name, &Some(value.clone())),
+        _ => {}
+    }
+}
+
+pub(crate) fn render(value: &Value) -> String {
+    let mut output = String::new();
+    render_value(value, &mut output);
+    output
+}
+
+fn render_value(value: &Value, output: &mut String) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(boolean) => output.push_str(if *boolean { "true" } else { "false" }),
+        Value::Number(number) => match (number.as_u64(), number.as_i64(), number.as_f64()) {
+            (Some(number), _, _) => output.push_str(&number.to_string()),
+            (_, Some(number), _) => output.push_str(&number.to_string()),
+            (_, _, Some(number)) => render_number(number, output),
+            _ => output.push_str("null"),
+        },
+        Value::String(string) => render_string(string, output),
+        Value::Array(values) => {
+            output.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                render_value(value, output);
+            }
+            output.push(']');
+        }
+        Value::Object(values) => {
+            output.push('{');
+            for (index, (key, value)) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                render_string(key, output);
+                output.push(':');
+                render_value(value, output);
+            }
+            output.push('}');
+        }
+    }
+}
+
+// osrm-routed prints numbers with `%.10f` and trims trailing zeros and a trailing `.`,
+// so integral values come out without a fraction.
+fn render_number(number: f64, output: &mut String) {
+    if !number.is_finite() {
+        output.push_str("null");
+        return;
+    }
+
+    let formatted = format!("{:.10}", number);
+    output.push_str(formatted.trim_end_matches('0').trim_end_matches('.'));
+}
+
+fn render_string(string: &str, output: &mut String) {
+    output.push('"');
+    for letter in string.chars() {
+        match letter {
+            '\\' => output.push_str("\\\\"),
+            '"' => output.push_str("\\\""),
+            '/' => output.push_str("\\/"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            letter if (letter as u32) < 0x20 => {
+                output.push_str(&format!("\\u{:04x}", letter as u32))
+            }
+            letter => output.push(letter),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{
+        general::rs_structs::geometry::Geometry,
+        match_api::match_result::MatchResult,
+        nearest_api::nearest_result::NearestResult,
+        osrm_error::OsrmError,
+        route_api::{route_result::RouteResult, GeometriesType},
+        table_api::table_result::TableResult,
+        trip_api::trip_result::TripResult,
+    };
+
+    use super::render;
+
+    fn assert_golden(recorded: &str, emitted: String) {
+        assert_eq!(emitted, recorded.trim_end());
+    }
+
+    #[test]
+    fn results_reproduce_recorded_responses() {
+        let recorded = include_str!("../../tests/golden/route.json");
+        let result = RouteResult::from_osrm_json(recorded, &GeometriesType::Polyline).unwrap();
+        assert_golden(recorded, result.to_osrm_json());
+
+        let recorded = include_str!("../../tests/golden/table.json");
+        let result = TableResult::from_osrm_json(recorded).unwrap();
+        assert_golden(recorded, result.to_osrm_json());
+
+        let recorded = include_str!("../../tests/golden/match.json");
+        let result = MatchResult::from_osrm_json(recorded, &GeometriesType::Polyline).unwrap();
+        assert_golden(recorded, result.to_osrm_json());
+
+        let recorded = include_str!("../../tests/golden/trip.json");
+        let result = TripResult::from_osrm_json(recorded, &GeometriesType::Polyline).unwrap();
+        assert_golden(recorded, result.to_osrm_json());
+
+        let recorded = include_str!("../../tests/golden/nearest.json");
+        let result = NearestResult::from_osrm_json(recorded).unwrap();
+        assert_golden(recorded, result.to_osrm_json());
+    }
+
+    #[test]
+    fn geometries_keep_their_encoding() {
+        let recorded = include_str!("../../tests/golden/route_polyline6.json");
+        let result = RouteResult::from_osrm_json(recorded, &GeometriesType::Polyline6).unwrap();
+        let geometry = result.routes[0].geometry.as_ref().unwrap();
+        assert_eq!(
+            geometry,
+            &Geometry::Polyline6("yikdcBwbepXsbA|I_vDvY".to_string())
+        );
+        let coordinates = geometry.coordinates().unwrap();
+        assert_eq!(coordinates.len(), 3);
+        assert_eq!(coordinates[2].latitude, 52.521047);
+        assert_eq!(coordinates[2].longitude, 13.388257);
+        assert_golden(recorded, result.to_osrm_json());
+
+        let polyline = RouteResult::from_osrm_json(recorded, &GeometriesType::Polyline).unwrap();
+        let coordinates = polyline.routes[0]
+            .geometry
+            .as_ref()
+            .unwrap()
+            .coordinates()
+            .unwrap();
+        assert_eq!(coordinates[2].latitude, 525.21047);
+
+        let recorded = include_str!("../../tests/golden/route_geojson.json");
+        let result = RouteResult::from_osrm_json(recorded, &GeometriesType::GeoJSON).unwrap();
+        let geometry = result.routes[0].legs[0].steps[0].geometry.as_ref().unwrap();
+        assert!(matches!(
+            geometry.geometries_type(),
+            GeometriesType::GeoJSON
+        ));
+        assert_eq!(geometry.coordinates().unwrap().len(), 3);
+        assert_golden(recorded, result.to_osrm_json());
+    }
+
+    #[test]
+    fn error_responses_become_errors() {
+        let error = RouteResult::from_osrm_json(
+            r#"{"code":"NoRoute","message":"Impossible route between points"}"#,
+            &GeometriesType::Polyline,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            OsrmError::NoRoute("Impossible route between points".to_string())
+        );
+
+        assert!(matches!(
+            TableResult::from_osrm_json("<html>"),
+            Err(OsrmError::Json(_))
+        ));
+        assert!(matches!(
+            TableResult::from_osrm_json(
+                r#"{"code":"Ok","durations":[[0,1]],"fallback_speed_cells":[[0,1],[0]]}"#
+            ),
+            Err(OsrmError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn numbers_and_strings_are_rendered_like_osrm() {
+        let value = serde_json::json!({
+            "distance": 0.0,
+            "duration": 1286.7,
+            "weight": 0.12345678912345,
+            "geometry": "a\\b/c\"",
+            "nodes": [2264199819u64, -1],
+            "missing": Value::Null,
+        });
+
+        assert_eq!(
+            render(&value),
+            r#"{"distance":0,"duration":1286.7,"weight":0.1234567891,"geometry":"a\\b\/c\"","nodes":[2264199819,-1],"missing":null}"#
+        );
+    }
+}
@@ -1,11 +1,16 @@
 use std::ffi::CStr;
 use std::os::raw::{c_char};
 
+use crate::{osrm_error::OsrmError, StringDecoding};
+
 use self::c_structs::c_coordinate::COsrmCoordinate;
 use self::rs_structs::coordinate::Coordinate;
 
 pub mod c_structs;
+#[cfg(feature = "json")]
+pub(crate) mod json;
 pub mod rs_structs;
+pub mod validation;
 
 pub(crate) fn to_vec_ccoordinate(coordinates: &Vec<Coordinate>) -> Vec<COsrmCoordinate> {
     let mut return_vec = Vec::new();
@@ -16,24 +21,64 @@ pub(crate) fn to_vec_ccoordinate(coordinates: &Vec<Coordinate>) -> Vec<COsrmCoor
     return_vec
 }
 
-pub(crate) fn c_string_to_string(c_string: *const c_char) -> String {
+pub(crate) fn c_string_to_string(
+    c_string: *const c_char,
+    string_decoding: &StringDecoding,
+) -> Result<String, OsrmError> {
+    Ok(c_string_to_option_string(c_string, string_decoding)?.unwrap_or_default())
+}
+
+pub(crate) fn c_string_to_option_string(
+    c_string: *const c_char,
+    string_decoding: &StringDecoding,
+) -> Result<Option<String>, OsrmError> {
     if c_string == std::ptr::null_mut() {
-        return "".to_string();
+        return Ok(None);
     }
 
     let c_str: &CStr = unsafe { CStr::from_ptr(c_string) };
-    let c_str_slice: &str = c_str.to_str().unwrap();
 
-    c_str_slice.to_owned()
+    match string_decoding {
+        StringDecoding::Lossy => Ok(Some(c_str.to_string_lossy().into_owned())),
+        StringDecoding::Strict => match c_str.to_str() {
+            Ok(c_str_slice) => Ok(Some(c_str_slice.to_owned())),
+            Err(e) => Err(OsrmError::InvalidUtf8(e.to_string())),
+        },
+    }
 }
 
-pub(crate) fn c_string_to_option_string(c_string: *const c_char) -> Option<String> {
-    if c_string == std::ptr::null_mut() {
-        return None;
-    }
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CString, os::raw::c_char};
 
-    let c_str: &CStr = unsafe { CStr::from_ptr(c_string) };
-    let c_str_slice: &str = c_str.to_str().unwrap();
+    use crate::{osrm_error::OsrmError, StringDecoding};
+
+    use super::{c_string_to_option_string, c_string_to_string};
 
-    Option::from(c_str_slice.to_owned())
+    #[test]
+    fn c_strings_are_decoded_lossy_or_strict() {
+        let valid = CString::new("Unter den Linden").unwrap();
+        for decoding in [StringDecoding::Lossy, StringDecoding::Strict] {
+            assert_eq!(
+                c_string_to_option_string(valid.as_ptr(), &decoding).unwrap(),
+                Some("Unter den Linden".to_string())
+            );
+            assert_eq!(
+                c_string_to_option_string(std::ptr::null(), &decoding).unwrap(),
+                None
+            );
+            assert_eq!(c_string_to_string(std::ptr::null(), &decoding).unwrap(), "");
+        }
+
+        let latin1 = CString::new(vec![b'S', b't', b'r', 0xe4, b'e']).unwrap();
+        let invalid: *const c_char = latin1.as_ptr();
+        assert_eq!(
+            c_string_to_option_string(invalid, &StringDecoding::Lossy).unwrap(),
+            Some("Str\u{fffd}e".to_string())
+        );
+        assert!(matches!(
+            c_string_to_option_string(invalid, &StringDecoding::Strict),
+            Err(OsrmError::InvalidUtf8(_))
+        ));
+    }
 }
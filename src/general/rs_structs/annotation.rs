@@ -1,10 +1,13 @@
 use std::slice;
 
-use crate::general::c_structs::c_annotation::COsrmAnnotation;
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{general::c_structs::c_annotation::COsrmAnnotation, osrm_error::OsrmError, StringDecoding};
 
 use super::meta_data::MetaData;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Annotation {
     pub duration: Vec<f64>,
     pub distance: Vec<f64>,
@@ -15,9 +18,12 @@ pub struct Annotation {
     pub metadata: Option<MetaData>,
 }
 
-impl From<&COsrmAnnotation> for Annotation {
-    fn from(c_annotation: &COsrmAnnotation) -> Self {
-        Annotation {
+impl Annotation {
+    pub(crate) fn new(
+        c_annotation: &COsrmAnnotation,
+        string_decoding: &StringDecoding,
+    ) -> Result<Annotation, OsrmError> {
+        Ok(Annotation {
             duration: if c_annotation.duration != std::ptr::null_mut() {
                 unsafe {
                     slice::from_raw_parts(
@@ -85,10 +91,70 @@ impl From<&COsrmAnnotation> for Annotation {
                 Vec::new()
             },
             metadata: if c_annotation.metadata != std::ptr::null_mut() {
-                Option::from(unsafe { let data: MetaData = (&(*c_annotation.metadata)).into(); data })
+                Some(MetaData::new(unsafe { &(*c_annotation.metadata) }, string_decoding)?)
             } else {
                 None
             },
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut annotation = serde_json::json!({});
+        if let Some(metadata) = &self.metadata {
+            annotation["metadata"] = metadata.to_json();
+        }
+        if !self.nodes.is_empty() {
+            annotation["nodes"] = serde_json::json!(self.nodes);
+        }
+        if !self.datasources.is_empty() {
+            annotation["datasources"] = serde_json::json!(self.datasources);
+        }
+        if !self.weight.is_empty() {
+            annotation["weight"] = serde_json::json!(self.weight);
+        }
+        if !self.distance.is_empty() {
+            annotation["distance"] = serde_json::json!(self.distance);
         }
+        if !self.duration.is_empty() {
+            annotation["duration"] = serde_json::json!(self.duration);
+        }
+        if !self.speed.is_empty() {
+            annotation["speed"] = serde_json::json!(self.speed);
+        }
+        annotation
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Annotation, OsrmError> {
+        Ok(Annotation {
+            duration: json::opt_array_field(value, "duration")
+                .map(|values| json::f64_array(values))
+                .unwrap_or_default(),
+            distance: json::opt_array_field(value, "distance")
+                .map(|values| json::f64_array(values))
+                .unwrap_or_default(),
+            speed: json::opt_array_field(value, "speed")
+                .map(|values| json::f64_array(values))
+                .unwrap_or_default(),
+            weight: json::opt_array_field(value, "weight")
+                .map(|values| json::f64_array(values))
+                .unwrap_or_default(),
+            nodes: json::opt_array_field(value, "nodes")
+                .map(|values| values.iter().map(|node| node.as_i64().unwrap_or_default()).collect())
+                .unwrap_or_default(),
+            datasources: json::opt_array_field(value, "datasources")
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|datasource| datasource.as_i64().unwrap_or_default() as i32)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            metadata: match value.get("metadata") {
+                Some(metadata) if !metadata.is_null() => Some(MetaData::from_json(metadata)?),
+                _ => None,
+            },
+        })
     }
-}
\ No newline at end of file
+}
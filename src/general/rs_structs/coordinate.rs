@@ -1,6 +1,7 @@
 use crate::general::c_structs::c_coordinate::COsrmCoordinate;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinate {
     pub latitude: f64,
     pub longitude: f64,
@@ -33,3 +34,38 @@ impl From<&COsrmCoordinate> for Coordinate {
         }
     }
 }
+
+
+#[cfg(feature = "geo")]
+impl From<geo_types::Point<f64>> for Coordinate {
+    fn from(point: geo_types::Point<f64>) -> Self {
+        Coordinate {
+            latitude: point.y(),
+            longitude: point.x(),
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo_types::Coord<f64>> for Coordinate {
+    fn from(coord: geo_types::Coord<f64>) -> Self {
+        Coordinate {
+            latitude: coord.y,
+            longitude: coord.x,
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<Coordinate> for geo_types::Point<f64> {
+    fn from(coordinate: Coordinate) -> Self {
+        geo_types::Point::new(coordinate.longitude, coordinate.latitude)
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<&Coordinate> for geo_types::Point<f64> {
+    fn from(coordinate: &Coordinate) -> Self {
+        geo_types::Point::new(coordinate.longitude, coordinate.latitude)
+    }
+}
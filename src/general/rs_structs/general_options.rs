@@ -1,4 +1,4 @@
-use std::ffi::CString;
+use std::{ffi::CString, ops::Range, os::raw::c_char};
 
 use crate::general::{
     c_structs::{c_approach::Approach, c_bearing::Bearing},
@@ -24,24 +24,36 @@ pub trait GeneralOptionsTrait {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GeneralOptions {
     pub(crate) coordinate: Vec<COsrmCoordinate>,
     pub(crate) bearings: Option<Vec<Option<Bearing>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) bearings_t: Vec<*const Bearing>,
     pub(crate) radiuses: Option<Vec<Option<f64>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) radiuses_t: Vec<*const f64>,
     pub(crate) generate_hints: bool,
     pub(crate) skip_waypoints: bool,
     pub(crate) hints: Option<Vec<CString>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) hints_t: Vec<*const c_char>,
     pub(crate) approach: Option<Vec<Option<Approach>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) approach_t: Vec<*const Approach>,
     pub(crate) exclude: Option<Vec<CString>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) exclude_t: Vec<*const c_char>,
 }
 
+unsafe impl Send for GeneralOptions {}
+
 impl GeneralOptions {
-    pub fn new(coordinates: &Vec<Coordinate>) -> GeneralOptions {
+    pub fn new<C: Clone + Into<Coordinate>>(coordinates: &[C]) -> GeneralOptions {
         GeneralOptions {
-            coordinate: to_vec_ccoordinate(&coordinates),
+            coordinate: to_vec_ccoordinate(
+                &coordinates.iter().cloned().map(Into::into).collect(),
+            ),
             bearings: None,
             bearings_t: vec![],
             radiuses: None,
@@ -49,9 +61,36 @@ impl GeneralOptions {
             generate_hints: true,
             skip_waypoints: false,
             hints: None,
+            hints_t: vec![],
             approach: None,
             approach_t: vec![],
             exclude: None,
+            exclude_t: vec![],
+        }
+    }
+
+    // Keeps the coordinates in `range` together with their per-coordinate options.
+    pub(crate) fn slice(&self, range: Range<usize>) -> GeneralOptions {
+        fn part<T: Clone>(values: &Option<Vec<T>>, range: &Range<usize>) -> Option<Vec<T>> {
+            values
+                .as_ref()
+                .and_then(|values| values.get(range.clone()).map(<[T]>::to_vec))
+        }
+
+        GeneralOptions {
+            coordinate: self.coordinate[range.clone()].to_vec(),
+            bearings: part(&self.bearings, &range),
+            bearings_t: vec![],
+            radiuses: part(&self.radiuses, &range),
+            radiuses_t: vec![],
+            generate_hints: self.generate_hints,
+            skip_waypoints: self.skip_waypoints,
+            hints: part(&self.hints, &range),
+            hints_t: vec![],
+            approach: part(&self.approach, &range),
+            approach_t: vec![],
+            exclude: self.exclude.clone(),
+            exclude_t: vec![],
         }
     }
 }
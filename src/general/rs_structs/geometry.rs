@@ -0,0 +1,308 @@
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{osrm_error::OsrmError, route_api::GeometriesType};
+
+use super::coordinate::Coordinate;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Geometry {
+    Polyline(String),
+    Polyline6(String),
+    GeoJson(String),
+}
+
+impl Geometry {
+    pub fn new(encoded: String, geometries: &GeometriesType) -> Geometry {
+        match geometries {
+            GeometriesType::Polyline => Geometry::Polyline(encoded),
+            GeometriesType::Polyline6 => Geometry::Polyline6(encoded),
+            GeometriesType::GeoJSON => Geometry::GeoJson(encoded),
+        }
+    }
+
+    pub fn encode(coordinates: &[Coordinate], geometries: &GeometriesType) -> Geometry {
+        match geometries {
+            GeometriesType::Polyline => Geometry::Polyline(encode_polyline(coordinates, 5)),
+            GeometriesType::Polyline6 => Geometry::Polyline6(encode_polyline(coordinates, 6)),
+            GeometriesType::GeoJSON => Geometry::GeoJson(encode_geojson(coordinates)),
+        }
+    }
+
+    pub fn geometries_type(&self) -> GeometriesType {
+        match self {
+            Geometry::Polyline(_) => GeometriesType::Polyline,
+            Geometry::Polyline6(_) => GeometriesType::Polyline6,
+            Geometry::GeoJson(_) => GeometriesType::GeoJSON,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Geometry::Polyline(encoded)
+            | Geometry::Polyline6(encoded)
+            | Geometry::GeoJson(encoded) => encoded,
+        }
+    }
+
+    pub fn coordinates(&self) -> Result<Vec<Coordinate>, OsrmError> {
+        match self {
+            Geometry::Polyline(encoded) => decode_polyline(encoded, 5),
+            Geometry::Polyline6(encoded) => decode_polyline(encoded, 6),
+            Geometry::GeoJson(encoded) => decode_geojson(encoded),
+        }
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn to_line_string(&self) -> Result<geo_types::LineString<f64>, OsrmError> {
+        Ok(self
+            .coordinates()?
+            .into_iter()
+            .map(geo_types::Point::from)
+            .collect())
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn from_line_string(
+        line_string: &geo_types::LineString<f64>,
+        geometries: &GeometriesType,
+    ) -> Geometry {
+        Geometry::encode(
+            &line_string
+                .points()
+                .map(Coordinate::from)
+                .collect::<Vec<Coordinate>>(),
+            geometries,
+        )
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        match self {
+            Geometry::GeoJson(encoded) => serde_json::from_str(encoded)
+                .unwrap_or_else(|_| serde_json::Value::String(encoded.clone())),
+            Geometry::Polyline(encoded) | Geometry::Polyline6(encoded) => {
+                serde_json::Value::String(encoded.clone())
+            }
+        }
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        name: &str,
+        geometries: &GeometriesType,
+    ) -> Option<Geometry> {
+        match value.get(name) {
+            Some(serde_json::Value::Object(_)) => {
+                json::geometry_field(value, name).map(Geometry::GeoJson)
+            }
+            Some(serde_json::Value::String(encoded)) => Some(match geometries {
+                GeometriesType::Polyline6 => Geometry::Polyline6(encoded.clone()),
+                _ => Geometry::Polyline(encoded.clone()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub fn encode_polyline(coordinates: &[Coordinate], precision: u32) -> String {
+    let factor = 10f64.powi(precision as i32);
+    let mut encoded = String::new();
+    let mut previous = (0i64, 0i64);
+
+    for coordinate in coordinates {
+        let current = (
+            (coordinate.latitude * factor).round() as i64,
+            (coordinate.longitude * factor).round() as i64,
+        );
+        encode_value(current.0 - previous.0, &mut encoded);
+        encode_value(current.1 - previous.1, &mut encoded);
+        previous = current;
+    }
+
+    encoded
+}
+
+pub fn decode_polyline(encoded: &str, precision: u32) -> Result<Vec<Coordinate>, OsrmError> {
+    let factor = 10f64.powi(precision as i32);
+    let mut bytes = encoded.bytes();
+    let mut coordinates = Vec::new();
+    let (mut latitude, mut longitude) = (0i64, 0i64);
+
+    while let Some(delta) = decode_value(&mut bytes)? {
+        latitude += delta;
+        longitude += decode_value(&mut bytes)?.ok_or_else(|| {
+            OsrmError::InvalidValue(format!("polyline `{}` ends after a latitude", encoded))
+        })?;
+        coordinates.push(Coordinate::new(
+            latitude as f64 / factor,
+            longitude as f64 / factor,
+        ));
+    }
+
+    Ok(coordinates)
+}
+
+fn encode_value(value: i64, encoded: &mut String) {
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 };
+    while value >= 0x20 {
+        encoded.push((((value & 0x1f) | 0x20) as u8 + 63) as char);
+        value >>= 5;
+    }
+    encoded.push((value as u8 + 63) as char);
+}
+
+fn decode_value(bytes: &mut impl Iterator<Item = u8>) -> Result<Option<i64>, OsrmError> {
+    let mut result = 0i64;
+    let mut shift = 0;
+
+    loop {
+        let byte = match bytes.next() {
+            Some(byte) => byte,
+            None if shift == 0 => return Ok(None),
+            None => return Err(OsrmError::InvalidValue("polyline is truncated".to_string())),
+        };
+        if !(63..127).contains(&byte) || shift > 60 {
+            return Err(OsrmError::InvalidValue(format!(
+                "invalid polyline character `{}`",
+                byte as char
+            )));
+        }
+
+        let chunk = (byte - 63) as i64;
+        result |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            break;
+        }
+    }
+
+    Ok(Some(if result & 1 == 1 {
+        !(result >> 1)
+    } else {
+        result >> 1
+    }))
+}
+
+fn encode_geojson(coordinates: &[Coordinate]) -> String {
+    format!(
+        "{{\"type\":\"LineString\",\"coordinates\":[{}]}}",
+        coordinates
+            .iter()
+            .map(|coordinate| format!("[{},{}]", coordinate.longitude, coordinate.latitude))
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+fn decode_geojson(encoded: &str) -> Result<Vec<Coordinate>, OsrmError> {
+    let invalid = || OsrmError::InvalidValue(format!("invalid GeoJSON LineString `{}`", encoded));
+
+    let start = encoded.find("\"coordinates\"").ok_or_else(invalid)?;
+    let rest = &encoded[start + "\"coordinates\"".len()..];
+    let rest = &rest[rest.find('[').ok_or_else(invalid)?..];
+
+    let mut depth = 0;
+    let mut end = None;
+    for (index, letter) in rest.char_indices() {
+        match letter {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(index);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let numbers = rest[..end.ok_or_else(invalid)?]
+        .split(['[', ']', ','])
+        .map(str::trim)
+        .filter(|number| !number.is_empty())
+        .map(|number| number.parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<f64>, OsrmError>>()?;
+    if numbers.len() % 2 != 0 {
+        return Err(invalid());
+    }
+
+    Ok(numbers
+        .chunks(2)
+        .map(|pair| Coordinate::new(pair[1], pair[0]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "geo")]
+    use crate::route_api::route_request_builder::RouteRequestBuilder;
+    use crate::{general::rs_structs::coordinate::Coordinate, route_api::GeometriesType};
+
+    use super::{decode_polyline, encode_polyline, Geometry};
+
+    #[test]
+    fn polylines_round_trip() {
+        let coordinates = decode_polyline("_p~iF~ps|U_ulLnnqC_mqNvxq`@", 5).unwrap();
+        assert_eq!(coordinates.len(), 3);
+        assert_eq!(coordinates[0].latitude, 38.5);
+        assert_eq!(coordinates[0].longitude, -120.2);
+        assert_eq!(coordinates[2].latitude, 43.252);
+        assert_eq!(coordinates[2].longitude, -126.453);
+        assert_eq!(
+            encode_polyline(&coordinates, 5),
+            "_p~iF~ps|U_ulLnnqC_mqNvxq`@"
+        );
+
+        let geometry = Geometry::encode(&coordinates, &GeometriesType::Polyline6);
+        let decoded = geometry.coordinates().unwrap();
+        assert_eq!(decoded[1].latitude, 40.7);
+        assert_eq!(decoded[1].longitude, -120.95);
+
+        assert!(decode_polyline("_p~iF~ps|U_", 5).is_err());
+    }
+
+    #[test]
+    fn geojson_line_strings_are_decoded() {
+        let geometry = Geometry::new(
+            "{\"coordinates\":[[13.38886,52.517037],[13.388685,52.518119]],\"type\":\"LineString\"}"
+                .to_string(),
+            &GeometriesType::GeoJSON,
+        );
+        let coordinates = geometry.coordinates().unwrap();
+        assert_eq!(coordinates.len(), 2);
+        assert_eq!(coordinates[1].longitude, 13.388685);
+        assert_eq!(coordinates[1].latitude, 52.518119);
+
+        let encoded = Geometry::encode(&[Coordinate::new(52.5, 13.4)], &GeometriesType::GeoJSON);
+        assert_eq!(
+            encoded.as_str(),
+            "{\"type\":\"LineString\",\"coordinates\":[[13.4,52.5]]}"
+        );
+    }
+
+    #[cfg(feature = "geo")]
+    #[test]
+    fn geometries_convert_to_geo_types() {
+        let geometry = Geometry::Polyline("_p~iF~ps|U_ulLnnqC_mqNvxq`@".to_string());
+        let line_string = geometry.to_line_string().unwrap();
+        assert_eq!(line_string.0.len(), 3);
+        assert_eq!(line_string.0[0], geo_types::coord! { x: -120.2, y: 38.5 });
+        assert_eq!(
+            Geometry::from_line_string(&line_string, &GeometriesType::Polyline),
+            geometry
+        );
+
+        let request = RouteRequestBuilder::new(&[
+            geo_types::Point::new(13.38886, 52.517037),
+            geo_types::Point::new(13.397634, 52.529407),
+        ])
+        .build()
+        .unwrap();
+        let point: geo_types::Point<f64> =
+            Coordinate::from(&request.general_options.coordinate[1]).into();
+        assert_eq!(point, geo_types::Point::new(13.397634, 52.529407));
+    }
+}
@@ -1,11 +1,18 @@
 use std::slice;
 
-use crate::{Boolean, general::{Coordinate, c_string_to_string, c_structs::c_intersections::COsrmIntersections}};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_string, c_structs::c_intersections::COsrmIntersections, Coordinate},
+    osrm_error::OsrmError,
+    Boolean, StringDecoding,
+};
 
 use super::lanes::Lanes;
 
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Intersections {
     pub location: Coordinate,
     pub bearings: Vec<i32>,
@@ -16,9 +23,12 @@ pub struct Intersections {
     pub lanes: Vec<Lanes>,
 }
 
-impl From<&COsrmIntersections> for Intersections {
-    fn from(c_intersection: &COsrmIntersections) -> Self {
-        Intersections {
+impl Intersections {
+    pub(crate) fn new(
+        c_intersection: &COsrmIntersections,
+        string_decoding: &StringDecoding,
+    ) -> Result<Intersections, OsrmError> {
+        Ok(Intersections {
             location: (&c_intersection.location).into(),
             intersection_in: c_intersection.intersection_in,
             intersection_out: c_intersection.intersection_out,
@@ -41,8 +51,8 @@ impl From<&COsrmIntersections> for Intersections {
                     )
                 }
                 .iter()
-                .map(|class| c_string_to_string(*class))
-                .collect()
+                .map(|class| c_string_to_string(*class, string_decoding))
+                .collect::<Result<Vec<String>, OsrmError>>()?
             } else {
                 Vec::new()
             },
@@ -67,11 +77,68 @@ impl From<&COsrmIntersections> for Intersections {
                     )
                 }
                 .iter()
-                .map(|lane| lane.into())
-                .collect()
+                .map(|lane| Lanes::new(lane, string_decoding))
+                .collect::<Result<Vec<Lanes>, OsrmError>>()?
             } else {
                 Vec::new()
             },
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut intersection = serde_json::json!({});
+        if self.intersection_out >= 0 {
+            intersection["out"] = serde_json::json!(self.intersection_out);
+        }
+        if self.intersection_in >= 0 {
+            intersection["in"] = serde_json::json!(self.intersection_in);
+        }
+        intersection["entry"] = serde_json::json!(self.entry);
+        intersection["bearings"] = serde_json::json!(self.bearings);
+        intersection["location"] = json::coordinate_value(&self.location);
+        if !self.lanes.is_empty() {
+            intersection["lanes"] =
+                serde_json::Value::Array(self.lanes.iter().map(Lanes::to_json).collect());
         }
+        if !self.classes.is_empty() {
+            intersection["classes"] = serde_json::json!(self.classes);
+        }
+        intersection
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Intersections, OsrmError> {
+        Ok(Intersections {
+            location: json::coordinate_field(value, "location")?,
+            bearings: json::opt_array_field(value, "bearings")
+                .map(|bearings| {
+                    bearings
+                        .iter()
+                        .map(|bearing| bearing.as_i64().unwrap_or_default() as i32)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            classes: json::opt_array_field(value, "classes")
+                .map(|classes| {
+                    classes
+                        .iter()
+                        .map(|class| class.as_str().unwrap_or_default().to_owned())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            entry: json::opt_array_field(value, "entry")
+                .map(|entry| entry.iter().map(|entry| entry.as_bool().unwrap_or(false)).collect())
+                .unwrap_or_default(),
+            intersection_in: json::opt_i32_field(value, "in").unwrap_or(-1),
+            intersection_out: json::opt_i32_field(value, "out").unwrap_or(-1),
+            lanes: match json::opt_array_field(value, "lanes") {
+                Some(lanes) => lanes
+                    .iter()
+                    .map(Lanes::from_json)
+                    .collect::<Result<Vec<Lanes>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
@@ -1,23 +1,56 @@
 use std::slice;
 
-use crate::{Boolean, general::{c_string_to_string, c_structs::c_lanes::COsrmLanes}};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_string, c_structs::c_lanes::COsrmLanes},
+    osrm_error::OsrmError,
+    Boolean, StringDecoding,
+};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lanes {
     pub indications: Vec<String>,
     pub valid: bool,
 }
 
-impl From<&COsrmLanes> for Lanes {
-    fn from(c_lanes: &COsrmLanes) -> Self {
-        Lanes {
-            indications: unsafe {
-                slice::from_raw_parts(c_lanes.indications, c_lanes.number_of_indications as usize)
-            }
-            .iter()
-            .map(|indication| c_string_to_string(*indication))
-            .collect(),
+impl Lanes {
+    pub(crate) fn new(
+        c_lanes: &COsrmLanes,
+        string_decoding: &StringDecoding,
+    ) -> Result<Lanes, OsrmError> {
+        Ok(Lanes {
+            indications: if c_lanes.indications != std::ptr::null_mut() {
+                unsafe {
+                    slice::from_raw_parts(
+                        c_lanes.indications,
+                        c_lanes.number_of_indications as usize,
+                    )
+                }
+                .iter()
+                .map(|indication| c_string_to_string(*indication, string_decoding))
+                .collect::<Result<Vec<String>, OsrmError>>()?
+            } else {
+                Vec::new()
+            },
             valid: c_lanes.valid == Boolean::TRUE,
-        }
+        })
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "valid": self.valid, "indications": self.indications })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Lanes, OsrmError> {
+        Ok(Lanes {
+            indications: json::array_field(value, "indications")?
+                .iter()
+                .map(|indication| indication.as_str().unwrap_or_default().to_owned())
+                .collect(),
+            valid: value.get("valid").and_then(|valid| valid.as_bool()).unwrap_or(false),
+        })
+    }
+}
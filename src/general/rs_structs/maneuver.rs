@@ -1,6 +1,16 @@
-use crate::general::{Coordinate, c_string_to_option_string, c_string_to_string, c_structs::c_maneuver::COsrmManeuver};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{
+        c_string_to_option_string, c_string_to_string, c_structs::c_maneuver::COsrmManeuver,
+        Coordinate,
+    },
+    osrm_error::OsrmError,
+    StringDecoding,
+};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Maneuver {
     pub bearing_before: i32,
     pub bearing_after: i32,
@@ -10,15 +20,45 @@ pub struct Maneuver {
     pub exit: i32,
 }
 
-impl From<&COsrmManeuver> for Maneuver {
-    fn from(c_maneuver: &COsrmManeuver) -> Self {
-        Maneuver {
+impl Maneuver {
+    pub(crate) fn new(
+        c_maneuver: &COsrmManeuver,
+        string_decoding: &StringDecoding,
+    ) -> Result<Maneuver, OsrmError> {
+        Ok(Maneuver {
             bearing_before: c_maneuver.bearing_before,
             bearing_after: c_maneuver.bearing_after,
             coordinate: (&c_maneuver.coordinate).into(),
-            maneuver_type: c_string_to_string(c_maneuver.maneuver_type),
-            modifer: c_string_to_option_string(c_maneuver.modifer),
+            maneuver_type: c_string_to_string(c_maneuver.maneuver_type, string_decoding)?,
+            modifer: c_string_to_option_string(c_maneuver.modifer, string_decoding)?,
             exit: c_maneuver.exit,
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut maneuver = serde_json::json!({
+            "bearing_after": self.bearing_after,
+            "bearing_before": self.bearing_before,
+            "location": json::coordinate_value(&self.coordinate),
+        });
+        json::insert_opt_string(&mut maneuver, "modifier", &self.modifer);
+        maneuver["type"] = serde_json::json!(self.maneuver_type);
+        if self.exit > 0 {
+            maneuver["exit"] = serde_json::json!(self.exit);
         }
+        maneuver
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Maneuver, OsrmError> {
+        Ok(Maneuver {
+            bearing_before: json::opt_i32_field(value, "bearing_before").unwrap_or_default(),
+            bearing_after: json::opt_i32_field(value, "bearing_after").unwrap_or_default(),
+            coordinate: json::coordinate_field(value, "location")?,
+            maneuver_type: json::string_field(value, "type")?,
+            modifer: json::opt_string_field(value, "modifier"),
+            exit: json::opt_i32_field(value, "exit").unwrap_or_default(),
+        })
+    }
+}
@@ -1,30 +1,54 @@
 use std::slice;
 
-use crate::general::{c_string_to_string, c_structs::c_meta_data::COsrmMetaData};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_string, c_structs::c_meta_data::COsrmMetaData},
+    osrm_error::OsrmError,
+    StringDecoding,
+};
 
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetaData {
     datasource_names: Vec<String>,
 }
 
-impl From<&COsrmMetaData> for MetaData {
-    fn from(c_meta_data: &COsrmMetaData) -> Self {
-        MetaData {
+impl MetaData {
+    pub(crate) fn new(
+        c_meta_data: &COsrmMetaData,
+        string_decoding: &StringDecoding,
+    ) -> Result<MetaData, OsrmError> {
+        Ok(MetaData {
             datasource_names: if c_meta_data.datasource_names != std::ptr::null_mut() {
                 unsafe {
                     slice::from_raw_parts(
                         c_meta_data.datasource_names,
                         c_meta_data.number_of_datasource_names as usize,
                     )
-                    .to_vec()
                 }
                 .iter()
-                .map(|data| c_string_to_string(*data))
-                .collect()
+                .map(|data| c_string_to_string(*data, string_decoding))
+                .collect::<Result<Vec<String>, OsrmError>>()?
             } else {
                 Vec::new()
             },
-        }
+        })
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "datasource_names": self.datasource_names })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<MetaData, OsrmError> {
+        Ok(MetaData {
+            datasource_names: json::array_field(value, "datasource_names")?
+                .iter()
+                .map(|name| name.as_str().unwrap_or_default().to_owned())
+                .collect(),
+        })
+    }
+}
@@ -8,4 +8,4 @@ pub mod meta_data;
 pub mod step;
 pub mod maneuver;
 pub mod lanes;
-pub mod coordinate;
\ No newline at end of file
+pub mod coordinate;pub mod geometry;
@@ -1,12 +1,61 @@
-use super::route_leg::RouteLeg;
+#[cfg(feature = "json")]
+use crate::{general::json, route_api::GeometriesType};
+#[cfg(any(feature = "json", feature = "geo"))]
+use crate::osrm_error::OsrmError;
+
+use super::{geometry::Geometry, route_leg::RouteLeg};
 
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Route {
     pub duration: f64,
     pub distance: f64,
     pub weight_name: Option<String>,
     pub weight: f64,
-    pub geometry: Option<String>,
+    pub geometry: Option<Geometry>,
     pub legs: Vec<RouteLeg>,
-}
\ No newline at end of file
+}
+
+impl Route {
+    #[cfg(feature = "geo")]
+    pub fn line_string(&self) -> Result<Option<geo_types::LineString<f64>>, OsrmError> {
+        self.geometry.as_ref().map(Geometry::to_line_string).transpose()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Route {
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut route = serde_json::json!({});
+        if let Some(geometry) = &self.geometry {
+            route["geometry"] = geometry.to_json();
+        }
+        route["legs"] = serde_json::Value::Array(self.legs.iter().map(RouteLeg::to_json).collect());
+        json::insert_opt_string(&mut route, "weight_name", &self.weight_name);
+        route["weight"] = serde_json::json!(self.weight);
+        route["duration"] = serde_json::json!(self.duration);
+        route["distance"] = serde_json::json!(self.distance);
+        route
+    }
+
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<Route, OsrmError> {
+        Ok(Route {
+            duration: json::f64_field(value, "duration")?,
+            distance: json::f64_field(value, "distance")?,
+            weight_name: json::opt_string_field(value, "weight_name"),
+            weight: json::opt_f64_field(value, "weight").unwrap_or_default(),
+            geometry: Geometry::from_json(value, "geometry", geometries),
+            legs: match json::opt_array_field(value, "legs") {
+                Some(legs) => legs
+                    .iter()
+                    .map(|leg| RouteLeg::from_json(leg, geometries))
+                    .collect::<Result<Vec<RouteLeg>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
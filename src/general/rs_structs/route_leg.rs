@@ -1,10 +1,18 @@
 use std::slice;
 
-use crate::general::{c_string_to_option_string, c_structs::c_route_leg::COsrmRouteLeg};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_option_string, c_structs::c_route_leg::COsrmRouteLeg},
+    osrm_error::OsrmError,
+    route_api::GeometriesType,
+    StringDecoding,
+};
 
 use super::{annotation::Annotation, step::Step};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RouteLeg {
     pub annotation: Option<Annotation>,
     pub duration: f64,
@@ -14,28 +22,71 @@ pub struct RouteLeg {
     pub steps: Vec<Step>,
 }
 
-impl From<&COsrmRouteLeg> for RouteLeg {
-    fn from(leg: &COsrmRouteLeg) -> Self {
-        RouteLeg {
+impl RouteLeg {
+    pub(crate) fn new(
+        leg: &COsrmRouteLeg,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<RouteLeg, OsrmError> {
+        Ok(RouteLeg {
             duration: leg.duration,
-            summary: c_string_to_option_string(leg.summary),
+            summary: c_string_to_option_string(leg.summary, string_decoding)?,
             weight: leg.weight,
             distance: leg.distance,
             annotation: if leg.annotation != std::ptr::null_mut() {
-                let annotation: Annotation = unsafe { (&(*leg.annotation)).into() };
-
-                annotation.into()
+                Some(Annotation::new(unsafe { &(*leg.annotation) }, string_decoding)?)
             } else {
                 None
             },
             steps: if leg.steps != std::ptr::null_mut() {
-                unsafe { slice::from_raw_parts(leg.steps, leg.number_of_steps as usize).to_vec() }
+                unsafe { slice::from_raw_parts(leg.steps, leg.number_of_steps as usize) }
                     .iter()
-                    .map(|step| step.into())
-                    .collect()
+                    .map(|step| Step::new(step, string_decoding, geometries))
+                    .collect::<Result<Vec<Step>, OsrmError>>()?
             } else {
                 Vec::new()
             },
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut leg = serde_json::json!({
+            "steps": self.steps.iter().map(Step::to_json).collect::<Vec<serde_json::Value>>(),
+            "summary": self.summary.clone().unwrap_or_default(),
+            "weight": self.weight,
+            "duration": self.duration,
+            "distance": self.distance,
+        });
+        if let Some(annotation) = &self.annotation {
+            leg["annotation"] = annotation.to_json();
         }
+        leg
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<RouteLeg, OsrmError> {
+        Ok(RouteLeg {
+            annotation: match value.get("annotation") {
+                Some(annotation) if !annotation.is_null() => {
+                    Some(Annotation::from_json(annotation)?)
+                }
+                _ => None,
+            },
+            duration: json::f64_field(value, "duration")?,
+            summary: json::opt_string_field(value, "summary"),
+            weight: json::opt_f64_field(value, "weight").unwrap_or_default(),
+            distance: json::f64_field(value, "distance")?,
+            steps: match json::opt_array_field(value, "steps") {
+                Some(steps) => steps
+                    .iter()
+                    .map(|step| Step::from_json(step, geometries))
+                    .collect::<Result<Vec<Step>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+        })
     }
 }
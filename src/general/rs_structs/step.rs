@@ -1,15 +1,23 @@
 use std::{os::raw::c_double, slice};
 
-use crate::general::{c_string_to_option_string, c_structs::c_step::COsrmStep};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_option_string, c_structs::c_step::COsrmStep},
+    osrm_error::OsrmError,
+    route_api::GeometriesType,
+    StringDecoding,
+};
 
-use super::{intersections::Intersections, maneuver::Maneuver};
+use super::{geometry::Geometry, intersections::Intersections, maneuver::Maneuver};
 
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Step {
     pub distance: f64,
     pub duration: f64,
-    pub geometry: Option<String>,
+    pub geometry: Option<Geometry>,
     pub weight: c_double,
     pub name: Option<String>,
     pub reference: Option<String>,
@@ -23,23 +31,31 @@ pub struct Step {
     pub driving_side: Option<String>,
 }
 
-impl From<&COsrmStep> for Step {
-    fn from(c_step: &COsrmStep) -> Self {
-        Step {
+impl Step {
+    pub(crate) fn new(
+        c_step: &COsrmStep,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<Step, OsrmError> {
+        Ok(Step {
             distance: c_step.distance,
             duration: c_step.duration,
-            geometry: c_string_to_option_string(c_step.geometry),
+            geometry: c_string_to_option_string(c_step.geometry, string_decoding)?
+                .map(|geometry| Geometry::new(geometry, geometries)),
             weight: c_step.weight,
-            name: c_string_to_option_string(c_step.name),
-            reference: c_string_to_option_string(c_step.reference),
-            pronunciation: c_string_to_option_string(c_step.pronunciation),
+            name: c_string_to_option_string(c_step.name, string_decoding)?,
+            reference: c_string_to_option_string(c_step.reference, string_decoding)?,
+            pronunciation: c_string_to_option_string(c_step.pronunciation, string_decoding)?,
             exits: c_step.exits,
-            mode: c_string_to_option_string(c_step.mode),
-            rotary_name: c_string_to_option_string(c_step.rotary_name),
-            rotary_pronunciation: c_string_to_option_string(c_step.rotary_pronunciation),
-            driving_side: c_string_to_option_string(c_step.driving_side),
+            mode: c_string_to_option_string(c_step.mode, string_decoding)?,
+            rotary_name: c_string_to_option_string(c_step.rotary_name, string_decoding)?,
+            rotary_pronunciation: c_string_to_option_string(
+                c_step.rotary_pronunciation,
+                string_decoding,
+            )?,
+            driving_side: c_string_to_option_string(c_step.driving_side, string_decoding)?,
             maneuver: if c_step.metadata != std::ptr::null_mut() {
-                unsafe {let maneuver: Maneuver = (&(*c_step.metadata)).into(); maneuver }.into()
+                Some(Maneuver::new(unsafe { &(*c_step.metadata) }, string_decoding)?)
             } else {
                 None
             },
@@ -49,14 +65,91 @@ impl From<&COsrmStep> for Step {
                         c_step.intersections,
                         c_step.number_of_intersections as usize,
                     )
-                    .iter()
-                    .map(|intersection| intersection.into())
-                    .collect()
                 }
+                .iter()
+                .map(|intersection| Intersections::new(intersection, string_decoding))
+                .collect::<Result<Vec<Intersections>, OsrmError>>()?
             } else {
                 Vec::new()
             },
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut step = serde_json::json!({
+            "geometry": self
+                .geometry
+                .as_ref()
+                .map(Geometry::to_json)
+                .unwrap_or(serde_json::Value::Null),
+        });
+        if let Some(maneuver) = &self.maneuver {
+            step["maneuver"] = maneuver.to_json();
         }
+        step["mode"] = serde_json::json!(self.mode.clone().unwrap_or_default());
+        step["driving_side"] = serde_json::json!(self.driving_side.clone().unwrap_or_default());
+        step["name"] = serde_json::json!(self.name.clone().unwrap_or_default());
+        step["intersections"] = serde_json::Value::Array(
+            self.intersections
+                .iter()
+                .map(Intersections::to_json)
+                .collect(),
+        );
+        step["weight"] = serde_json::json!(self.weight);
+        step["duration"] = serde_json::json!(self.duration);
+        step["distance"] = serde_json::json!(self.distance);
+        json::insert_non_empty_string(&mut step, "ref", &self.reference);
+        json::insert_non_empty_string(&mut step, "pronunciation", &self.pronunciation);
+        if self.exits > 0 {
+            step["exits"] = serde_json::json!(self.exits.to_string());
+        }
+        json::insert_non_empty_string(&mut step, "rotary_name", &self.rotary_name);
+        json::insert_non_empty_string(
+            &mut step,
+            "rotary_pronunciation",
+            &self.rotary_pronunciation,
+        );
+        step
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<Step, OsrmError> {
+        Ok(Step {
+            distance: json::f64_field(value, "distance")?,
+            duration: json::f64_field(value, "duration")?,
+            geometry: Geometry::from_json(value, "geometry", geometries),
+            weight: json::opt_f64_field(value, "weight").unwrap_or_default(),
+            name: json::opt_string_field(value, "name"),
+            reference: json::opt_string_field(value, "ref"),
+            pronunciation: json::opt_string_field(value, "pronunciation"),
+            exits: json::opt_string_field(value, "exits")
+                .and_then(|exits| exits.split(';').next().and_then(|exit| exit.parse().ok()))
+                .or_else(|| json::opt_i32_field(value, "exits"))
+                .unwrap_or_default(),
+            mode: json::opt_string_field(value, "mode"),
+            maneuver: match value.get("maneuver") {
+                Some(maneuver) if !maneuver.is_null() => Some(Maneuver::from_json(maneuver)?),
+                _ => None,
+            },
+            intersections: match json::opt_array_field(value, "intersections") {
+                Some(intersections) => intersections
+                    .iter()
+                    .map(Intersections::from_json)
+                    .collect::<Result<Vec<Intersections>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+            rotary_name: json::opt_string_field(value, "rotary_name"),
+            rotary_pronunciation: json::opt_string_field(value, "rotary_pronunciation"),
+            driving_side: json::opt_string_field(value, "driving_side"),
+        })
     }
-}
 
+    #[cfg(feature = "geo")]
+    pub fn line_string(&self) -> Result<Option<geo_types::LineString<f64>>, OsrmError> {
+        self.geometry.as_ref().map(Geometry::to_line_string).transpose()
+    }
+}
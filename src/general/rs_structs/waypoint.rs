@@ -1,8 +1,13 @@
-use std::{ptr::null};
-
-use crate::general::{c_string_to_string, c_structs::c_waypoint::CWaypoint};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_option_string, c_string_to_string, c_structs::c_waypoint::CWaypoint},
+    osrm_error::OsrmError,
+    StringDecoding,
+};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Waypoint {
     pub hint: Option<String>,
     pub distance: f64,
@@ -10,18 +15,41 @@ pub struct Waypoint {
     pub location: [f64; 2],
 }
 
-impl From<&CWaypoint> for Waypoint {
-    fn from(c_waypoint: &CWaypoint) -> Self {
-        let mut hint: Option<String> = None;
-        if c_waypoint.hint != null() {
-            hint = Option::from(c_string_to_string(c_waypoint.hint));
-        }
-
-        Waypoint {
-            hint,
+impl Waypoint {
+    pub(crate) fn new(
+        c_waypoint: &CWaypoint,
+        string_decoding: &StringDecoding,
+    ) -> Result<Waypoint, OsrmError> {
+        Ok(Waypoint {
+            hint: c_string_to_option_string(c_waypoint.hint, string_decoding)?,
             distance: c_waypoint.distance,
-            name: c_string_to_string(c_waypoint.name),
+            name: c_string_to_string(c_waypoint.name, string_decoding)?,
             location: c_waypoint.location,
-        }
+        })
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut waypoint = serde_json::json!({});
+        json::insert_opt_string(&mut waypoint, "hint", &self.hint);
+        waypoint["distance"] = serde_json::json!(self.distance);
+        waypoint["name"] = serde_json::json!(self.name);
+        waypoint["location"] = serde_json::json!(self.location);
+        waypoint
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Waypoint, OsrmError> {
+        Ok(Waypoint {
+            hint: json::opt_string_field(value, "hint"),
+            distance: json::opt_f64_field(value, "distance").unwrap_or_default(),
+            name: json::opt_string_field(value, "name").unwrap_or_default(),
+            location: json::location_field(value, "location")?,
+        })
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn point(&self) -> geo_types::Point<f64> {
+        geo_types::Point::new(self.location[0], self.location[1])
+    }
+}
@@ -0,0 +1,478 @@
+use core::fmt::Display;
+use std::{error::Error, fmt};
+
+use crate::{
+    general::rs_structs::general_options::GeneralOptions,
+    osrm_error::OsrmError,
+    trip_api::{trip_end, trip_start},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    TooFewCoordinates {
+        minimum: usize,
+        actual: usize,
+    },
+    TooManyCoordinates {
+        limit: usize,
+        actual: usize,
+    },
+    TableTooBig {
+        limit: usize,
+        sources: usize,
+        destinations: usize,
+    },
+    TooManyResults {
+        field: &'static str,
+        limit: usize,
+        actual: usize,
+    },
+    LengthMismatch {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    BearingOutOfRange {
+        index: usize,
+        bearing: i16,
+        range: i16,
+    },
+    RadiusOutOfRange {
+        index: usize,
+        radius: f64,
+        limit: Option<f64>,
+    },
+    IndexOutOfRange {
+        field: &'static str,
+        position: usize,
+        index: i64,
+        len: usize,
+    },
+    InvalidWaypoints(&'static str),
+    InvalidValue {
+        field: &'static str,
+        value: f64,
+    },
+    UnsupportedTrip {
+        roundtrip: bool,
+        source: trip_start,
+        destination: trip_end,
+    },
+    NonMonotonicTimestamps {
+        index: usize,
+    },
+}
+
+impl ValidationError {
+    pub fn code(&self) -> &str {
+        match self {
+            ValidationError::TooManyCoordinates { .. }
+            | ValidationError::TableTooBig { .. }
+            | ValidationError::TooManyResults { .. }
+            | ValidationError::RadiusOutOfRange { limit: Some(_), .. } => "TooBig",
+            ValidationError::UnsupportedTrip { .. } => "NotImplemented",
+            ValidationError::InvalidValue { .. }
+            | ValidationError::NonMonotonicTimestamps { .. } => "InvalidValue",
+            _ => "InvalidOptions",
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::TooFewCoordinates { minimum, actual } => write!(
+                f,
+                "At least {} coordinates are required, got {}",
+                minimum, actual
+            ),
+            ValidationError::TooManyCoordinates { limit, actual } => write!(
+                f,
+                "Number of coordinates {} exceeds the limit of {}",
+                actual, limit
+            ),
+            ValidationError::TableTooBig {
+                limit,
+                sources,
+                destinations,
+            } => write!(
+                f,
+                "Table of {}x{} exceeds the limit of {}x{}",
+                sources, destinations, limit, limit
+            ),
+            ValidationError::TooManyResults {
+                field,
+                limit,
+                actual,
+            } => write!(f, "{} of {} exceeds the limit of {}", field, actual, limit),
+            ValidationError::LengthMismatch {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} has {} entries, expected one per coordinate ({})",
+                field, actual, expected
+            ),
+            ValidationError::BearingOutOfRange {
+                index,
+                bearing,
+                range,
+            } => write!(
+                f,
+                "Bearing {} is {},{}; bearings must be within 0-360 and ranges within 0-180",
+                index, bearing, range
+            ),
+            ValidationError::RadiusOutOfRange {
+                index,
+                radius,
+                limit: Some(limit),
+            } => write!(
+                f,
+                "Radius {} is {}, larger than the limit of {}",
+                index, radius, limit
+            ),
+            ValidationError::RadiusOutOfRange { index, radius, .. } => {
+                write!(
+                    f,
+                    "Radius {} is {}, radiuses must not be negative",
+                    index, radius
+                )
+            }
+            ValidationError::IndexOutOfRange {
+                field,
+                position,
+                index,
+                len,
+            } => write!(
+                f,
+                "{} entry {} is {}, expected an index below {}",
+                field, position, index, len
+            ),
+            ValidationError::InvalidWaypoints(message) => write!(f, "{}", message),
+            ValidationError::InvalidValue { field, value } => {
+                write!(f, "{} of {} is not valid", field, value)
+            }
+            ValidationError::UnsupportedTrip {
+                roundtrip,
+                source,
+                destination,
+            } => write!(
+                f,
+                "Trips with roundtrip={}, source={:?} and destination={:?} are not supported",
+                roundtrip, source, destination
+            ),
+            ValidationError::NonMonotonicTimestamps { index } => write!(
+                f,
+                "Timestamp {} is earlier than the one before; timestamps must not decrease",
+                index
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+impl From<ValidationError> for OsrmError {
+    fn from(error: ValidationError) -> OsrmError {
+        OsrmError::from_code(Some(error.code().to_string()), Some(error.to_string()))
+    }
+}
+
+pub(crate) fn check_general_options(
+    options: &GeneralOptions,
+    minimum: usize,
+) -> Result<(), ValidationError> {
+    let count = options.coordinate.len();
+    if count < minimum {
+        return Err(ValidationError::TooFewCoordinates {
+            minimum,
+            actual: count,
+        });
+    }
+
+    check_length("bearings", count, options.bearings.as_ref().map(Vec::len))?;
+    check_length("radiuses", count, options.radiuses.as_ref().map(Vec::len))?;
+    check_length("hints", count, options.hints.as_ref().map(Vec::len))?;
+    check_length("approaches", count, options.approach.as_ref().map(Vec::len))?;
+
+    for (index, bearing) in options.bearings.iter().flatten().enumerate() {
+        if let Some(bearing) = bearing {
+            if !(0..=360).contains(&bearing.bearing) || !(0..=180).contains(&bearing.range) {
+                return Err(ValidationError::BearingOutOfRange {
+                    index,
+                    bearing: bearing.bearing,
+                    range: bearing.range,
+                });
+            }
+        }
+    }
+    check_radiuses(options, -1.0)
+}
+
+// `limit` is the engine's maximum number of coordinates; zero or negative means unlimited.
+pub(crate) fn check_coordinate_limit(
+    options: &GeneralOptions,
+    limit: i32,
+) -> Result<(), ValidationError> {
+    let count = options.coordinate.len();
+    if limit > 0 && count > limit as usize {
+        return Err(ValidationError::TooManyCoordinates {
+            limit: limit as usize,
+            actual: count,
+        });
+    }
+
+    Ok(())
+}
+
+// `limit` is the engine's maximum radius; zero or negative means unlimited.
+pub(crate) fn check_radiuses(options: &GeneralOptions, limit: f64) -> Result<(), ValidationError> {
+    for (index, radius) in options.radiuses.iter().flatten().enumerate() {
+        if let Some(radius) = *radius {
+            if radius.is_nan() || radius < 0.0 {
+                return Err(ValidationError::RadiusOutOfRange {
+                    index,
+                    radius,
+                    limit: None,
+                });
+            }
+            if limit > 0.0 && radius > limit {
+                return Err(ValidationError::RadiusOutOfRange {
+                    index,
+                    radius,
+                    limit: Some(limit),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_length(
+    field: &'static str,
+    expected: usize,
+    actual: Option<usize>,
+) -> Result<(), ValidationError> {
+    match actual {
+        Some(actual) if actual != expected => Err(ValidationError::LengthMismatch {
+            field,
+            expected,
+            actual,
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_indices(
+    field: &'static str,
+    indices: &[i64],
+    len: usize,
+) -> Result<(), ValidationError> {
+    for (position, &index) in indices.iter().enumerate() {
+        if index < 0 || index >= len as i64 {
+            return Err(ValidationError::IndexOutOfRange {
+                field,
+                position,
+                index,
+                len,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Route and match waypoints must start at the first and end at the last coordinate, in order.
+pub(crate) fn check_waypoints(indices: &[i64], len: usize) -> Result<(), ValidationError> {
+    check_indices("waypoints", indices, len)?;
+    if indices.first() != Some(&0) || indices.last() != Some(&(len as i64 - 1)) {
+        return Err(ValidationError::InvalidWaypoints(
+            "First and last coordinates must be specified as waypoints",
+        ));
+    }
+    if indices.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(ValidationError::InvalidWaypoints(
+            "Waypoints must be given in increasing order",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::{
+        engine_config::engine_limits::EngineLimits,
+        general::{
+            c_structs::c_bearing::Bearing,
+            rs_structs::{coordinate::Coordinate, general_options::GeneralOptionsTrait},
+        },
+        match_api::match_request_builder::MatchRequestBuilder,
+        osrm_error::OsrmError,
+        route_api::route_request_builder::RouteRequestBuilder,
+        table_api::table_request_builder::TableRequestBuilder,
+        trip_api::{trip_end, trip_request_builder::TripRequestBuilder, trip_start},
+    };
+
+    use super::ValidationError;
+
+    fn coordinates(count: usize) -> Vec<Coordinate> {
+        (0..count)
+            .map(|index| Coordinate::new(13.0 + index as f64 * 0.01, 52.5))
+            .collect()
+    }
+
+    #[test]
+    fn builders_reject_invalid_requests() {
+        let mut limits = EngineLimits::unlimited();
+        limits.max_locations_viaroute = 3;
+        let error = RouteRequestBuilder::new(&coordinates(4))
+            .set_limits(&limits)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            ValidationError::TooManyCoordinates {
+                limit: 3,
+                actual: 4
+            }
+        );
+        assert_eq!(OsrmError::from(error).code(), "TooBig");
+        let request = RouteRequestBuilder::new(&coordinates(4)).build().unwrap();
+        assert_eq!(
+            request.check_limits(&limits),
+            Err(ValidationError::TooManyCoordinates {
+                limit: 3,
+                actual: 4
+            })
+        );
+
+        assert_eq!(
+            RouteRequestBuilder::new(&coordinates(3))
+                .set_radiuses(Some(vec![Some(5.0), None]))
+                .build()
+                .err()
+                .unwrap(),
+            ValidationError::LengthMismatch {
+                field: "radiuses",
+                expected: 3,
+                actual: 2
+            }
+        );
+        assert!(RouteRequestBuilder::new(&coordinates(2))
+            .set_radiuses(Some(vec![Some(0.0), None]))
+            .build()
+            .is_ok());
+        let error = RouteRequestBuilder::new(&coordinates(2))
+            .set_radiuses(Some(vec![Some(-1.0), None]))
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Radius 0 is -1, radiuses must not be negative"
+        );
+        assert_eq!(
+            RouteRequestBuilder::new(&coordinates(2))
+                .set_bearings(Some(vec![
+                    None,
+                    Some(Bearing {
+                        bearing: 90,
+                        range: 270
+                    })
+                ]))
+                .build()
+                .err()
+                .unwrap(),
+            ValidationError::BearingOutOfRange {
+                index: 1,
+                bearing: 90,
+                range: 270
+            }
+        );
+        assert!(RouteRequestBuilder::new(&coordinates(3))
+            .set_waypoints(Some(vec![0, 1]))
+            .build()
+            .is_err());
+
+        let error = TripRequestBuilder::new(&coordinates(3))
+            .set_roundtrip(false)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            ValidationError::UnsupportedTrip {
+                roundtrip: false,
+                source: trip_start::StartAny,
+                destination: trip_end::EndAny
+            }
+        );
+        assert_eq!(OsrmError::from(error).code(), "NotImplemented");
+        assert!(TripRequestBuilder::new(&coordinates(3))
+            .set_roundtrip(false)
+            .set_sources(trip_start::First)
+            .set_destinations(trip_end::Last)
+            .build()
+            .is_ok());
+
+        assert_eq!(
+            MatchRequestBuilder::new(&coordinates(3))
+                .set_timestamps(Some(vec![10, 20, 15]))
+                .build()
+                .err()
+                .unwrap(),
+            ValidationError::NonMonotonicTimestamps { index: 2 }
+        );
+        let after_2038 = UNIX_EPOCH + Duration::from_secs(4_102_444_800);
+        let request = MatchRequestBuilder::new(&coordinates(2))
+            .set_system_timestamps(Some(vec![after_2038, after_2038 + Duration::from_secs(5)]))
+            .build()
+            .ok()
+            .unwrap();
+        assert_eq!(request.timestamps, Some(vec![4_102_444_800, 4_102_444_805]));
+        assert_eq!(request.c_timestamps().err().unwrap().code(), "InvalidValue");
+        let mut request = request;
+        request.timestamps = Some(vec![10]);
+        assert_eq!(
+            request.c_timestamps().err().unwrap().code(),
+            "InvalidOptions"
+        );
+        assert_eq!(
+            MatchRequestBuilder::new(&coordinates(2))
+                .set_system_timestamps(Some(vec![after_2038, UNIX_EPOCH]))
+                .build()
+                .err()
+                .unwrap(),
+            ValidationError::NonMonotonicTimestamps { index: 1 }
+        );
+
+        limits.max_locations_distance_table = 2;
+        assert_eq!(
+            TableRequestBuilder::new(&coordinates(3))
+                .set_limits(&limits)
+                .build()
+                .err()
+                .unwrap(),
+            ValidationError::TableTooBig {
+                limit: 2,
+                sources: 3,
+                destinations: 3
+            }
+        );
+        assert!(TableRequestBuilder::new(&coordinates(3))
+            .set_sources(Some(vec![0]))
+            .set_limits(&limits)
+            .build()
+            .is_ok());
+        assert!(TableRequestBuilder::new(&coordinates(3))
+            .set_destinations(Some(vec![0, 3]))
+            .build()
+            .is_err());
+    }
+}
@@ -7,16 +7,23 @@ use std::{
     os::raw::{c_char, c_void},
 };
 
-use engine_config::c_engine_config::CEngineConfig;
+use engine_config::{c_engine_config::CEngineConfig, engine_limits::EngineLimits};
 
+#[cfg(feature = "async")]
+pub mod async_osrm;
+pub mod backend;
 pub mod engine_config;
 pub mod general;
 pub mod match_api;
 pub mod nearest_api;
+pub mod osrm_error;
 pub mod route_api;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod table_api;
 pub mod tile_api;
 pub mod trip_api;
+pub mod vrp;
 
 #[link(name = "c_osrm")]
 extern "C" {
@@ -64,6 +71,12 @@ pub enum Boolean {
     TRUE = 1,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum StringDecoding {
+    Lossy,
+    Strict,
+}
+
 impl Boolean {
     fn from(value: bool) -> Boolean {
         match value {
@@ -75,38 +88,46 @@ impl Boolean {
 
 pub struct Osrm {
     config: Box<*mut c_void>,
+    pub(crate) string_decoding: StringDecoding,
+    limits: EngineLimits,
 }
 
 impl Osrm {
-    pub(crate) fn new(c_engine_config: CEngineConfig) -> Result<Osrm, String> {
+    pub(crate) fn new(
+        c_engine_config: CEngineConfig,
+        string_decoding: StringDecoding,
+        limits: EngineLimits,
+    ) -> Result<Osrm, String> {
         unsafe {
             let mut result: *mut COSRM = std::ptr::null_mut();
             let result_ptr: *mut *mut COSRM = &mut result;
             osrm_create(&c_engine_config as *const CEngineConfig, result_ptr);
 
+            if result.is_null() {
+                return Err("osrm_create returned no result".to_string());
+            }
+
             if (*result).error_message != std::ptr::null_mut() {
                 let c_name_buf: *const c_char = (*result).error_message;
                 let c_name_str: &CStr = CStr::from_ptr(c_name_buf);
+                let name_str_slice = c_name_str.to_string_lossy().into_owned();
 
-                match c_name_str.to_str() {
-                    Ok(ok) => {
-                        let name_str_slice = ok.to_string();
+                osrm_destroy_error_message((*result).error_message);
 
-                        osrm_destroy_error_message((*result).error_message);
-
-                        return Err(name_str_slice);
-                    }
-                    Err(e) => {
-                        return Err(e.to_string());
-                    }
-                }
+                return Err(name_str_slice);
             }
 
             Ok(Osrm {
                 config: Box::new((*result).obj),
+                string_decoding,
+                limits,
             })
         }
     }
+
+    pub fn limits(&self) -> &EngineLimits {
+        &self.limits
+    }
 }
 
 impl Drop for Osrm {
@@ -0,0 +1,518 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    backend::RoutingBackend,
+    general::rs_structs::{coordinate::Coordinate, geometry::Geometry, route_leg::RouteLeg},
+    osrm_error::OsrmError,
+};
+
+use super::{
+    match_request::MatchRequest, match_result::MatchResult, match_route::MatchRoute,
+    match_waypoint::MatchWaypoint,
+};
+
+pub struct LongTraceMatcher {
+    pub request: MatchRequest,
+    pub window: usize,
+    pub overlap: usize,
+    pub threads: usize,
+}
+
+impl LongTraceMatcher {
+    pub fn new(request: MatchRequest, max_locations_map_matching: usize) -> LongTraceMatcher {
+        LongTraceMatcher {
+            request,
+            window: max_locations_map_matching,
+            overlap: (max_locations_map_matching / 4).max(1),
+            threads: 1,
+        }
+    }
+
+    // Consecutive windows share `overlap` points; the trace is cut in the middle of each overlap.
+    pub fn windows(&self) -> Result<Vec<Range<usize>>, OsrmError> {
+        if self.window < 2 || self.overlap == 0 || self.overlap >= self.window {
+            return Err(OsrmError::InvalidValue(format!(
+                "a window of {} points with an overlap of {} cannot be stitched",
+                self.window, self.overlap
+            )));
+        }
+
+        let length = self.request.general_options.coordinate.len();
+        let mut windows = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = (start + self.window).min(length);
+            windows.push(start..end);
+            if end == length {
+                return Ok(windows);
+            }
+            start = end - self.overlap;
+        }
+    }
+
+    pub fn run<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+    ) -> Result<MatchResult, OsrmError> {
+        let windows = self.windows()?;
+        if windows.len() == 1 {
+            return self.request.clone().run(backend);
+        }
+        if self.request.waypoints.is_some() {
+            return Err(OsrmError::InvalidOptions(
+                "waypoints are not supported when a trace is split into windows".to_string(),
+            ));
+        }
+
+        let requests = windows
+            .iter()
+            .map(|window| self.window_request(window.clone()))
+            .collect();
+        let mut results = Vec::with_capacity(windows.len());
+        for result in run_windows(requests, backend, self.threads) {
+            results.push(match result {
+                Ok(result) => Some(result),
+                // A window without any matchable point only leaves a gap in the trace.
+                Err(OsrmError::NoMatch(_)) => None,
+                Err(error) => return Err(error),
+            });
+        }
+
+        stitch(&windows, results)
+    }
+
+    fn window_request(&self, window: Range<usize>) -> MatchRequest {
+        let mut request = self.request.clone();
+        request.general_options = self.request.general_options.slice(window.clone());
+        request.timestamps = self
+            .request
+            .timestamps
+            .as_ref()
+            .map(|timestamps| timestamps[window].to_vec());
+        request
+    }
+}
+
+fn run_windows<B: RoutingBackend + Sync + ?Sized>(
+    requests: Vec<MatchRequest>,
+    backend: &B,
+    threads: usize,
+) -> Vec<Result<MatchResult, OsrmError>> {
+    let requests: Vec<Mutex<Option<MatchRequest>>> = requests
+        .into_iter()
+        .map(|request| Mutex::new(Some(request)))
+        .collect();
+    let results: Vec<Mutex<Option<Result<MatchResult, OsrmError>>>> =
+        requests.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1).min(requests.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let mut request = match requests
+                    .get(index)
+                    .and_then(|request| request.lock().unwrap().take())
+                {
+                    Some(request) => request,
+                    None => break,
+                };
+
+                let result = request.run(backend);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| {
+            result
+                .into_inner()
+                .unwrap()
+                .unwrap_or_else(|| Err(OsrmError::Ffi("match window was not run".to_string())))
+        })
+        .collect()
+}
+
+// Window `k` contributes the points from the previous cut up to and including its own cut, so
+// the cut point is shared. A matching that reaches a cut continues in the next window's matching
+// that starts there; otherwise a new matching begins, as with `Gap::Split`.
+fn stitch(
+    windows: &[Range<usize>],
+    results: Vec<Option<MatchResult>>,
+) -> Result<MatchResult, OsrmError> {
+    let length = windows.last().map_or(0, |window| window.end);
+    let cuts: Vec<usize> = windows
+        .windows(2)
+        .map(|pair| pair[1].start + (pair[0].end - 1 - pair[1].start) / 2)
+        .collect();
+
+    let mut tracepoints: Vec<Option<MatchWaypoint>> = (0..length).map(|_| None).collect();
+    let mut matchings: Vec<MatchRoute> = Vec::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+
+    for (index, (window, result)) in windows.iter().zip(results).enumerate() {
+        let result = match result {
+            Some(result) => result,
+            None => continue,
+        };
+        if result.tracepoints.len() != window.len() {
+            return Err(OsrmError::NoMatch(format!(
+                "match window returned {} tracepoints for {} points",
+                result.tracepoints.len(),
+                window.len()
+            )));
+        }
+
+        let first = if index == 0 { 0 } else { cuts[index - 1] };
+        let last = cuts.get(index).copied().unwrap_or(length - 1);
+        let mut window_tracepoints = result.tracepoints;
+        let mut routes: Vec<Option<MatchRoute>> = result.matchings.into_iter().map(Some).collect();
+
+        // (global index, waypoint index) of every matched point, grouped by window matching.
+        let mut all: Vec<Vec<(usize, usize)>> = routes.iter().map(|_| Vec::new()).collect();
+        for (offset, tracepoint) in window_tracepoints.iter().enumerate() {
+            if let Some(tracepoint) = tracepoint {
+                if let Some(points) = all.get_mut(tracepoint.matchings_index as usize) {
+                    points.push((window.start + offset, tracepoint.waypoint_index as usize));
+                }
+            }
+        }
+        for points in &mut all {
+            points.sort_by_key(|&(_, waypoint)| waypoint);
+        }
+
+        let mut pieces: Vec<(usize, Vec<(usize, usize)>)> = all
+            .iter()
+            .enumerate()
+            .map(|(matching, points)| {
+                let kept: Vec<(usize, usize)> = points
+                    .iter()
+                    .copied()
+                    .filter(|&(point, _)| (first..=last).contains(&point))
+                    .collect();
+                (matching, kept)
+            })
+            .filter(|(_, kept)| !kept.is_empty())
+            .collect();
+        pieces.sort_by_key(|(_, kept)| kept[0].0);
+
+        for (matching, kept) in pieces {
+            let route = match routes[matching].take() {
+                Some(route) => route,
+                None => continue,
+            };
+            let locations: Vec<[f64; 2]> = all[matching]
+                .iter()
+                .map(|&(point, _)| {
+                    window_tracepoints[point - window.start]
+                        .as_ref()
+                        .map_or([0.0, 0.0], |tracepoint| tracepoint.location)
+                })
+                .collect();
+            let from = kept[0].1;
+            let to = kept[kept.len() - 1].1;
+            let piece = cut_route(route, &locations, from, to)?;
+
+            let continues = members
+                .last()
+                .and_then(|points| points.last())
+                .is_some_and(|&point| point == kept[0].0);
+            if continues {
+                let previous = matchings.last_mut().unwrap();
+                join_routes(previous, piece)?;
+                members
+                    .last_mut()
+                    .unwrap()
+                    .extend(kept[1..].iter().map(|&(point, _)| point));
+            } else {
+                matchings.push(piece);
+                members.push(kept.iter().map(|&(point, _)| point).collect());
+            }
+
+            for &(point, _) in &kept {
+                tracepoints[point] = window_tracepoints[point - window.start].take();
+            }
+        }
+    }
+
+    if matchings.is_empty() {
+        return Err(OsrmError::NoMatch("Could not match the trace.".to_string()));
+    }
+
+    for (matching, points) in members.iter().enumerate() {
+        for (waypoint, &point) in points.iter().enumerate() {
+            if let Some(tracepoint) = tracepoints[point].as_mut() {
+                tracepoint.matchings_index = matching as i32;
+                tracepoint.waypoint_index = waypoint as i32;
+            }
+        }
+    }
+
+    Ok(MatchResult {
+        code: Some("Ok".to_string()),
+        message: None,
+        tracepoints,
+        matchings,
+    })
+}
+
+// Keeps the legs between waypoints `from` and `to` of a matching, and the part of its geometry
+// between the vertices closest to those waypoints.
+fn cut_route(
+    route: MatchRoute,
+    locations: &[[f64; 2]],
+    from: usize,
+    to: usize,
+) -> Result<MatchRoute, OsrmError> {
+    if from == 0 && to == route.legs.len() {
+        return Ok(route);
+    }
+
+    let legs: Vec<RouteLeg> = route
+        .legs
+        .into_iter()
+        .skip(from)
+        .take(to.saturating_sub(from))
+        .collect();
+    let geometry = match route.geometry {
+        Some(geometry) => {
+            let coordinates = geometry.coordinates()?;
+            let vertices = waypoint_vertices(&coordinates, locations);
+            let start = vertices.get(from).copied().unwrap_or(0);
+            let end = vertices
+                .get(to)
+                .copied()
+                .unwrap_or(coordinates.len().saturating_sub(1))
+                .max(start);
+            Some(Geometry::encode(
+                coordinates.get(start..=end).unwrap_or(&[]),
+                &geometry.geometries_type(),
+            ))
+        }
+        None => None,
+    };
+
+    Ok(MatchRoute {
+        duration: legs.iter().map(|leg| leg.duration).sum(),
+        distance: legs.iter().map(|leg| leg.distance).sum(),
+        weight_name: route.weight_name,
+        weight: legs.iter().map(|leg| leg.weight).sum(),
+        geometry,
+        number_of_legs: legs.len() as i32,
+        legs,
+        confidence: route.confidence,
+    })
+}
+
+fn join_routes(route: &mut MatchRoute, next: MatchRoute) -> Result<(), OsrmError> {
+    let legs = route.legs.len() as f32;
+    let next_legs = next.legs.len() as f32;
+    if legs + next_legs > 0.0 {
+        route.confidence =
+            (route.confidence * legs + next.confidence * next_legs) / (legs + next_legs);
+    }
+
+    route.geometry = match (route.geometry.take(), next.geometry) {
+        (Some(geometry), Some(next_geometry)) => {
+            let mut coordinates = geometry.coordinates()?;
+            coordinates.extend(next_geometry.coordinates()?.into_iter().skip(1));
+            Some(Geometry::encode(&coordinates, &geometry.geometries_type()))
+        }
+        _ => None,
+    };
+    route.duration += next.duration;
+    route.distance += next.distance;
+    route.weight += next.weight;
+    route.legs.extend(next.legs);
+    route.number_of_legs = route.legs.len() as i32;
+
+    Ok(())
+}
+
+// Walks the geometry forward and picks the closest vertex for each waypoint in order.
+fn waypoint_vertices(coordinates: &[Coordinate], locations: &[[f64; 2]]) -> Vec<usize> {
+    let mut vertices = Vec::with_capacity(locations.len());
+    let mut start = 0;
+    for (index, location) in locations.iter().enumerate() {
+        let distance = |coordinate: &Coordinate| {
+            (coordinate.longitude - location[0]).powi(2)
+                + (coordinate.latitude - location[1]).powi(2)
+        };
+        // Later waypoints must stay reachable, so leave one vertex for each of them.
+        let end = coordinates
+            .len()
+            .saturating_sub(locations.len() - 1 - index)
+            .max(start + 1)
+            .min(coordinates.len());
+        let vertex = (start..end)
+            .min_by(|&a, &b| distance(&coordinates[a]).total_cmp(&distance(&coordinates[b])))
+            .unwrap_or(start);
+        vertices.push(vertex);
+        start = vertex;
+    }
+
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::fake_backend::FakeBackend,
+        general::rs_structs::{coordinate::Coordinate, geometry::Geometry, route_leg::RouteLeg},
+        match_api::{
+            match_request_builder::MatchRequestBuilder, match_result::MatchResult,
+            match_route::MatchRoute, match_waypoint::MatchWaypoint,
+        },
+        osrm_error::OsrmError,
+        route_api::GeometriesType,
+    };
+
+    use super::LongTraceMatcher;
+
+    // Snaps every point onto itself, except the one at longitude `unmatched`, which splits the
+    // matching in two.
+    fn road(max_locations: usize, unmatched: f64) -> FakeBackend<'static> {
+        FakeBackend::new().on_match(move |request| {
+            let points: Vec<Coordinate> = request
+                .general_options
+                .coordinate
+                .iter()
+                .map(|coordinate| Coordinate::new(coordinate.latitude, coordinate.longitude))
+                .collect();
+            if points.len() > max_locations {
+                return Err(OsrmError::TooBig("Too many trace coordinates".to_string()));
+            }
+
+            let mut tracepoints = Vec::new();
+            let mut matchings: Vec<Vec<Coordinate>> = Vec::new();
+            let mut open = false;
+            for point in &points {
+                if point.longitude == unmatched {
+                    tracepoints.push(None);
+                    open = false;
+                    continue;
+                }
+                if !open {
+                    matchings.push(Vec::new());
+                    open = true;
+                }
+                let matchings_index = matchings.len() as i32 - 1;
+                let matching = matchings.last_mut().unwrap();
+                tracepoints.push(Some(MatchWaypoint {
+                    hint: None,
+                    distance: 0.0,
+                    name: format!("road {}", point.longitude),
+                    location: [point.longitude, point.latitude],
+                    matchings_index,
+                    waypoint_index: matching.len() as i32,
+                    alternatives_count: 0,
+                }));
+                matching.push(point.clone());
+            }
+
+            Ok(MatchResult {
+                code: Some("Ok".to_string()),
+                message: None,
+                tracepoints,
+                matchings: matchings
+                    .into_iter()
+                    .map(|points| {
+                        let legs: Vec<RouteLeg> = points
+                            .windows(2)
+                            .map(|pair| RouteLeg {
+                                annotation: None,
+                                duration: pair[1].longitude - pair[0].longitude,
+                                summary: None,
+                                weight: pair[1].longitude - pair[0].longitude,
+                                distance: 100.0,
+                                steps: Vec::new(),
+                            })
+                            .collect();
+                        MatchRoute {
+                            duration: legs.iter().map(|leg| leg.duration).sum(),
+                            distance: legs.iter().map(|leg| leg.distance).sum(),
+                            weight_name: Some("routability".to_string()),
+                            weight: legs.iter().map(|leg| leg.weight).sum(),
+                            geometry: Some(Geometry::encode(&points, &GeometriesType::Polyline6)),
+                            number_of_legs: legs.len() as i32,
+                            legs,
+                            confidence: 1.0,
+                        }
+                    })
+                    .collect(),
+            })
+        })
+    }
+
+    fn trace(length: usize) -> Vec<Coordinate> {
+        (0..length)
+            .map(|index| Coordinate::new(0.5, index as f64))
+            .collect()
+    }
+
+    #[test]
+    fn long_traces_are_stitched_into_one_result() {
+        let backend = road(10, -1.0);
+        let request = MatchRequestBuilder::new(&trace(40))
+            .set_timestamps(Some((0..40).collect()))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            request.clone().run(&backend),
+            Err(OsrmError::TooBig(_))
+        ));
+
+        let mut matcher = LongTraceMatcher::new(request, 10);
+        matcher.threads = 2;
+        assert_eq!(matcher.windows().unwrap().len(), 5);
+
+        let result = matcher.run(&backend).unwrap();
+        assert_eq!(result.matchings.len(), 1);
+        let matching = &result.matchings[0];
+        assert_eq!(matching.legs.len(), 39);
+        assert_eq!(matching.duration, 39.0);
+        assert_eq!(matching.distance, 3900.0);
+        assert_eq!(
+            matching
+                .geometry
+                .as_ref()
+                .unwrap()
+                .coordinates()
+                .unwrap()
+                .len(),
+            40
+        );
+        for (index, tracepoint) in result.tracepoints.iter().enumerate() {
+            let tracepoint = tracepoint.as_ref().unwrap();
+            assert_eq!(tracepoint.location, [index as f64, 0.5]);
+            assert_eq!(tracepoint.matchings_index, 0);
+            assert_eq!(tracepoint.waypoint_index, index as i32);
+        }
+
+        let backend = road(10, 20.0);
+        let result = matcher.run(&backend).unwrap();
+        assert_eq!(result.matchings.len(), 2);
+        assert!(result.tracepoints[20].is_none());
+        assert_eq!(result.matchings[0].legs.len(), 19);
+        assert_eq!(result.matchings[1].legs.len(), 18);
+        let tracepoint = result.tracepoints[21].as_ref().unwrap();
+        assert_eq!(
+            (tracepoint.matchings_index, tracepoint.waypoint_index),
+            (1, 0)
+        );
+        let tracepoint = result.tracepoints[39].as_ref().unwrap();
+        assert_eq!(
+            (tracepoint.matchings_index, tracepoint.waypoint_index),
+            (1, 18)
+        );
+    }
+}
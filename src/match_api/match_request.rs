@@ -1,11 +1,12 @@
-use std::os::raw::c_int;
+use std::{convert::TryFrom, os::raw::c_int};
 
-use crate::{Boolean, Osrm, Status, general::{
+use crate::{Boolean, Osrm, Status, backend::RoutingBackend, engine_config::engine_limits::EngineLimits, general::{
         c_structs::{c_general_options::CGeneralOptions},
         rs_structs::{
             general_options::GeneralOptions,
         },
-    }, route_api::{AnnotationsType, GeometriesType, OverviewType}};
+        validation::{self, ValidationError},
+    }, osrm_error::OsrmError, route_api::{AnnotationsType, GeometriesType, OverviewType}};
 
 use super::{
     match_result::{CMatchResult, MatchResult},
@@ -27,8 +28,10 @@ pub(crate) struct CMatchRequest {
     number_of_waypoints: c_int,
 }
 
-impl From<&mut MatchRequest> for CMatchRequest {
-    fn from(request: &mut MatchRequest) -> Self {
+impl CMatchRequest {
+    // `timestamps` are the request's timestamps narrowed by `MatchRequest::c_timestamps`; they
+    // must outlive the returned struct.
+    fn new(request: &mut MatchRequest, timestamps: &Option<Vec<c_int>>) -> Self {
         CMatchRequest {
             general_options: (&mut request.general_options).into(),
             steps: Boolean::from(request.steps),
@@ -36,7 +39,7 @@ impl From<&mut MatchRequest> for CMatchRequest {
             annotations: Boolean::from(request.annotations),
             annotations_type: request.annotations_type.clone(),
             overview: request.overview.clone(),
-            timestamps: match &request.timestamps {
+            timestamps: match timestamps {
                 Some(timestamps) => timestamps.as_ptr(),
                 None => std::ptr::null(),
             },
@@ -54,6 +57,8 @@ impl From<&mut MatchRequest> for CMatchRequest {
     }
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchRequest {
     pub(crate) general_options: GeneralOptions,
     pub(crate) steps: bool,
@@ -61,29 +66,82 @@ pub struct MatchRequest {
     pub(crate) annotations: bool,
     pub(crate) annotations_type: AnnotationsType,
     pub(crate) overview: OverviewType,
-    pub(crate) timestamps: Option<Vec<i32>>,
+    pub(crate) timestamps: Option<Vec<i64>>,
     pub(crate) gaps: Gap,
     pub(crate) tidy: bool,
     pub(crate) waypoints: Option<Vec<i32>>,
 }
 
 impl MatchRequest {
-    pub fn run(&mut self, osrm: &Osrm) -> (Status, MatchResult) {
+    pub fn run<B: RoutingBackend + ?Sized>(&mut self, backend: &B) -> Result<MatchResult, OsrmError> {
+        backend.match_trace(self)
+    }
+
+    // The c_osrm wrapper reads one 32-bit timestamp per coordinate and has no count of its own,
+    // so both are checked here before the pointer crosses the FFI boundary.
+    pub(crate) fn c_timestamps(&self) -> Result<Option<Vec<c_int>>, OsrmError> {
+        let timestamps = match &self.timestamps {
+            Some(timestamps) => timestamps,
+            None => return Ok(None),
+        };
+        if timestamps.len() != self.general_options.coordinate.len() {
+            return Err(OsrmError::InvalidOptions(format!(
+                "{} timestamps given for {} coordinates",
+                timestamps.len(),
+                self.general_options.coordinate.len()
+            )));
+        }
+
+        timestamps
+            .iter()
+            .map(|&timestamp| {
+                c_int::try_from(timestamp).map_err(|_| {
+                    OsrmError::InvalidValue(format!(
+                        "Timestamp {} does not fit the 32-bit timestamps of libosrm",
+                        timestamp
+                    ))
+                })
+            })
+            .collect::<Result<Vec<c_int>, OsrmError>>()
+            .map(Some)
+    }
+
+    pub(crate) fn check_limits(&self, limits: &EngineLimits) -> Result<(), ValidationError> {
+        validation::check_coordinate_limit(
+            &self.general_options,
+            limits.max_locations_map_matching,
+        )?;
+        validation::check_radiuses(&self.general_options, limits.max_radius_map_matching)
+    }
+
+    pub(crate) fn run_osrm(&mut self, osrm: &Osrm) -> Result<MatchResult, OsrmError> {
+        self.check_limits(osrm.limits())?;
+        let timestamps = self.c_timestamps()?;
         unsafe {
             let mut result: *mut CMatchResult = std::ptr::null_mut();
             let result_ptr: *mut *mut CMatchResult = &mut result;
 
             let status = osrm_match(
                 *osrm.config,
-                &mut CMatchRequest::from(self) as *mut CMatchRequest,
+                &mut CMatchRequest::new(&mut *self, &timestamps) as *mut CMatchRequest,
                 result_ptr,
             );
 
-            let converted_result = MatchResult::from(&(*result));
+            if result.is_null() {
+                return Err(OsrmError::Ffi("osrm_match returned no result".to_string()));
+            }
+
+            let converted_result = MatchResult::new(&(*result), &osrm.string_decoding, &self.geometries);
 
             match_result_destroy(result);
 
-            (status, converted_result)
+            let converted_result = converted_result?;
+
+            if status == Status::Ok {
+                Ok(converted_result)
+            } else {
+                Err(OsrmError::from_code(converted_result.code, converted_result.message))
+            }
         }
     }
 }
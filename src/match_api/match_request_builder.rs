@@ -1,6 +1,10 @@
-use std::ffi::CString;
+use std::{
+    ffi::CString,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
+    engine_config::engine_limits::EngineLimits,
     general::{
         c_structs::{c_approach::Approach, c_bearing::Bearing},
         rs_structs::{
@@ -8,12 +12,14 @@ use crate::{
             general_options::{GeneralOptions, GeneralOptionsTrait},
         },
         to_vec_ccoordinate,
+        validation::{self, ValidationError},
     },
     route_api::{AnnotationsType, GeometriesType, OverviewType},
 };
 
 use super::{match_request::MatchRequest, Gap};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchRequestBuilder {
     general_options: GeneralOptions,
     steps: bool,
@@ -21,14 +27,15 @@ pub struct MatchRequestBuilder {
     annotations: bool,
     annotations_type: AnnotationsType,
     overview: OverviewType,
-    timestamps: Option<Vec<i32>>,
+    timestamps: Option<Vec<i64>>,
     gaps: Gap,
     tidy: bool,
     waypoints: Option<Vec<i32>>,
+    limits: EngineLimits,
 }
 
 impl MatchRequestBuilder {
-    pub fn new(coordinates: &Vec<Coordinate>) -> MatchRequestBuilder {
+    pub fn new<C: Clone + Into<Coordinate>>(coordinates: &[C]) -> MatchRequestBuilder {
         MatchRequestBuilder {
             general_options: GeneralOptions::new(coordinates),
             steps: false,
@@ -40,6 +47,7 @@ impl MatchRequestBuilder {
             gaps: Gap::Split,
             tidy: false,
             waypoints: None,
+            limits: EngineLimits::unlimited(),
         }
     }
 
@@ -47,7 +55,7 @@ impl MatchRequestBuilder {
         self.steps = steps;
         self
     }
-    
+
     pub fn set_geometries<'a>(&'a mut self, geometries: GeometriesType) -> &'a mut Self {
         self.geometries = geometries;
         self
@@ -58,7 +66,10 @@ impl MatchRequestBuilder {
         self
     }
 
-    pub fn set_annotations_type<'a>(&'a mut self, annotations_type: AnnotationsType) -> &'a mut Self {
+    pub fn set_annotations_type<'a>(
+        &'a mut self,
+        annotations_type: AnnotationsType,
+    ) -> &'a mut Self {
         self.annotations_type = annotations_type;
         self
     }
@@ -68,11 +79,21 @@ impl MatchRequestBuilder {
         self
     }
 
-    pub fn set_timestamps<'a>(&'a mut self, timestamps: Option<Vec<i32>>) -> &'a mut Self {
+    pub fn set_timestamps<'a>(&'a mut self, timestamps: Option<Vec<i64>>) -> &'a mut Self {
         self.timestamps = timestamps;
         self
     }
 
+    pub fn set_system_timestamps<'a>(
+        &'a mut self,
+        timestamps: Option<Vec<SystemTime>>,
+    ) -> &'a mut Self {
+        self.timestamps = timestamps.map(|timestamps| {
+            timestamps.iter().map(|&timestamp| unix_seconds(timestamp)).collect()
+        });
+        self
+    }
+
     pub fn set_gaps<'a>(&'a mut self, gaps: Gap) -> &'a mut Self {
         self.gaps = gaps;
         self
@@ -88,8 +109,29 @@ impl MatchRequestBuilder {
         self
     }
 
-    pub fn build(&self) -> Result<MatchRequest, String> {
-        Ok(MatchRequest {
+    pub fn set_limits<'a>(&'a mut self, limits: &EngineLimits) -> &'a mut Self {
+        self.limits = limits.clone();
+        self
+    }
+
+    pub fn build(&self) -> Result<MatchRequest, ValidationError> {
+        validation::check_general_options(&self.general_options, 2)?;
+
+        let count = self.general_options.coordinate.len();
+        if let Some(timestamps) = &self.timestamps {
+            validation::check_length("timestamps", count, Some(timestamps.len()))?;
+            if let Some(index) =
+                (1..timestamps.len()).find(|&index| timestamps[index] < timestamps[index - 1])
+            {
+                return Err(ValidationError::NonMonotonicTimestamps { index });
+            }
+        }
+        if let Some(waypoints) = &self.waypoints {
+            let waypoints: Vec<i64> = waypoints.iter().map(|&waypoint| waypoint as i64).collect();
+            validation::check_waypoints(&waypoints, count)?;
+        }
+
+        let request = MatchRequest {
             general_options: self.general_options.clone(),
             steps: self.steps,
             geometries: self.geometries.clone(),
@@ -100,7 +142,21 @@ impl MatchRequestBuilder {
             gaps: self.gaps.clone(),
             tidy: self.tidy,
             waypoints: self.waypoints.clone(),
-        })
+        };
+        request.check_limits(&self.limits)?;
+
+        Ok(request)
+    }
+}
+
+// Whole seconds, rounded down so times before 1970 stay in order.
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(error) => {
+            let before = error.duration();
+            -(before.as_secs() as i64) - if before.subsec_nanos() > 0 { 1 } else { 0 }
+        }
     }
 }
 
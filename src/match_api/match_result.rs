@@ -1,9 +1,15 @@
 use std::{
-    ffi::CStr,
     os::raw::{c_char, c_int},
     slice,
 };
 
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::c_string_to_option_string, osrm_error::OsrmError, route_api::GeometriesType,
+    StringDecoding,
+};
+
 use super::{
     match_route::{CMatchRoute, MatchRoute},
     match_waypoint::{CMatchWaypoint, MatchWaypoint},
@@ -20,58 +26,118 @@ pub(crate) struct CMatchResult {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchResult {
     pub code: Option<String>,
     pub message: Option<String>,
-    pub tracepoints: Vec<MatchWaypoint>,
+    pub tracepoints: Vec<Option<MatchWaypoint>>,
     pub matchings: Vec<MatchRoute>,
 }
 
-impl From<&CMatchResult> for MatchResult {
-    fn from(c_reasult: &CMatchResult) -> Self {
-        unsafe {
-            MatchResult {
-                code: if c_reasult.code != std::ptr::null_mut() {
-                    CStr::from_ptr(c_reasult.code as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                message: if c_reasult.message != std::ptr::null_mut() {
-                    CStr::from_ptr(c_reasult.message as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                tracepoints: if c_reasult.waypoints != std::ptr::null_mut() {
+impl MatchResult {
+    pub(crate) fn new(
+        c_reasult: &CMatchResult,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<MatchResult, OsrmError> {
+        Ok(MatchResult {
+            code: c_string_to_option_string(c_reasult.code, string_decoding)?,
+            message: c_string_to_option_string(c_reasult.message, string_decoding)?,
+            tracepoints: if c_reasult.waypoints != std::ptr::null_mut() {
+                unsafe {
                     slice::from_raw_parts(
                         c_reasult.waypoints,
                         c_reasult.number_of_waypoints as usize,
                     )
-                    .iter()
-                    .map(|waypoint| MatchWaypoint::from(waypoint))
-                    .collect()
-                } else {
-                    Vec::new()
-                },
-                matchings: if c_reasult.routes != std::ptr::null_mut() {
+                }
+                .iter()
+                .map(|waypoint| MatchWaypoint::new(waypoint, string_decoding).map(Some))
+                .collect::<Result<Vec<Option<MatchWaypoint>>, OsrmError>>()?
+            } else {
+                Vec::new()
+            },
+            matchings: if c_reasult.routes != std::ptr::null_mut() {
+                unsafe {
                     slice::from_raw_parts(
                         c_reasult.routes,
                         c_reasult.number_of_routes as usize,
                     )
+                }
+                .iter()
+                .map(|route| MatchRoute::new(route, string_decoding, geometries))
+                .collect::<Result<Vec<MatchRoute>, OsrmError>>()?
+            } else {
+                Vec::new()
+            },
+        })
+    }
+
+    // `geometries` must be the value the response was requested with: polyline and polyline6
+    // strings look alike, and decoding one as the other is off by a factor of ten.
+    #[cfg(feature = "json")]
+    pub fn from_osrm_json(
+        response: &str,
+        geometries: &GeometriesType,
+    ) -> Result<MatchResult, OsrmError> {
+        let value = json::parse(response)?;
+        json::check_code(&value)?;
+        MatchResult::from_json(&value, geometries)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_osrm_json(&self) -> String {
+        json::render(&self.to_json())
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut result = serde_json::json!({
+            "code": self.code.clone().unwrap_or_else(|| "Ok".to_string()),
+        });
+        json::insert_opt_string(&mut result, "message", &self.message);
+        result["matchings"] = serde_json::Value::Array(
+            self.matchings.iter().map(MatchRoute::to_json).collect(),
+        );
+        result["tracepoints"] = serde_json::Value::Array(
+            self.tracepoints
+                .iter()
+                .map(|tracepoint| match tracepoint {
+                    Some(tracepoint) => tracepoint.to_json(),
+                    None => serde_json::Value::Null,
+                })
+                .collect(),
+        );
+        result
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<MatchResult, OsrmError> {
+        Ok(MatchResult {
+            code: json::opt_string_field(value, "code"),
+            message: json::opt_string_field(value, "message"),
+            tracepoints: match json::opt_array_field(value, "tracepoints") {
+                Some(tracepoints) => tracepoints
+                    .iter()
+                    .map(|tracepoint| {
+                        if tracepoint.is_null() {
+                            Ok(None)
+                        } else {
+                            MatchWaypoint::from_json(tracepoint).map(Some)
+                        }
+                    })
+                    .collect::<Result<Vec<Option<MatchWaypoint>>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+            matchings: match json::opt_array_field(value, "matchings") {
+                Some(matchings) => matchings
                     .iter()
-                    .map(|route| MatchRoute::from(route))
-                    .collect()
-                } else {
-                    Vec::new()
-                },
-            }
-        }
+                    .map(|route| MatchRoute::from_json(route, geometries))
+                    .collect::<Result<Vec<MatchRoute>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+        })
     }
 }
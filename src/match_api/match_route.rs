@@ -3,8 +3,17 @@ use std::{
     slice,
 };
 
-use crate::general::{
-    c_string_to_string, c_structs::c_route_leg::COsrmRouteLeg, rs_structs::route_leg::RouteLeg,
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{
+        c_string_to_option_string,
+        c_structs::c_route_leg::COsrmRouteLeg,
+        rs_structs::{geometry::Geometry, route_leg::RouteLeg},
+    },
+    osrm_error::OsrmError,
+    route_api::GeometriesType,
+    StringDecoding,
 };
 
 #[repr(C)]
@@ -21,40 +30,36 @@ pub(crate) struct CMatchRoute {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchRoute {
     pub duration: f64,
     pub distance: f64,
     pub weight_name: Option<String>,
     pub weight: f64,
-    pub geometry: Option<String>,
+    pub geometry: Option<Geometry>,
     pub legs: Vec<RouteLeg>,
     pub number_of_legs: i32,
     pub confidence: f32,
 }
 
-impl From<&CMatchRoute> for MatchRoute {
-    fn from(c_route: &CMatchRoute) -> Self {
-        MatchRoute {
+impl MatchRoute {
+    pub(crate) fn new(
+        c_route: &CMatchRoute,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<MatchRoute, OsrmError> {
+        Ok(MatchRoute {
             duration: c_route.duration,
             distance: c_route.distance,
-            weight_name: if c_route.weight_name != std::ptr::null() {
-                c_string_to_string(c_route.weight_name).into()
-            } else {
-                None
-            },
+            weight_name: c_string_to_option_string(c_route.weight_name, string_decoding)?,
             weight: c_route.weight,
-            geometry: if c_route.geometry != std::ptr::null() {
-                c_string_to_string(c_route.geometry).into()
-            } else {
-                None
-            },
+            geometry: c_string_to_option_string(c_route.geometry, string_decoding)?
+                .map(|geometry| Geometry::new(geometry, geometries)),
             legs: if c_route.legs != std::ptr::null_mut() {
-                unsafe {
-                    slice::from_raw_parts(c_route.legs, c_route.number_of_legs as usize)
-                        .iter()
-                        .map(|leg| leg.into())
-                        .collect()
-                }
+                unsafe { slice::from_raw_parts(c_route.legs, c_route.number_of_legs as usize) }
+                    .iter()
+                    .map(|leg| RouteLeg::new(leg, string_decoding, geometries))
+                    .collect::<Result<Vec<RouteLeg>, OsrmError>>()?
             } else {
                 Vec::new()
             },
@@ -64,6 +69,53 @@ impl From<&CMatchRoute> for MatchRoute {
                 c_route.number_of_legs as i32
             },
             confidence: c_route.confidence,
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut route = serde_json::json!({
+            // widen through the shortest decimal so 0.974621f32 is not printed as 0.974620997
+            "confidence": self.confidence.to_string().parse::<f64>().unwrap_or_default(),
+        });
+        if let Some(geometry) = &self.geometry {
+            route["geometry"] = geometry.to_json();
         }
+        route["legs"] = serde_json::Value::Array(self.legs.iter().map(RouteLeg::to_json).collect());
+        json::insert_opt_string(&mut route, "weight_name", &self.weight_name);
+        route["weight"] = serde_json::json!(self.weight);
+        route["duration"] = serde_json::json!(self.duration);
+        route["distance"] = serde_json::json!(self.distance);
+        route
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<MatchRoute, OsrmError> {
+        let legs = match json::opt_array_field(value, "legs") {
+            Some(legs) => legs
+                .iter()
+                .map(|leg| RouteLeg::from_json(leg, geometries))
+                .collect::<Result<Vec<RouteLeg>, OsrmError>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(MatchRoute {
+            duration: json::f64_field(value, "duration")?,
+            distance: json::f64_field(value, "distance")?,
+            weight_name: json::opt_string_field(value, "weight_name"),
+            weight: json::opt_f64_field(value, "weight").unwrap_or_default(),
+            geometry: Geometry::from_json(value, "geometry", geometries),
+            number_of_legs: legs.len() as i32,
+            legs,
+            confidence: json::opt_f64_field(value, "confidence").unwrap_or_default() as f32,
+        })
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn line_string(&self) -> Result<Option<geo_types::LineString<f64>>, OsrmError> {
+        self.geometry.as_ref().map(Geometry::to_line_string).transpose()
     }
 }
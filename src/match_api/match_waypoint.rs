@@ -1,6 +1,12 @@
 use std::os::raw::{c_char, c_double, c_int};
 
-use crate::general::{c_string_to_option_string, c_string_to_string};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_option_string, c_string_to_string},
+    osrm_error::OsrmError,
+    StringDecoding,
+};
 
 #[repr(C)]
 #[derive(Clone)]
@@ -15,6 +21,7 @@ pub(crate) struct CMatchWaypoint {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchWaypoint {
     pub hint: Option<String>,
     pub distance: f64,
@@ -25,16 +32,51 @@ pub struct MatchWaypoint {
     pub alternatives_count: i32,
 }
 
-impl From<&CMatchWaypoint> for MatchWaypoint {
-    fn from(c_match: &CMatchWaypoint) -> Self {
-        MatchWaypoint {
-            hint: c_string_to_option_string(c_match.hint),
+impl MatchWaypoint {
+    pub(crate) fn new(
+        c_match: &CMatchWaypoint,
+        string_decoding: &StringDecoding,
+    ) -> Result<MatchWaypoint, OsrmError> {
+        Ok(MatchWaypoint {
+            hint: c_string_to_option_string(c_match.hint, string_decoding)?,
             distance: c_match.distance,
-            name: c_string_to_string(c_match.name),
+            name: c_string_to_string(c_match.name, string_decoding)?,
             location: c_match.location,
             matchings_index: c_match.matchings_index,
             waypoint_index: c_match.waypoint_index,
             alternatives_count: c_match.alternatives_count,
-        }
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut waypoint = serde_json::json!({
+            "alternatives_count": self.alternatives_count,
+            "waypoint_index": self.waypoint_index,
+            "matchings_index": self.matchings_index,
+        });
+        json::insert_opt_string(&mut waypoint, "hint", &self.hint);
+        waypoint["distance"] = serde_json::json!(self.distance);
+        waypoint["name"] = serde_json::json!(self.name);
+        waypoint["location"] = serde_json::json!(self.location);
+        waypoint
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<MatchWaypoint, OsrmError> {
+        Ok(MatchWaypoint {
+            hint: json::opt_string_field(value, "hint"),
+            distance: json::opt_f64_field(value, "distance").unwrap_or_default(),
+            name: json::opt_string_field(value, "name").unwrap_or_default(),
+            location: json::location_field(value, "location")?,
+            matchings_index: json::i32_field(value, "matchings_index")?,
+            waypoint_index: json::i32_field(value, "waypoint_index")?,
+            alternatives_count: json::opt_i32_field(value, "alternatives_count").unwrap_or_default(),
+        })
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn point(&self) -> geo_types::Point<f64> {
+        geo_types::Point::new(self.location[0], self.location[1])
     }
 }
@@ -1,7 +1,10 @@
+pub mod long_trace;
 pub mod match_request;
 pub mod match_result;
 pub mod match_route;
 pub mod match_waypoint;
+pub mod streaming_matcher;
+pub mod trace_import;
 pub mod match_request_builder;
 
 
@@ -24,6 +27,7 @@ extern "C" {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Annotations {
     NONE = 0,
     DURATION = 1,
@@ -33,6 +37,7 @@ pub enum Annotations {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FallbackCoordinate {
     INPUT = 0,
     SNAPPED = 1,
@@ -40,6 +45,7 @@ pub enum FallbackCoordinate {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Gap {
     Split = 0,
     Ignore = 1,
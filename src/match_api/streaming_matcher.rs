@@ -0,0 +1,285 @@
+use std::collections::VecDeque;
+
+use crate::{
+    backend::RoutingBackend,
+    general::rs_structs::{coordinate::Coordinate, general_options::GeneralOptionsTrait},
+    nearest_api::nearest_request_builder::NearestRequestBuilder,
+    osrm_error::OsrmError,
+    route_api::OverviewType,
+};
+
+use super::{match_request_builder::MatchRequestBuilder, Gap};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ping {
+    pub coordinate: Coordinate,
+    pub timestamp: i64,
+    pub radius: Option<f64>,
+}
+
+impl Ping {
+    pub fn new(coordinate: Coordinate, timestamp: i64) -> Ping {
+        Ping {
+            coordinate,
+            timestamp,
+            radius: None,
+        }
+    }
+}
+
+// `location` and `name` are None when the ping could not be snapped; `split` is set when a gap
+// dropped the previous pings, so the position starts a new matching.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchedPosition {
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub location: Option<[f64; 2]>,
+    pub name: Option<String>,
+    pub distance: Option<f64>,
+    pub confidence: Option<f32>,
+    pub split: bool,
+}
+
+// Matches each new ping together with at most `window - 1` earlier ones, so the cost of a ping
+// does not grow with the length of the trip. A ping more than `max_gap` seconds after the previous
+// one, or earlier than it, starts over with an empty window.
+pub struct StreamingMatcher {
+    pub window: usize,
+    pub max_gap: i64,
+    pings: VecDeque<Ping>,
+    sequence: u64,
+}
+
+impl StreamingMatcher {
+    pub fn new(window: usize) -> StreamingMatcher {
+        StreamingMatcher {
+            window,
+            max_gap: 60,
+            pings: VecDeque::new(),
+            sequence: 0,
+        }
+    }
+
+    pub fn pings(&self) -> impl Iterator<Item = &Ping> {
+        self.pings.iter()
+    }
+
+    pub fn reset(&mut self) {
+        self.pings.clear();
+    }
+
+    pub fn push<B: RoutingBackend + ?Sized>(
+        &mut self,
+        backend: &B,
+        ping: Ping,
+    ) -> Result<MatchedPosition, OsrmError> {
+        let split = self.pings.back().is_some_and(|last| {
+            ping.timestamp < last.timestamp || ping.timestamp - last.timestamp > self.max_gap
+        });
+        if split {
+            self.pings.clear();
+        }
+
+        let mut position = MatchedPosition {
+            sequence: self.sequence,
+            timestamp: ping.timestamp,
+            location: None,
+            name: None,
+            distance: None,
+            confidence: None,
+            split,
+        };
+        self.sequence += 1;
+        self.pings.push_back(ping);
+        while self.pings.len() > self.window.max(2) {
+            self.pings.pop_front();
+        }
+
+        if self.pings.len() == 1 {
+            self.snap(backend, &mut position)?;
+        } else {
+            self.match_window(backend, &mut position)?;
+        }
+
+        Ok(position)
+    }
+
+    // A single ping cannot be matched, so it is snapped to the nearest road instead.
+    fn snap<B: RoutingBackend + ?Sized>(
+        &self,
+        backend: &B,
+        position: &mut MatchedPosition,
+    ) -> Result<(), OsrmError> {
+        let ping = &self.pings[0];
+        let mut builder =
+            NearestRequestBuilder::new(ping.coordinate.latitude, ping.coordinate.longitude);
+        builder.set_radiuses(ping.radius.map(|radius| vec![Some(radius)]));
+
+        let result = match builder.build()?.run(backend) {
+            Ok(result) => result,
+            Err(OsrmError::NoSegment(_)) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        if let Some(waypoint) = result.waypoints.into_iter().flatten().next() {
+            position.location = Some(waypoint.location);
+            position.name = Some(waypoint.name);
+            position.distance = Some(waypoint.distance);
+        }
+
+        Ok(())
+    }
+
+    fn match_window<B: RoutingBackend + ?Sized>(
+        &self,
+        backend: &B,
+        position: &mut MatchedPosition,
+    ) -> Result<(), OsrmError> {
+        let coordinates: Vec<Coordinate> = self
+            .pings
+            .iter()
+            .map(|ping| ping.coordinate.clone())
+            .collect();
+        let mut builder = MatchRequestBuilder::new(&coordinates);
+        builder
+            .set_timestamps(Some(self.pings.iter().map(|ping| ping.timestamp).collect()))
+            .set_gaps(Gap::Split)
+            .set_overview(OverviewType::False);
+        if self.pings.iter().any(|ping| ping.radius.is_some()) {
+            builder.set_radiuses(Some(self.pings.iter().map(|ping| ping.radius).collect()));
+        }
+
+        let mut result = match builder.build()?.run(backend) {
+            Ok(result) => result,
+            Err(OsrmError::NoMatch(_)) | Err(OsrmError::NoSegment(_)) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        if let Some(Some(tracepoint)) = result.tracepoints.pop() {
+            position.confidence = result
+                .matchings
+                .get(tracepoint.matchings_index as usize)
+                .map(|matching| matching.confidence);
+            position.location = Some(tracepoint.location);
+            position.name = Some(tracepoint.name);
+            position.distance = Some(tracepoint.distance);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        backend::fake_backend::FakeBackend,
+        general::rs_structs::coordinate::Coordinate,
+        match_api::{
+            match_result::MatchResult, match_route::MatchRoute, match_waypoint::MatchWaypoint,
+        },
+        nearest_api::{nearest_result::NearestResult, nearest_waypoint::NearestWaypoint},
+        osrm_error::OsrmError,
+    };
+
+    use super::{Ping, StreamingMatcher};
+
+    // Snaps every point onto the equator and records the size of each match request.
+    fn equator(requests: &Mutex<Vec<usize>>) -> FakeBackend<'_> {
+        FakeBackend::new()
+            .on_match(move |request| {
+                let coordinates = &request.general_options.coordinate;
+                requests.lock().unwrap().push(coordinates.len());
+
+                Ok(MatchResult {
+                    code: Some("Ok".to_string()),
+                    message: None,
+                    tracepoints: coordinates
+                        .iter()
+                        .enumerate()
+                        .map(|(index, coordinate)| {
+                            Some(MatchWaypoint {
+                                hint: None,
+                                distance: coordinate.latitude.abs(),
+                                name: "Equator".to_string(),
+                                location: [coordinate.longitude, 0.0],
+                                matchings_index: 0,
+                                waypoint_index: index as i32,
+                                alternatives_count: 0,
+                            })
+                        })
+                        .collect(),
+                    matchings: vec![MatchRoute {
+                        duration: 0.0,
+                        distance: 0.0,
+                        weight_name: None,
+                        weight: 0.0,
+                        geometry: None,
+                        legs: Vec::new(),
+                        number_of_legs: 0,
+                        confidence: 0.75,
+                    }],
+                })
+            })
+            .on_nearest(|request| {
+                let coordinate = &request.general_options.coordinate[0];
+                if coordinate.latitude.abs() > 1.0 {
+                    return Err(OsrmError::NoSegment(
+                        "Could not find a matching segment".to_string(),
+                    ));
+                }
+
+                Ok(NearestResult {
+                    code: Some("Ok".to_string()),
+                    message: None,
+                    waypoints: Some(vec![NearestWaypoint {
+                        nodes: [0, 0],
+                        hint: None,
+                        distance: coordinate.latitude.abs(),
+                        name: "Nearest".to_string(),
+                        location: [coordinate.longitude, 0.0],
+                    }]),
+                })
+            })
+    }
+
+    #[test]
+    fn pings_are_matched_in_a_sliding_window() {
+        let requests = Mutex::new(Vec::new());
+        let backend = equator(&requests);
+        let mut matcher = StreamingMatcher::new(3);
+        let ping = |longitude: f64, timestamp: i64| {
+            Ping::new(Coordinate::new(0.001, longitude), timestamp)
+        };
+
+        let first = matcher.push(&backend, ping(13.0, 0)).unwrap();
+        assert_eq!(first.sequence, 0);
+        assert_eq!(first.name.as_deref(), Some("Nearest"));
+        assert_eq!(first.location, Some([13.0, 0.0]));
+        assert_eq!(first.confidence, None);
+
+        for (index, timestamp) in [5, 10, 15, 20].iter().enumerate() {
+            let position = matcher
+                .push(&backend, ping(13.0 + index as f64 * 0.001, *timestamp))
+                .unwrap();
+            assert_eq!(position.name.as_deref(), Some("Equator"));
+            assert_eq!(position.location, Some([13.0 + index as f64 * 0.001, 0.0]));
+            assert_eq!(position.confidence, Some(0.75));
+            assert!(!position.split);
+        }
+        assert_eq!(*requests.lock().unwrap(), vec![2, 3, 3, 3]);
+
+        let after_gap = matcher.push(&backend, ping(13.1, 200)).unwrap();
+        assert!(after_gap.split);
+        assert_eq!(after_gap.sequence, 5);
+        assert_eq!(after_gap.name.as_deref(), Some("Nearest"));
+        assert_eq!(matcher.pings().count(), 1);
+
+        let backwards = matcher
+            .push(&backend, Ping::new(Coordinate::new(5.0, 13.1), 150))
+            .unwrap();
+        assert!(backwards.split);
+        assert_eq!(backwards.location, None);
+    }
+}
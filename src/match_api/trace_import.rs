@@ -0,0 +1,498 @@
+use crate::{
+    general::rs_structs::{coordinate::Coordinate, general_options::GeneralOptionsTrait},
+    osrm_error::OsrmError,
+};
+
+use super::match_request_builder::MatchRequestBuilder;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TracePoint {
+    pub coordinate: Coordinate,
+    pub timestamp: Option<i64>,
+    pub hdop: Option<f64>,
+}
+
+impl TracePoint {
+    pub fn new(coordinate: Coordinate) -> TracePoint {
+        TracePoint {
+            coordinate,
+            timestamp: None,
+            hdop: None,
+        }
+    }
+}
+
+// A recorded trace read from a device log. Timestamps are Unix seconds, and a radius of
+// `hdop * meters_per_hdop` is derived for every point that reports its HDOP.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    pub points: Vec<TracePoint>,
+    pub meters_per_hdop: f64,
+}
+
+impl Trace {
+    pub fn new(points: Vec<TracePoint>) -> Trace {
+        Trace {
+            points,
+            meters_per_hdop: 5.0,
+        }
+    }
+
+    // Reads every `trkpt` of every track and segment, in document order.
+    #[cfg(feature = "gpx")]
+    pub fn from_gpx(gpx: &str) -> Result<Trace, OsrmError> {
+        let document =
+            roxmltree::Document::parse(gpx).map_err(|e| OsrmError::Trace(e.to_string()))?;
+
+        let mut points = Vec::new();
+        for node in document
+            .descendants()
+            .filter(|node| node.tag_name().name() == "trkpt")
+        {
+            let attribute = |name: &str| -> Result<f64, OsrmError> {
+                node.attribute(name)
+                    .and_then(|value| value.trim().parse().ok())
+                    .ok_or_else(|| {
+                        OsrmError::Trace(format!("trkpt {} has no valid `{}`", points.len(), name))
+                    })
+            };
+            let child = |name: &str| {
+                node.children()
+                    .find(|child| child.tag_name().name() == name)
+                    .and_then(|child| child.text())
+                    .map(str::trim)
+            };
+
+            let mut point = TracePoint::new(Coordinate::new(attribute("lat")?, attribute("lon")?));
+            if let Some(time) = child("time") {
+                point.timestamp = Some(parse_time(time).ok_or_else(|| {
+                    OsrmError::Trace(format!(
+                        "trkpt {} has an invalid time `{}`",
+                        points.len(),
+                        time
+                    ))
+                })?);
+            }
+            point.hdop = child("hdop").and_then(|hdop| hdop.parse().ok());
+            points.push(point);
+        }
+
+        Trace::non_empty(points, "GPX document has no track points")
+    }
+
+    // Reads RMC and GGA sentences from any talker. Sentences reporting the same fix time are
+    // merged, so GGA contributes the HDOP and RMC the date. Invalid fixes and sentences with a
+    // wrong checksum are skipped. Without any RMC sentence the timestamps count from midnight.
+    pub fn from_nmea(nmea: &str) -> Result<Trace, OsrmError> {
+        let mut fixes: Vec<NmeaFix> = Vec::new();
+        for sentence in nmea.lines().filter_map(nmea_fields) {
+            let fix = match sentence[0].get(sentence[0].len().saturating_sub(3)..) {
+                Some("RMC") => NmeaFix::from_rmc(&sentence),
+                Some("GGA") => NmeaFix::from_gga(&sentence),
+                _ => None,
+            };
+            let fix = match fix {
+                Some(fix) => fix,
+                None => continue,
+            };
+
+            match fixes.last_mut() {
+                Some(last) if last.time == fix.time => {
+                    last.date = last.date.or(fix.date);
+                    last.hdop = last.hdop.or(fix.hdop);
+                }
+                _ => fixes.push(fix),
+            }
+        }
+
+        let mut day = fixes.iter().find_map(|fix| fix.date).unwrap_or(0);
+        let mut previous = 0.0;
+        let points = fixes
+            .into_iter()
+            .map(|fix| {
+                match fix.date {
+                    Some(date) => day = date,
+                    None if fix.time < previous => day += 1,
+                    None => {}
+                }
+                previous = fix.time;
+
+                TracePoint {
+                    coordinate: fix.coordinate,
+                    timestamp: Some(day * 86400 + fix.time as i64),
+                    hdop: fix.hdop,
+                }
+            })
+            .collect();
+
+        Trace::non_empty(points, "NMEA log has no valid RMC or GGA fixes")
+    }
+
+    // Reads LineString, MultiLineString and MultiPoint geometries, as well as Point features,
+    // from a geometry, a feature or a feature collection. Times come from a `coordTimes`,
+    // `times` or `timestamps` array property (a `time` or `timestamp` property for points),
+    // holding RFC 3339 strings or Unix seconds.
+    #[cfg(feature = "json")]
+    pub fn from_geojson(geojson: &str) -> Result<Trace, OsrmError> {
+        use crate::general::json;
+
+        let value = json::parse(geojson)?;
+        let features = match json::opt_string_field(&value, "type").as_deref() {
+            Some("FeatureCollection") => json::array_field(&value, "features")?.iter().collect(),
+            _ => vec![&value],
+        };
+
+        let mut points = Vec::new();
+        for feature in features {
+            let (geometry, properties) = match json::opt_string_field(feature, "type").as_deref() {
+                Some("Feature") => (json::field(feature, "geometry")?, feature.get("properties")),
+                _ => (feature, None),
+            };
+            let property = |names: &[&str]| {
+                names
+                    .iter()
+                    .find_map(|name| properties.and_then(|properties| properties.get(*name)))
+            };
+
+            let (positions, times) = match json::string_field(geometry, "type")?.as_str() {
+                "Point" => (
+                    vec![json::field(geometry, "coordinates")?],
+                    property(&["time", "timestamp"]).map(|time| vec![time]),
+                ),
+                "LineString" | "MultiPoint" => (
+                    json::array_field(geometry, "coordinates")?.iter().collect(),
+                    property(&["coordTimes", "times", "timestamps"])
+                        .and_then(|times| times.as_array())
+                        .map(|times| times.iter().collect()),
+                ),
+                "MultiLineString" => (
+                    flatten(json::array_field(geometry, "coordinates")?),
+                    property(&["coordTimes", "times", "timestamps"])
+                        .and_then(|times| times.as_array())
+                        .map(|times| flatten(times)),
+                ),
+                _ => continue,
+            };
+            if let Some(times) = &times {
+                if times.len() != positions.len() {
+                    return Err(OsrmError::Trace(format!(
+                        "{} times given for {} coordinates",
+                        times.len(),
+                        positions.len()
+                    )));
+                }
+            }
+
+            for (index, position) in positions.iter().enumerate() {
+                let position = position.as_array().filter(|position| position.len() >= 2);
+                let location = position
+                    .and_then(|position| Some([position[0].as_f64()?, position[1].as_f64()?]));
+                let location = location.ok_or_else(|| {
+                    OsrmError::Trace(format!("coordinate {} is not a position", points.len()))
+                })?;
+
+                let mut point = TracePoint::new(Coordinate::new(location[1], location[0]));
+                if let Some(times) = &times {
+                    let time = times[index];
+                    point.timestamp = time
+                        .as_str()
+                        .and_then(parse_time)
+                        .or_else(|| time.as_i64())
+                        .or_else(|| time.as_f64().map(|time| time as i64));
+                    if point.timestamp.is_none() && !time.is_null() {
+                        return Err(OsrmError::Trace(format!(
+                            "coordinate {} has an invalid time `{}`",
+                            points.len(),
+                            time
+                        )));
+                    }
+                }
+                points.push(point);
+            }
+        }
+
+        Trace::non_empty(
+            points,
+            "GeoJSON has no LineString, MultiPoint or Point coordinates",
+        )
+    }
+
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        self.points
+            .iter()
+            .map(|point| point.coordinate.clone())
+            .collect()
+    }
+
+    // Timestamps are only usable when every point has one.
+    pub fn timestamps(&self) -> Option<Vec<i64>> {
+        self.points.iter().map(|point| point.timestamp).collect()
+    }
+
+    pub fn radiuses(&self) -> Option<Vec<Option<f64>>> {
+        if self.points.iter().all(|point| point.hdop.is_none()) {
+            return None;
+        }
+
+        Some(
+            self.points
+                .iter()
+                .map(|point| point.hdop.map(|hdop| hdop * self.meters_per_hdop))
+                .collect(),
+        )
+    }
+
+    pub fn to_builder(&self) -> MatchRequestBuilder {
+        let mut builder = MatchRequestBuilder::new(&self.coordinates());
+        builder
+            .set_timestamps(self.timestamps())
+            .set_radiuses(self.radiuses());
+
+        builder
+    }
+
+    fn non_empty(points: Vec<TracePoint>, message: &str) -> Result<Trace, OsrmError> {
+        if points.is_empty() {
+            return Err(OsrmError::Trace(message.to_string()));
+        }
+
+        Ok(Trace::new(points))
+    }
+}
+
+struct NmeaFix {
+    coordinate: Coordinate,
+    time: f64,
+    date: Option<i64>,
+    hdop: Option<f64>,
+}
+
+impl NmeaFix {
+    // $GPRMC,hhmmss.ss,A,llll.ll,a,yyyyy.yy,a,speed,course,ddmmyy,...
+    fn from_rmc(fields: &[&str]) -> Option<NmeaFix> {
+        if fields.get(2) != Some(&"A") {
+            return None;
+        }
+
+        let date = fields.get(9).filter(|date| date.len() == 6)?;
+        let year = date[4..6].parse::<i64>().ok()?;
+        Some(NmeaFix {
+            coordinate: nmea_coordinate(fields.get(3..7)?)?,
+            time: nmea_time(fields.get(1)?)?,
+            date: Some(days_from_civil(
+                if year < 80 { 2000 + year } else { 1900 + year },
+                date[2..4].parse().ok()?,
+                date[0..2].parse().ok()?,
+            )),
+            hdop: None,
+        })
+    }
+
+    // $GPGGA,hhmmss.ss,llll.ll,a,yyyyy.yy,a,quality,satellites,hdop,...
+    fn from_gga(fields: &[&str]) -> Option<NmeaFix> {
+        if matches!(fields.get(6), None | Some(&"") | Some(&"0")) {
+            return None;
+        }
+
+        Some(NmeaFix {
+            coordinate: nmea_coordinate(fields.get(2..6)?)?,
+            time: nmea_time(fields.get(1)?)?,
+            date: None,
+            hdop: fields.get(8).and_then(|hdop| hdop.parse().ok()),
+        })
+    }
+}
+
+fn nmea_fields(line: &str) -> Option<Vec<&str>> {
+    let sentence = line.trim().strip_prefix('$')?;
+    let sentence = match sentence.rsplit_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+            if body.bytes().fold(0, |sum, byte| sum ^ byte) != expected {
+                return None;
+            }
+            body
+        }
+        None => sentence,
+    };
+
+    Some(sentence.split(',').collect())
+}
+
+// ddmm.mmmm,N,dddmm.mmmm,E
+fn nmea_coordinate(fields: &[&str]) -> Option<Coordinate> {
+    let degrees = |value: &str, hemisphere: &str, negative: &str| -> Option<f64> {
+        let point = value.find('.').unwrap_or(value.len());
+        let whole = value.get(..point.checked_sub(2)?)?.parse::<f64>().ok()?;
+        let minutes = value.get(point - 2..)?.parse::<f64>().ok()?;
+        let degrees = whole + minutes / 60.0;
+        Some(if hemisphere == negative {
+            -degrees
+        } else {
+            degrees
+        })
+    };
+
+    Some(Coordinate::new(
+        degrees(fields[0], fields[1], "S")?,
+        degrees(fields[2], fields[3], "W")?,
+    ))
+}
+
+// hhmmss.ss as seconds since midnight
+fn nmea_time(time: &str) -> Option<f64> {
+    let hours = time.get(0..2)?.parse::<f64>().ok()?;
+    let minutes = time.get(2..4)?.parse::<f64>().ok()?;
+    let seconds = time.get(4..)?.parse::<f64>().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+// RFC 3339 / ISO 8601 date-times such as `2024-05-01T12:30:00Z` or
+// `2024-05-01T14:30:00.250+02:00`, as Unix seconds with the fraction dropped.
+fn parse_time(time: &str) -> Option<i64> {
+    let number = |range| number_in(time, range);
+    let bytes = time.as_bytes();
+    if bytes.len() < 19
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let days = days_from_civil(number(0..4)?, number(5..7)?, number(8..10)?);
+    let mut seconds =
+        days * 86400 + number(11..13)? * 3600 + number(14..16)? * 60 + number(17..19)?;
+
+    let mut zone = &time[19..];
+    if let Some(fraction) = zone.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        zone = &fraction[digits..];
+    }
+    match zone.as_bytes().first() {
+        None | Some(b'Z') | Some(b'z') if zone.len() <= 1 => {}
+        Some(sign @ (b'+' | b'-')) if zone.len() == 6 && zone.as_bytes()[3] == b':' => {
+            let offset = number_in(zone, 1..3)? * 3600 + number_in(zone, 4..6)? * 60;
+            seconds -= if *sign == b'+' { offset } else { -offset };
+        }
+        _ => return None,
+    }
+
+    Some(seconds)
+}
+
+fn number_in(text: &str, range: std::ops::Range<usize>) -> Option<i64> {
+    text.get(range)?.parse().ok()
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(feature = "json")]
+fn flatten(values: &[serde_json::Value]) -> Vec<&serde_json::Value> {
+    values
+        .iter()
+        .flat_map(|value| value.as_array().into_iter().flatten())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_time, Trace};
+
+    #[test]
+    fn nmea_sentences_become_a_match_request() {
+        assert_eq!(parse_time("1970-01-02T00:00:01Z"), Some(86401));
+        assert_eq!(
+            parse_time("2024-05-01T14:30:00.250+02:00"),
+            Some(1714566600)
+        );
+        assert_eq!(parse_time("2024-05-01"), None);
+
+        let nmea = "\
+$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47
+$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A
+$GPGGA,123520,4807.138,N,01131.100,E,0,08,0.9,545.4,M,46.9,M,,*00
+$GPRMC,123521,V,4807.238,N,01131.200,E,022.4,084.4,230394,003.1,W*00
+$GPGGA,123522,4807.338,S,01131.300,W,1,08,2.0,545.4,M,46.9,M,,*00
+garbage";
+        let nmea = nmea
+            .lines()
+            .enumerate()
+            .map(|(index, line)| match (index, line.rsplit_once('*')) {
+                (1..=4, Some((body, _))) => format!("{}*{:02X}", body, checksum(&body[1..])),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let trace = Trace::from_nmea(&nmea).unwrap();
+        assert_eq!(trace.points.len(), 2);
+        assert!((trace.points[0].coordinate.latitude - 48.1173).abs() < 1e-9);
+        assert!((trace.points[0].coordinate.longitude - 11.516_666_666).abs() < 1e-6);
+        assert!(trace.points[1].coordinate.latitude < 0.0);
+        assert!(trace.points[1].coordinate.longitude < 0.0);
+
+        // 1994-03-23 12:35:19 UTC
+        assert_eq!(trace.timestamps(), Some(vec![764426119, 764426122]));
+        assert_eq!(trace.radiuses(), Some(vec![Some(4.5), Some(10.0)]));
+        assert!(trace.to_builder().build().is_ok());
+
+        assert!(Trace::from_nmea("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9*00").is_err());
+    }
+
+    fn checksum(body: &str) -> u8 {
+        body.bytes().fold(0, |sum, byte| sum ^ byte)
+    }
+
+    #[cfg(feature = "gpx")]
+    #[test]
+    fn gpx_tracks_become_a_match_request() {
+        let gpx = r#"<?xml version="1.0"?>
+<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk><trkseg>
+    <trkpt lat="52.5" lon="13.4"><time>2024-05-01T12:00:00Z</time><hdop>1.5</hdop></trkpt>
+    <trkpt lat="52.501" lon="13.401"><time>2024-05-01T12:00:05Z</time></trkpt>
+  </trkseg></trk>
+</gpx>"#;
+
+        let trace = Trace::from_gpx(gpx).unwrap();
+        assert_eq!(trace.coordinates()[1].longitude, 13.401);
+        assert_eq!(trace.timestamps(), Some(vec![1714564800, 1714564805]));
+        assert_eq!(trace.radiuses(), Some(vec![Some(7.5), None]));
+        assert!(Trace::from_gpx("<gpx/>").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn geojson_lines_become_a_match_request() {
+        let geojson = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "properties": {"coordTimes": ["2024-05-01T12:00:00Z", 1714564805]},
+             "geometry": {"type": "LineString", "coordinates": [[13.4, 52.5, 34.0], [13.401, 52.501]]}},
+            {"type": "Feature", "properties": {"name": "stop"},
+             "geometry": {"type": "Polygon", "coordinates": []}}
+        ]}"#;
+
+        let trace = Trace::from_geojson(geojson).unwrap();
+        assert_eq!(trace.coordinates()[0].latitude, 52.5);
+        assert_eq!(trace.timestamps(), Some(vec![1714564800, 1714564805]));
+        assert_eq!(trace.radiuses(), None);
+
+        let untimed = r#"{"type": "MultiPoint", "coordinates": [[13.4, 52.5], [13.401, 52.501]]}"#;
+        assert_eq!(Trace::from_geojson(untimed).unwrap().timestamps(), None);
+    }
+}
@@ -1,6 +1,10 @@
 use std::os::raw::c_int;
 
 use crate::Osrm;
+use crate::engine_config::engine_limits::EngineLimits;
+use crate::general::validation::ValidationError;
+use crate::backend::RoutingBackend;
+use crate::osrm_error::OsrmError;
 use crate::Status;
 use crate::general::c_structs::c_general_options::CGeneralOptions;
 use crate::general::rs_structs::general_options::GeneralOptions;
@@ -24,13 +28,32 @@ impl From<&mut NearestRequest> for CNearestRequest {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NearestRequest {
     pub(crate) general_options: GeneralOptions,
     pub(crate) number_of_results: i32,
 }
 
 impl NearestRequest {
-    pub fn run(&mut self, osrm: &Osrm) -> (Status, NearestResult) {
+    pub fn run<B: RoutingBackend + ?Sized>(&mut self, backend: &B) -> Result<NearestResult, OsrmError> {
+        backend.nearest(self)
+    }
+
+    pub(crate) fn check_limits(&self, limits: &EngineLimits) -> Result<(), ValidationError> {
+        let limit = limits.max_results_nearest;
+        if limit > 0 && self.number_of_results > limit {
+            return Err(ValidationError::TooManyResults {
+                field: "number_of_results",
+                limit: limit as usize,
+                actual: self.number_of_results as usize,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn run_osrm(&mut self, osrm: &Osrm) -> Result<NearestResult, OsrmError> {
+        self.check_limits(osrm.limits())?;
         unsafe {
             let mut result: *mut CNearestResult = std::ptr::null_mut();
             let result_ptr: *mut *mut CNearestResult = &mut result;
@@ -41,11 +64,21 @@ impl NearestRequest {
                 result_ptr,
             );
 
-            let converted_result = NearestResult::new(&(*result));
+            if result.is_null() {
+                return Err(OsrmError::Ffi("osrm_nearest returned no result".to_string()));
+            }
+
+            let converted_result = NearestResult::new(&(*result), &osrm.string_decoding);
 
             nearest_result_destroy(result);
 
-            (status, converted_result)
+            let converted_result = converted_result?;
+
+            if status == Status::Ok {
+                Ok(converted_result)
+            } else {
+                Err(OsrmError::from_code(converted_result.code, converted_result.message))
+            }
         }
     }
 }
@@ -1,19 +1,22 @@
 use std::ffi::CString;
 
-use crate::general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate};
+use crate::{engine_config::engine_limits::EngineLimits, general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate, validation::{self, ValidationError}}};
 
 use super::nearest_request::NearestRequest;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NearestRequestBuilder {
     general_options: GeneralOptions,
     number_of_results: i32,
+    limits: EngineLimits,
 }
 
 impl NearestRequestBuilder {
     pub fn new(lat: f64, lng: f64) -> NearestRequestBuilder {
         NearestRequestBuilder {
             general_options: GeneralOptions::new(&vec![Coordinate { latitude: lat, longitude: lng}]),
-            number_of_results: 1
+            number_of_results: 1,
+            limits: EngineLimits::unlimited(),
         }
     }
 
@@ -22,11 +25,28 @@ impl NearestRequestBuilder {
         self
     }
 
-    pub fn build(&self) -> Result<NearestRequest, String> {
-        Ok(NearestRequest {
+    pub fn set_limits<'a>(&'a mut self, limits: &EngineLimits) -> &'a mut Self {
+        self.limits = limits.clone();
+        self
+    }
+
+    pub fn build(&self) -> Result<NearestRequest, ValidationError> {
+        validation::check_general_options(&self.general_options, 1)?;
+        validation::check_coordinate_limit(&self.general_options, 1)?;
+        if self.number_of_results < 1 {
+            return Err(ValidationError::InvalidValue {
+                field: "number_of_results",
+                value: self.number_of_results as f64,
+            });
+        }
+
+        let request = NearestRequest {
             general_options: self.general_options.clone(),
             number_of_results: self.number_of_results,
-        })
+        };
+        request.check_limits(&self.limits)?;
+
+        Ok(request)
     }
     
 }
@@ -1,8 +1,12 @@
 use core::slice;
-use std::borrow::ToOwned;
-use std::ffi::CStr;
 use std::os::raw::{c_char, c_int};
 
+use crate::general::c_string_to_option_string;
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::osrm_error::OsrmError;
+use crate::StringDecoding;
+
 use super::nearest_waypoint::{CNearestWaypoint, NearestWaypoint};
 
 
@@ -16,6 +20,7 @@ pub struct CNearestResult {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NearestResult {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -23,41 +28,71 @@ pub struct NearestResult {
 }
 
 impl NearestResult {
-    pub fn new(c_reasult: &CNearestResult) -> NearestResult {
-        unsafe {
-            NearestResult {
-                code: if c_reasult.code != std::ptr::null_mut() {
-                    CStr::from_ptr(c_reasult.code as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                message: if c_reasult.message != std::ptr::null_mut() {
-                    CStr::from_ptr(c_reasult.message as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                waypoints: if c_reasult.waypoints != std::ptr::null_mut() {
+    pub fn new(
+        c_reasult: &CNearestResult,
+        string_decoding: &StringDecoding,
+    ) -> Result<NearestResult, OsrmError> {
+        Ok(NearestResult {
+            code: c_string_to_option_string(c_reasult.code, string_decoding)?,
+            message: c_string_to_option_string(c_reasult.message, string_decoding)?,
+            waypoints: if c_reasult.waypoints != std::ptr::null_mut() {
+                Some(
+                    unsafe {
                         slice::from_raw_parts(
                             c_reasult.waypoints,
                             c_reasult.number_of_waypoints as usize,
                         )
-                        .iter()
-                        .map(|waypoint| NearestWaypoint::new(waypoint))
-                        .collect::<Vec<NearestWaypoint>>()
-                        .into()
-                    
-                } else {
-                    None
-                },
-            }
+                    }
+                    .iter()
+                    .map(|waypoint| NearestWaypoint::new(waypoint, string_decoding))
+                    .collect::<Result<Vec<NearestWaypoint>, OsrmError>>()?,
+                )
+            } else {
+                None
+            },
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_osrm_json(response: &str) -> Result<NearestResult, OsrmError> {
+        let value = json::parse(response)?;
+        json::check_code(&value)?;
+        NearestResult::from_json(&value)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_osrm_json(&self) -> String {
+        json::render(&self.to_json())
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut result = serde_json::json!({
+            "code": self.code.clone().unwrap_or_else(|| "Ok".to_string()),
+        });
+        json::insert_opt_string(&mut result, "message", &self.message);
+        if let Some(waypoints) = &self.waypoints {
+            result["waypoints"] = serde_json::Value::Array(
+                waypoints.iter().map(NearestWaypoint::to_json).collect(),
+            );
         }
+        result
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<NearestResult, OsrmError> {
+        Ok(NearestResult {
+            code: json::opt_string_field(value, "code"),
+            message: json::opt_string_field(value, "message"),
+            waypoints: match json::opt_array_field(value, "waypoints") {
+                Some(waypoints) => Some(
+                    waypoints
+                        .iter()
+                        .map(NearestWaypoint::from_json)
+                        .collect::<Result<Vec<NearestWaypoint>, OsrmError>>()?,
+                ),
+                None => None,
+            },
+        })
     }
 }
@@ -1,7 +1,11 @@
-use std::{
-    ffi::CStr,
-    os::raw::{c_char, c_double, c_longlong},
-    ptr::null,
+use std::os::raw::{c_char, c_double, c_longlong};
+
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_option_string, c_string_to_string},
+    osrm_error::OsrmError,
+    StringDecoding,
 };
 
 #[repr(C)]
@@ -15,6 +19,7 @@ pub struct CNearestWaypoint {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NearestWaypoint {
     pub nodes: [i64; 2],
     pub hint: Option<String>,
@@ -24,26 +29,50 @@ pub struct NearestWaypoint {
 }
 
 impl NearestWaypoint {
-    pub fn new(c_waypoints: &CNearestWaypoint) -> NearestWaypoint {
-        unsafe {
-            NearestWaypoint {
-                nodes: c_waypoints.nodes,
-                hint: if c_waypoints.hint != null() {
-                    CStr::from_ptr(c_waypoints.hint as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                distance: c_waypoints.distance,
-                name: CStr::from_ptr(c_waypoints.name as *const c_char)
-                    .to_str()
-                    .unwrap()
-                    .to_owned(),
-                location: c_waypoints.location,
-            }
+    pub fn new(
+        c_waypoints: &CNearestWaypoint,
+        string_decoding: &StringDecoding,
+    ) -> Result<NearestWaypoint, OsrmError> {
+        Ok(NearestWaypoint {
+            nodes: c_waypoints.nodes,
+            hint: c_string_to_option_string(c_waypoints.hint, string_decoding)?,
+            distance: c_waypoints.distance,
+            name: c_string_to_string(c_waypoints.name, string_decoding)?,
+            location: c_waypoints.location,
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut waypoint = serde_json::json!({ "nodes": self.nodes });
+        json::insert_opt_string(&mut waypoint, "hint", &self.hint);
+        waypoint["distance"] = serde_json::json!(self.distance);
+        waypoint["name"] = serde_json::json!(self.name);
+        waypoint["location"] = serde_json::json!(self.location);
+        waypoint
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<NearestWaypoint, OsrmError> {
+        let nodes = json::array_field(value, "nodes")?;
+        if nodes.len() != 2 {
+            return Err(OsrmError::Json("field `nodes` is not a pair".to_string()));
         }
+
+        Ok(NearestWaypoint {
+            nodes: [
+                nodes[0].as_i64().unwrap_or_default(),
+                nodes[1].as_i64().unwrap_or_default(),
+            ],
+            hint: json::opt_string_field(value, "hint"),
+            distance: json::opt_f64_field(value, "distance").unwrap_or_default(),
+            name: json::opt_string_field(value, "name").unwrap_or_default(),
+            location: json::location_field(value, "location")?,
+        })
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn point(&self) -> geo_types::Point<f64> {
+        geo_types::Point::new(self.location[0], self.location[1])
     }
 }
@@ -0,0 +1,174 @@
+use core::fmt::Display;
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OsrmError {
+    InvalidUrl(String),
+    InvalidService(String),
+    InvalidVersion(String),
+    InvalidOptions(String),
+    InvalidQuery(String),
+    InvalidValue(String),
+    NoSegment(String),
+    TooBig(String),
+    DisabledDataset(String),
+    NoRoute(String),
+    NoTable(String),
+    NoMatch(String),
+    NoTrips(String),
+    NotImplemented(String),
+    Unknown { code: String, message: String },
+    InvalidUtf8(String),
+    Json(String),
+    Http(String),
+    Ffi(String),
+    Mvt(String),
+    Io(String),
+    Trace(String),
+}
+
+impl OsrmError {
+    pub(crate) fn from_code(code: Option<String>, message: Option<String>) -> OsrmError {
+        let message = message.unwrap_or_default();
+
+        let code = match code {
+            Some(code) => code,
+            None => return OsrmError::Ffi(message),
+        };
+
+        match code.as_str() {
+            "InvalidUrl" => OsrmError::InvalidUrl(message),
+            "InvalidService" => OsrmError::InvalidService(message),
+            "InvalidVersion" => OsrmError::InvalidVersion(message),
+            "InvalidOptions" => OsrmError::InvalidOptions(message),
+            "InvalidQuery" => OsrmError::InvalidQuery(message),
+            "InvalidValue" => OsrmError::InvalidValue(message),
+            "NoSegment" => OsrmError::NoSegment(message),
+            "TooBig" => OsrmError::TooBig(message),
+            "DisabledDataset" => OsrmError::DisabledDataset(message),
+            "NoRoute" => OsrmError::NoRoute(message),
+            "NoTable" => OsrmError::NoTable(message),
+            "NoMatch" => OsrmError::NoMatch(message),
+            "NoTrips" => OsrmError::NoTrips(message),
+            "NotImplemented" => OsrmError::NotImplemented(message),
+            _ => OsrmError::Unknown { code, message },
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            OsrmError::InvalidUrl(_) => "InvalidUrl",
+            OsrmError::InvalidService(_) => "InvalidService",
+            OsrmError::InvalidVersion(_) => "InvalidVersion",
+            OsrmError::InvalidOptions(_) => "InvalidOptions",
+            OsrmError::InvalidQuery(_) => "InvalidQuery",
+            OsrmError::InvalidValue(_) => "InvalidValue",
+            OsrmError::NoSegment(_) => "NoSegment",
+            OsrmError::TooBig(_) => "TooBig",
+            OsrmError::DisabledDataset(_) => "DisabledDataset",
+            OsrmError::NoRoute(_) => "NoRoute",
+            OsrmError::NoTable(_) => "NoTable",
+            OsrmError::NoMatch(_) => "NoMatch",
+            OsrmError::NoTrips(_) => "NoTrips",
+            OsrmError::NotImplemented(_) => "NotImplemented",
+            OsrmError::Unknown { code, .. } => code,
+            OsrmError::InvalidUtf8(_) => "InvalidUtf8",
+            OsrmError::Json(_) => "Json",
+            OsrmError::Http(_) => "Http",
+            OsrmError::Ffi(_) => "Ffi",
+            OsrmError::Mvt(_) => "Mvt",
+            OsrmError::Io(_) => "Io",
+            OsrmError::Trace(_) => "Trace",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            OsrmError::InvalidUrl(message)
+            | OsrmError::InvalidService(message)
+            | OsrmError::InvalidVersion(message)
+            | OsrmError::InvalidOptions(message)
+            | OsrmError::InvalidQuery(message)
+            | OsrmError::InvalidValue(message)
+            | OsrmError::NoSegment(message)
+            | OsrmError::TooBig(message)
+            | OsrmError::DisabledDataset(message)
+            | OsrmError::NoRoute(message)
+            | OsrmError::NoTable(message)
+            | OsrmError::NoMatch(message)
+            | OsrmError::NoTrips(message)
+            | OsrmError::NotImplemented(message)
+            | OsrmError::Unknown { message, .. }
+            | OsrmError::InvalidUtf8(message)
+            | OsrmError::Json(message)
+            | OsrmError::Http(message)
+            | OsrmError::Ffi(message)
+            | OsrmError::Mvt(message)
+            | OsrmError::Io(message)
+            | OsrmError::Trace(message) => message,
+        }
+    }
+}
+
+impl Display for OsrmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.message().is_empty() {
+            write!(f, "{}", self.code())
+        } else {
+            write!(f, "{}: {}", self.code(), self.message())
+        }
+    }
+}
+
+impl Error for OsrmError {}
+
+#[cfg(test)]
+mod tests {
+    use super::OsrmError;
+
+    #[test]
+    fn every_osrm_code_maps_to_its_variant() {
+        let codes = [
+            "InvalidUrl",
+            "InvalidService",
+            "InvalidVersion",
+            "InvalidOptions",
+            "InvalidQuery",
+            "InvalidValue",
+            "NoSegment",
+            "TooBig",
+            "DisabledDataset",
+            "NoRoute",
+            "NoTable",
+            "NoMatch",
+            "NoTrips",
+            "NotImplemented",
+        ];
+        for code in codes {
+            let error = OsrmError::from_code(Some(code.to_string()), Some("message".to_string()));
+            assert!(!matches!(error, OsrmError::Unknown { .. }), "{}", code);
+            assert_eq!(error.code(), code);
+            assert_eq!(error.message(), "message");
+            assert_eq!(error.to_string(), format!("{}: message", code));
+        }
+
+        let error = OsrmError::from_code(Some("NoRoute".to_string()), None);
+        assert_eq!(error, OsrmError::NoRoute(String::new()));
+        assert_eq!(error.to_string(), "NoRoute");
+
+        let error = OsrmError::from_code(Some("Overloaded".to_string()), Some("busy".to_string()));
+        assert_eq!(
+            error,
+            OsrmError::Unknown {
+                code: "Overloaded".to_string(),
+                message: "busy".to_string()
+            }
+        );
+        assert_eq!(error.code(), "Overloaded");
+
+        assert_eq!(
+            OsrmError::from_code(None, Some("no response".to_string())),
+            OsrmError::Ffi("no response".to_string())
+        );
+    }
+}
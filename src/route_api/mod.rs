@@ -21,6 +21,7 @@ extern "C" {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GeometriesType {
     Polyline,
     Polyline6,
@@ -29,6 +30,7 @@ pub enum GeometriesType {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OverviewType {
     Simplified,
     Full,
@@ -37,6 +39,7 @@ pub enum OverviewType {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AnnotationsType {
     None,
     Duration,
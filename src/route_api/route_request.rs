@@ -1,6 +1,6 @@
 use std::os::raw::c_int;
 
-use crate::{Boolean, Osrm, Status, general::{c_structs::{c_general_options::CGeneralOptions}, rs_structs::{general_options::GeneralOptions}}};
+use crate::{Boolean, Osrm, Status, backend::RoutingBackend, engine_config::engine_limits::EngineLimits, general::{c_structs::{c_general_options::CGeneralOptions}, rs_structs::{general_options::GeneralOptions}, validation::{self, ValidationError}}, osrm_error::OsrmError};
 
 use super::{
     osrm_route,
@@ -57,6 +57,7 @@ impl From<&mut RouteRequest> for CRouteRequest {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RouteRequest {
     pub(crate) general_options: GeneralOptions,
     pub(crate) steps: bool,
@@ -71,24 +72,53 @@ pub struct RouteRequest {
 }
 
 impl RouteRequest {
+    pub fn run<B: RoutingBackend + ?Sized>(&mut self, backend: &B) -> Result<RouteResult, OsrmError> {
+        backend.route(self)
+    }
+
+    pub(crate) fn check_limits(&self, limits: &EngineLimits) -> Result<(), ValidationError> {
+        validation::check_coordinate_limit(&self.general_options, limits.max_locations_viaroute)?;
+        if self.alternatives && limits.max_alternatives >= 0 {
+            let alternatives = self.number_of_alternatives.max(1) as usize;
+            if alternatives > limits.max_alternatives as usize {
+                return Err(ValidationError::TooManyResults {
+                    field: "alternatives",
+                    limit: limits.max_alternatives as usize,
+                    actual: alternatives,
+                });
+            }
+        }
 
+        Ok(())
+    }
 
-    pub fn run(&mut self, osrm: &Osrm) -> (Status, RouteResult) {
+    pub(crate) fn run_osrm(&mut self, osrm: &Osrm) -> Result<RouteResult, OsrmError> {
+        self.check_limits(osrm.limits())?;
         unsafe {
             let mut result: *mut CRouteResult = std::ptr::null_mut();
             let result_ptr: *mut *mut CRouteResult = &mut result;
 
             let status = osrm_route(
                 *osrm.config,
-                &mut CRouteRequest::from(self) as *mut CRouteRequest,
+                &mut CRouteRequest::from(&mut *self) as *mut CRouteRequest,
                 result_ptr,
             );
 
-            let converted_result = RouteResult::from(&(*result));
+            if result.is_null() {
+                return Err(OsrmError::Ffi("osrm_route returned no result".to_string()));
+            }
+
+            let converted_result = RouteResult::new(&(*result), &osrm.string_decoding, &self.geometries);
 
             route_result_destroy(result);
 
-            (status, converted_result)
+            let converted_result = converted_result?;
+
+            if status == Status::Ok {
+                Ok(converted_result)
+            } else {
+                Err(OsrmError::from_code(converted_result.code, converted_result.message))
+            }
         }
     }
 }
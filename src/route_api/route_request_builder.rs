@@ -1,9 +1,10 @@
 use std::ffi::CString;
 
-use crate::general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate};
+use crate::{engine_config::engine_limits::EngineLimits, general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate, validation::{self, ValidationError}}};
 
 use super::{AnnotationsType, GeometriesType, OverviewType, route_request::RouteRequest};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RouteRequestBuilder {
     general_options: GeneralOptions,
     steps: bool,
@@ -15,10 +16,11 @@ pub struct RouteRequestBuilder {
     overview: OverviewType,
     continue_straight: Option<bool>,
     waypoints: Option<Vec<u64>>,
+    limits: EngineLimits,
 }
 
 impl RouteRequestBuilder {
-    pub fn new(coordinates: &Vec<Coordinate>) -> RouteRequestBuilder {
+    pub fn new<C: Clone + Into<Coordinate>>(coordinates: &[C]) -> RouteRequestBuilder {
         RouteRequestBuilder {
             general_options: GeneralOptions::new(coordinates),
             steps: false,
@@ -30,6 +32,7 @@ impl RouteRequestBuilder {
             overview: OverviewType::Simplified,
             continue_straight: None,
             waypoints: None,
+            limits: EngineLimits::unlimited(),
         }
     }
 
@@ -78,21 +81,33 @@ impl RouteRequestBuilder {
         self
     }
 
-    pub fn build(&self) -> Result<RouteRequest, String> {
-        Ok(
-            RouteRequest {
-                general_options: self.general_options.clone(),
-                steps: self.steps,
-                alternatives: self.alternatives,
-                number_of_alternatives: self.number_of_alternatives,
-                annotations: self.annotations,
-                annotations_type: self.annotations_type.clone(),
-                geometries: self.geometries.clone(),
-                overview: self.overview.clone(),
-                continue_straight: self.continue_straight,
-                waypoints: self.waypoints.clone(),
-            }
-        )
+    pub fn set_limits<'a>(&'a mut self, limits: &EngineLimits) -> &'a mut Self {
+        self.limits = limits.clone();
+        self
+    }
+
+    pub fn build(&self) -> Result<RouteRequest, ValidationError> {
+        validation::check_general_options(&self.general_options, 2)?;
+        if let Some(waypoints) = &self.waypoints {
+            let waypoints: Vec<i64> = waypoints.iter().map(|&waypoint| waypoint as i64).collect();
+            validation::check_waypoints(&waypoints, self.general_options.coordinate.len())?;
+        }
+
+        let request = RouteRequest {
+            general_options: self.general_options.clone(),
+            steps: self.steps,
+            alternatives: self.alternatives,
+            number_of_alternatives: self.number_of_alternatives,
+            annotations: self.annotations,
+            annotations_type: self.annotations_type.clone(),
+            geometries: self.geometries.clone(),
+            overview: self.overview.clone(),
+            continue_straight: self.continue_straight,
+            waypoints: self.waypoints.clone(),
+        };
+        request.check_limits(&self.limits)?;
+
+        Ok(request)
     }
 }
 
@@ -1,14 +1,22 @@
 use std::{
-    ffi::CStr,
     os::raw::{c_char, c_int},
     slice,
 };
 
-use crate::general::{
-    c_structs::{c_route::COsrmRoute, c_waypoint::CWaypoint},
-    rs_structs::{route::Route, waypoint::Waypoint},
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{
+        c_string_to_option_string,
+        c_structs::{c_route::COsrmRoute, c_waypoint::CWaypoint},
+        rs_structs::{route::Route, waypoint::Waypoint},
+    },
+    osrm_error::OsrmError,
+    StringDecoding,
 };
 
+use super::GeometriesType;
+
 #[repr(C)]
 pub(crate) struct CRouteResult {
     code: *const c_char,
@@ -20,6 +28,7 @@ pub(crate) struct CRouteResult {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RouteResult {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -27,51 +36,99 @@ pub struct RouteResult {
     pub routes: Vec<Route>,
 }
 
-impl From<&CRouteResult> for RouteResult {
-    fn from(c_reasult: &CRouteResult) -> Self {
-        unsafe {
-            RouteResult {
-                code: if c_reasult.code != std::ptr::null_mut() {
-                    CStr::from_ptr(c_reasult.code as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                message: if c_reasult.message != std::ptr::null_mut() {
-                    CStr::from_ptr(c_reasult.message as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
-                        .into()
-                } else {
-                    None
-                },
-                waypoints: if c_reasult.waypoints != std::ptr::null_mut() {
+impl RouteResult {
+    pub(crate) fn new(
+        c_reasult: &CRouteResult,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<RouteResult, OsrmError> {
+        Ok(RouteResult {
+            code: c_string_to_option_string(c_reasult.code, string_decoding)?,
+            message: c_string_to_option_string(c_reasult.message, string_decoding)?,
+            waypoints: if c_reasult.waypoints != std::ptr::null_mut() {
+                unsafe {
                     slice::from_raw_parts(
                         c_reasult.waypoints,
                         c_reasult.number_of_waypoints as usize,
                     )
-                    .iter()
-                    .map(|waypoint| waypoint.into())
-                    .collect()
-                } else {
-                    Vec::new()
-                },
-                routes: if c_reasult.routes != std::ptr::null_mut() {
+                }
+                .iter()
+                .map(|waypoint| Waypoint::new(waypoint, string_decoding))
+                .collect::<Result<Vec<Waypoint>, OsrmError>>()?
+            } else {
+                Vec::new()
+            },
+            routes: if c_reasult.routes != std::ptr::null_mut() {
+                unsafe {
                     slice::from_raw_parts(
                         c_reasult.routes,
                         c_reasult.number_of_routes as usize,
                     )
-                    .iter()
-                    .map(|route| route.into())
-                    .collect()
-                } else {
-                    Vec::new()
-                },
-            }
+                }
+                .iter()
+                .map(|route| Route::new(route, string_decoding, geometries))
+                .collect::<Result<Vec<Route>, OsrmError>>()?
+            } else {
+                Vec::new()
+            },
+        })
+    }
+
+    // `geometries` must be the value the response was requested with: polyline and polyline6
+    // strings look alike, and decoding one as the other is off by a factor of ten.
+    #[cfg(feature = "json")]
+    pub fn from_osrm_json(
+        response: &str,
+        geometries: &GeometriesType,
+    ) -> Result<RouteResult, OsrmError> {
+        let value = json::parse(response)?;
+        json::check_code(&value)?;
+        RouteResult::from_json(&value, geometries)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_osrm_json(&self) -> String {
+        json::render(&self.to_json())
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut result = serde_json::json!({
+            "code": self.code.clone().unwrap_or_else(|| "Ok".to_string()),
+        });
+        json::insert_opt_string(&mut result, "message", &self.message);
+        result["routes"] =
+            serde_json::Value::Array(self.routes.iter().map(Route::to_json).collect());
+        if !self.waypoints.is_empty() {
+            result["waypoints"] = serde_json::Value::Array(
+                self.waypoints.iter().map(Waypoint::to_json).collect(),
+            );
         }
+        result
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<RouteResult, OsrmError> {
+        Ok(RouteResult {
+            code: json::opt_string_field(value, "code"),
+            message: json::opt_string_field(value, "message"),
+            waypoints: match json::opt_array_field(value, "waypoints") {
+                Some(waypoints) => waypoints
+                    .iter()
+                    .map(Waypoint::from_json)
+                    .collect::<Result<Vec<Waypoint>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+            routes: match json::opt_array_field(value, "routes") {
+                Some(routes) => routes
+                    .iter()
+                    .map(|route| Route::from_json(route, geometries))
+                    .collect::<Result<Vec<Route>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
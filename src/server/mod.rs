@@ -0,0 +1,285 @@
+use std::{sync::Arc, thread};
+
+use crate::{backend::RoutingBackend, general::json, osrm_error::OsrmError, Osrm};
+
+mod url_parser;
+
+pub struct ServerResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl ServerResponse {
+    fn json(value: serde_json::Value) -> ServerResponse {
+        ServerResponse {
+            status: 200,
+            content_type: "application/json; charset=UTF-8",
+            body: json::render(&value).into_bytes(),
+        }
+    }
+
+    fn error(error: OsrmError) -> ServerResponse {
+        let status = match error {
+            OsrmError::Ffi(_)
+            | OsrmError::Json(_)
+            | OsrmError::Http(_)
+            | OsrmError::InvalidUtf8(_)
+            | OsrmError::Mvt(_)
+            | OsrmError::Io(_) => 500,
+            _ => 400,
+        };
+
+        ServerResponse {
+            status,
+            content_type: "application/json; charset=UTF-8",
+            body: serde_json::json!({ "code": error.code(), "message": error.message() })
+                .to_string()
+                .into_bytes(),
+        }
+    }
+}
+
+pub fn handle_request<B: RoutingBackend + ?Sized>(backend: &B, url: &str) -> ServerResponse {
+    match dispatch(backend, url) {
+        Ok(response) => response,
+        Err(error) => ServerResponse::error(error),
+    }
+}
+
+fn dispatch<B: RoutingBackend + ?Sized>(
+    backend: &B,
+    url: &str,
+) -> Result<ServerResponse, OsrmError> {
+    let parsed = url_parser::parse_url(url)?;
+    let limits = backend.engine_limits();
+
+    match parsed.service.as_str() {
+        "route" => Ok(ServerResponse::json(
+            url_parser::route_request(&parsed, &limits)?
+                .run(backend)?
+                .to_json(),
+        )),
+        "table" => Ok(ServerResponse::json(
+            url_parser::table_request(&parsed, &limits)?
+                .run(backend)?
+                .to_json(),
+        )),
+        "match" => Ok(ServerResponse::json(
+            url_parser::match_request(&parsed, &limits)?
+                .run(backend)?
+                .to_json(),
+        )),
+        "trip" => Ok(ServerResponse::json(
+            url_parser::trip_request(&parsed, &limits)?
+                .run(backend)?
+                .to_json(),
+        )),
+        "nearest" => Ok(ServerResponse::json(
+            url_parser::nearest_request(&parsed, &limits)?
+                .run(backend)?
+                .to_json(),
+        )),
+        "tile" => Ok(ServerResponse {
+            status: 200,
+            content_type: "application/x-protobuf",
+            body: url_parser::tile_request(&parsed)?.run(backend)?.result,
+        }),
+        service => Err(OsrmError::InvalidService(format!(
+            "Service {} not found!",
+            service
+        ))),
+    }
+}
+
+pub fn serve(osrm: Osrm, address: &str, threads: usize) -> Result<(), String> {
+    let server = Arc::new(tiny_http::Server::http(address).map_err(|e| e.to_string())?);
+    let osrm = Arc::new(osrm);
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..threads.max(1))
+        .map(|_| {
+            let server = server.clone();
+            let osrm = osrm.clone();
+
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let response = handle_request(osrm.as_ref(), request.url());
+
+                    let http_response = tiny_http::Response::from_data(response.body)
+                        .with_status_code(response.status)
+                        .with_header(header("Content-Type", response.content_type))
+                        .with_header(header("Access-Control-Allow-Origin", "*"));
+
+                    if let Err(e) = request.respond(http_response) {
+                        eprintln!("failed to send response: {}", e);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    Ok(())
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes())
+        .expect("static header is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::fake_backend::FakeBackend,
+        engine_config::engine_limits::EngineLimits,
+        general::rs_structs::{coordinate::Coordinate, geometry::encode_polyline},
+        nearest_api::{nearest_result::NearestResult, nearest_waypoint::NearestWaypoint},
+        osrm_error::OsrmError,
+        tile_api::tile_result::TileResult,
+    };
+
+    use super::handle_request;
+
+    fn nearest_only() -> FakeBackend<'static> {
+        FakeBackend::new()
+            .on_route(|_| {
+                Err(OsrmError::NoRoute(
+                    "Impossible route between points".to_string(),
+                ))
+            })
+            .on_nearest(|request| {
+                let coordinate = &request.general_options.coordinate[0];
+
+                Ok(NearestResult {
+                    code: Some("Ok".to_string()),
+                    message: None,
+                    waypoints: Some(
+                        (0..request.number_of_results)
+                            .map(|index| NearestWaypoint {
+                                nodes: [index as i64, index as i64 + 1],
+                                hint: None,
+                                distance: 0.0,
+                                name: "Main Street".to_string(),
+                                location: [coordinate.longitude, coordinate.latitude],
+                            })
+                            .collect(),
+                    ),
+                })
+            })
+            .on_tile(|_| {
+                Ok(TileResult {
+                    result: vec![1, 2, 3],
+                })
+            })
+    }
+
+    #[test]
+    fn nearest_url_is_parsed_into_request() {
+        let response = handle_request(
+            &nearest_only(),
+            "/nearest/v1/driving/13.388860,52.517037.json?number=2",
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(body["code"], "Ok");
+        assert_eq!(body["waypoints"].as_array().unwrap().len(), 2);
+        assert_eq!(body["waypoints"][1]["nodes"], serde_json::json!([1, 2]));
+        assert_eq!(
+            body["waypoints"][0]["location"],
+            serde_json::json!([13.38886, 52.517037])
+        );
+    }
+
+    #[test]
+    fn polyline_coordinates_are_decoded() {
+        let response = handle_request(
+            &nearest_only(),
+            "/nearest/v1/driving/polyline(_p~iF~ps%7CU).json",
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "Ok");
+        assert_eq!(
+            body["waypoints"][0]["location"],
+            serde_json::json!([-120.2, 38.5])
+        );
+
+        let encoded = encode_polyline(&[Coordinate::new(52.517037, 13.38886)], 6);
+        let response = handle_request(
+            &nearest_only(),
+            &format!("/nearest/v1/driving/polyline6({})", encoded),
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(
+            body["waypoints"][0]["location"],
+            serde_json::json!([13.38886, 52.517037])
+        );
+
+        let response = handle_request(&nearest_only(), "/nearest/v1/driving/polyline(_p~iF)");
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "InvalidQuery");
+    }
+
+    #[test]
+    fn errors_use_osrm_codes() {
+        let response = handle_request(
+            &nearest_only(),
+            "/route/v1/driving/13.4,52.5;13.5,52.6?steps=true",
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(response.status, 400);
+        assert_eq!(body["code"], "NoRoute");
+
+        let response = handle_request(&nearest_only(), "/route/v2/driving/13.4,52.5;13.5,52.6");
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "InvalidVersion");
+
+        let response = handle_request(
+            &nearest_only(),
+            "/route/v1/driving/13.4,52.5;13.5,52.6?steps=maybe",
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "InvalidValue");
+
+        let response = handle_request(&nearest_only(), "/isochrone/v1/driving/13.4,52.5");
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "InvalidService");
+    }
+
+    #[test]
+    fn engine_limits_and_annotations_are_checked() {
+        let mut limits = EngineLimits::unlimited();
+        limits.max_locations_viaroute = 2;
+        let backend = nearest_only().with_limits(limits);
+
+        let response = handle_request(&backend, "/route/v1/driving/13.4,52.5;13.5,52.6;13.6,52.7");
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "TooBig");
+
+        let response = handle_request(
+            &backend,
+            "/route/v1/driving/13.4,52.5;13.5,52.6?annotations=duration,distance",
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "InvalidOptions");
+
+        let response = handle_request(
+            &backend,
+            "/route/v1/driving/13.4,52.5;13.5,52.6?annotations=duration,duration",
+        );
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["code"], "NoRoute");
+    }
+
+    #[test]
+    fn tile_url_returns_raw_bytes() {
+        let response = handle_request(&nearest_only(), "/tile/v1/driving/tile(1310,3166,13).mvt");
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "application/x-protobuf");
+        assert_eq!(response.body, vec![1, 2, 3]);
+    }
+}
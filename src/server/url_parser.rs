@@ -0,0 +1,578 @@
+use std::{ffi::CString, os::raw::c_short, str::FromStr};
+
+use crate::{
+    engine_config::engine_limits::EngineLimits,
+    general::{
+        c_structs::{c_approach::Approach, c_bearing::Bearing},
+        rs_structs::{
+            coordinate::Coordinate, general_options::GeneralOptionsTrait, geometry::decode_polyline,
+        },
+    },
+    match_api::{match_request::MatchRequest, match_request_builder::MatchRequestBuilder, Gap},
+    nearest_api::{
+        nearest_request::NearestRequest, nearest_request_builder::NearestRequestBuilder,
+    },
+    osrm_error::OsrmError,
+    route_api::{
+        route_request::RouteRequest, route_request_builder::RouteRequestBuilder, AnnotationsType,
+        GeometriesType, OverviewType,
+    },
+    table_api::{
+        table_request::TableRequest, table_request_builder::TableRequestBuilder, Annotations,
+        FallbackCoordinate,
+    },
+    tile_api::tile_request::TileRequest,
+    trip_api::{
+        trip_end, trip_request::TripRequest, trip_request_builder::TripRequestBuilder, trip_start,
+    },
+};
+
+pub(crate) struct ParsedUrl {
+    pub(crate) service: String,
+    pub(crate) profile: String,
+    pub(crate) coordinates: String,
+    pub(crate) query: Vec<(String, String)>,
+}
+
+pub(crate) fn parse_url(url: &str) -> Result<ParsedUrl, OsrmError> {
+    let (path, query) = match url.find('?') {
+        Some(index) => (&url[..index], &url[index + 1..]),
+        None => (url, ""),
+    };
+
+    let mut segments = path.trim_start_matches('/').splitn(4, '/');
+    let service = segments.next().unwrap_or_default();
+    let version = segments.next().unwrap_or_default();
+    let profile = segments.next().unwrap_or_default();
+    let coordinates = segments.next().unwrap_or_default();
+
+    if service.is_empty() || profile.is_empty() || coordinates.is_empty() {
+        return Err(OsrmError::InvalidUrl(format!(
+            "URL string malformed: {}",
+            url
+        )));
+    }
+
+    if version != "v1" {
+        return Err(OsrmError::InvalidVersion(format!(
+            "Service {} not found for version {}",
+            service, version
+        )));
+    }
+
+    let mut parsed_query = Vec::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let mut parts = pair.splitn(2, '=');
+        let key = percent_decode(parts.next().unwrap_or_default());
+        let value = percent_decode(parts.next().unwrap_or_default());
+        parsed_query.push((key, value));
+    }
+
+    Ok(ParsedUrl {
+        service: service.to_string(),
+        profile: profile.to_string(),
+        coordinates: coordinates.trim_end_matches(".json").to_string(),
+        query: parsed_query,
+    })
+}
+
+pub(crate) fn route_request(
+    parsed: &ParsedUrl,
+    limits: &EngineLimits,
+) -> Result<RouteRequest, OsrmError> {
+    let mut builder = RouteRequestBuilder::new(&parse_coordinates(&parsed.coordinates)?);
+    builder.set_limits(limits);
+
+    for (key, value) in &parsed.query {
+        if apply_general_option(&mut builder, key, value)? {
+            continue;
+        }
+
+        match key.as_str() {
+            "steps" => {
+                builder.set_steps(parse_bool(key, value)?);
+            }
+            "alternatives" => match value.as_str() {
+                "true" | "false" => {
+                    builder.set_alternatives(parse_bool(key, value)?);
+                }
+                number => {
+                    let number: u32 = parse_number(key, number)?;
+                    builder
+                        .set_alternatives(number > 0)
+                        .set_number_of_alternatives(number);
+                }
+            },
+            "annotations" => {
+                let (annotations, annotations_type) = parse_annotations(value)?;
+                builder
+                    .set_annotations(annotations)
+                    .set_annotations_type(annotations_type);
+            }
+            "geometries" => {
+                builder.set_geometries(parse_geometries(value)?);
+            }
+            "overview" => {
+                builder.set_overview(parse_overview(value)?);
+            }
+            "continue_straight" => {
+                builder.set_continue_straight(match value.as_str() {
+                    "default" => None,
+                    _ => Some(parse_bool(key, value)?),
+                });
+            }
+            "waypoints" => {
+                builder.set_waypoints(Some(parse_list(key, value)?));
+            }
+            _ => return Err(unknown_option(key)),
+        }
+    }
+
+    builder.build().map_err(OsrmError::from)
+}
+
+pub(crate) fn table_request(
+    parsed: &ParsedUrl,
+    limits: &EngineLimits,
+) -> Result<TableRequest, OsrmError> {
+    let mut builder = TableRequestBuilder::new(&parse_coordinates(&parsed.coordinates)?);
+    builder.set_limits(limits);
+
+    for (key, value) in &parsed.query {
+        if apply_general_option(&mut builder, key, value)? {
+            continue;
+        }
+
+        match key.as_str() {
+            "sources" => {
+                builder.set_sources(parse_index_list(key, value)?);
+            }
+            "destinations" => {
+                builder.set_destinations(parse_index_list(key, value)?);
+            }
+            "annotations" => {
+                let mut duration = false;
+                let mut distance = false;
+                for annotation in value.split(',') {
+                    match annotation {
+                        "duration" => duration = true,
+                        "distance" => distance = true,
+                        _ => return Err(invalid_value(key, value)),
+                    }
+                }
+
+                builder.set_annotations(match (duration, distance) {
+                    (true, true) => Annotations::ALL,
+                    (false, true) => Annotations::DISTANCE,
+                    _ => Annotations::DURATION,
+                });
+            }
+            "fallback_speed" => {
+                builder.set_fallback_speed(parse_number(key, value)?);
+            }
+            "fallback_coordinate" => {
+                builder.set_fallback_coordinate(match value.as_str() {
+                    "input" => FallbackCoordinate::INPUT,
+                    "snapped" => FallbackCoordinate::SNAPPED,
+                    _ => return Err(invalid_value(key, value)),
+                });
+            }
+            "scale_factor" => {
+                builder.set_scale_factor(parse_number(key, value)?);
+            }
+            _ => return Err(unknown_option(key)),
+        }
+    }
+
+    builder.build().map_err(OsrmError::from)
+}
+
+pub(crate) fn match_request(
+    parsed: &ParsedUrl,
+    limits: &EngineLimits,
+) -> Result<MatchRequest, OsrmError> {
+    let mut builder = MatchRequestBuilder::new(&parse_coordinates(&parsed.coordinates)?);
+    builder.set_limits(limits);
+
+    for (key, value) in &parsed.query {
+        if apply_general_option(&mut builder, key, value)? {
+            continue;
+        }
+
+        match key.as_str() {
+            "steps" => {
+                builder.set_steps(parse_bool(key, value)?);
+            }
+            "geometries" => {
+                builder.set_geometries(parse_geometries(value)?);
+            }
+            "annotations" => {
+                let (annotations, annotations_type) = parse_annotations(value)?;
+                builder
+                    .set_annotations(annotations)
+                    .set_annotations_type(annotations_type);
+            }
+            "overview" => {
+                builder.set_overview(parse_overview(value)?);
+            }
+            "timestamps" => {
+                builder.set_timestamps(Some(parse_list(key, value)?));
+            }
+            "gaps" => {
+                builder.set_gaps(match value.as_str() {
+                    "split" => Gap::Split,
+                    "ignore" => Gap::Ignore,
+                    _ => return Err(invalid_value(key, value)),
+                });
+            }
+            "tidy" => {
+                builder.set_tidy(parse_bool(key, value)?);
+            }
+            "waypoints" => {
+                builder.set_waypoints(Some(parse_list(key, value)?));
+            }
+            _ => return Err(unknown_option(key)),
+        }
+    }
+
+    builder.build().map_err(OsrmError::from)
+}
+
+pub(crate) fn trip_request(
+    parsed: &ParsedUrl,
+    limits: &EngineLimits,
+) -> Result<TripRequest, OsrmError> {
+    let mut builder = TripRequestBuilder::new(&parse_coordinates(&parsed.coordinates)?);
+    builder.set_limits(limits);
+
+    for (key, value) in &parsed.query {
+        if apply_general_option(&mut builder, key, value)? {
+            continue;
+        }
+
+        match key.as_str() {
+            "roundtrip" => {
+                builder.set_roundtrip(parse_bool(key, value)?);
+            }
+            "source" => {
+                builder.set_sources(match value.as_str() {
+                    "any" => trip_start::StartAny,
+                    "first" => trip_start::First,
+                    _ => return Err(invalid_value(key, value)),
+                });
+            }
+            "destination" => {
+                builder.set_destinations(match value.as_str() {
+                    "any" => trip_end::EndAny,
+                    "last" => trip_end::Last,
+                    _ => return Err(invalid_value(key, value)),
+                });
+            }
+            "steps" => {
+                builder.set_steps(parse_bool(key, value)?);
+            }
+            "annotations" => {
+                let (annotations, annotations_type) = parse_annotations(value)?;
+                builder
+                    .set_annotations(annotations)
+                    .set_annotations_type(annotations_type);
+            }
+            "geometries" => {
+                builder.set_geometries(parse_geometries(value)?);
+            }
+            "overview" => {
+                builder.set_overview(parse_overview(value)?);
+            }
+            _ => return Err(unknown_option(key)),
+        }
+    }
+
+    builder.build().map_err(OsrmError::from)
+}
+
+pub(crate) fn nearest_request(
+    parsed: &ParsedUrl,
+    limits: &EngineLimits,
+) -> Result<NearestRequest, OsrmError> {
+    let coordinates = parse_coordinates(&parsed.coordinates)?;
+    if coordinates.len() != 1 {
+        return Err(OsrmError::InvalidOptions(
+            "Only one input coordinate is supported".to_string(),
+        ));
+    }
+
+    let mut builder = NearestRequestBuilder::new(coordinates[0].latitude, coordinates[0].longitude);
+    builder.set_limits(limits);
+
+    for (key, value) in &parsed.query {
+        if apply_general_option(&mut builder, key, value)? {
+            continue;
+        }
+
+        match key.as_str() {
+            "number" => {
+                builder.set_number_of_results(parse_number(key, value)?);
+            }
+            _ => return Err(unknown_option(key)),
+        }
+    }
+
+    builder.build().map_err(OsrmError::from)
+}
+
+pub(crate) fn tile_request(parsed: &ParsedUrl) -> Result<TileRequest, OsrmError> {
+    let tile = parsed
+        .coordinates
+        .strip_prefix("tile(")
+        .and_then(|tile| tile.strip_suffix(").mvt"))
+        .ok_or_else(|| OsrmError::InvalidUrl(format!("Invalid tile: {}", parsed.coordinates)))?;
+
+    let values: Vec<i32> = tile
+        .split(',')
+        .map(|value| parse_number("tile", value))
+        .collect::<Result<Vec<i32>, OsrmError>>()?;
+
+    if values.len() != 3 {
+        return Err(OsrmError::InvalidUrl(format!(
+            "Invalid tile: {}",
+            parsed.coordinates
+        )));
+    }
+
+    Ok(TileRequest::new(values[0], values[1], values[2]))
+}
+
+fn apply_general_option<T: GeneralOptionsTrait>(
+    builder: &mut T,
+    key: &str,
+    value: &str,
+) -> Result<bool, OsrmError> {
+    match key {
+        "bearings" => {
+            let bearings = value
+                .split(';')
+                .map(|bearing| {
+                    if bearing.is_empty() {
+                        return Ok(None);
+                    }
+
+                    let mut parts = bearing.splitn(2, ',');
+                    let bearing_value: c_short =
+                        parse_number(key, parts.next().unwrap_or_default())?;
+                    let range: c_short = parse_number(key, parts.next().unwrap_or_default())?;
+
+                    Ok(Some(Bearing {
+                        bearing: bearing_value,
+                        range,
+                    }))
+                })
+                .collect::<Result<Vec<Option<Bearing>>, OsrmError>>()?;
+            builder.set_bearings(Some(bearings));
+        }
+        "radiuses" => {
+            let radiuses = value
+                .split(';')
+                .map(|radius| match radius {
+                    "" | "unlimited" => Ok(None),
+                    radius => parse_number(key, radius).map(Some),
+                })
+                .collect::<Result<Vec<Option<f64>>, OsrmError>>()?;
+            builder.set_radiuses(Some(radiuses));
+        }
+        "generate_hints" => {
+            builder.set_generate_hints(parse_bool(key, value)?);
+        }
+        "skip_waypoints" => {
+            builder.set_skip_waypoints(parse_bool(key, value)?);
+        }
+        "hints" => {
+            let hints = value
+                .split(';')
+                .map(|hint| CString::new(hint).map_err(|_| invalid_value(key, value)))
+                .collect::<Result<Vec<CString>, OsrmError>>()?;
+            builder.set_hints(Some(hints));
+        }
+        "approaches" => {
+            let approaches = value
+                .split(';')
+                .map(|approach| match approach {
+                    "" => Ok(None),
+                    "curb" => Ok(Some(Approach::CURB)),
+                    "unrestricted" => Ok(Some(Approach::UNRESTRICTED)),
+                    _ => Err(invalid_value(key, value)),
+                })
+                .collect::<Result<Vec<Option<Approach>>, OsrmError>>()?;
+            builder.set_approach(Some(approaches));
+        }
+        "exclude" => {
+            let exclude = value
+                .split(',')
+                .map(|class| CString::new(class).map_err(|_| invalid_value(key, value)))
+                .collect::<Result<Vec<CString>, OsrmError>>()?;
+            builder.set_exclude(Some(exclude));
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+// Accepts `lon,lat;lon,lat`, `polyline(...)` and `polyline6(...)`, like osrm-routed. Polylines
+// are percent-decoded first, since their alphabet includes `?`.
+pub(crate) fn parse_coordinates(coordinates: &str) -> Result<Vec<Coordinate>, OsrmError> {
+    for (prefix, precision) in [("polyline(", 5), ("polyline6(", 6)] {
+        let encoded = match coordinates
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            Some(encoded) => percent_decode(encoded),
+            None => continue,
+        };
+
+        return match decode_polyline(&encoded, precision) {
+            Ok(decoded) if !decoded.is_empty() => Ok(decoded),
+            _ => Err(OsrmError::InvalidQuery(format!(
+                "Query string malformed close to: {}",
+                coordinates
+            ))),
+        };
+    }
+
+    coordinates
+        .split(';')
+        .map(|coordinate| {
+            let mut parts = coordinate.splitn(2, ',');
+            let longitude = parts.next().and_then(|value| value.parse::<f64>().ok());
+            let latitude = parts.next().and_then(|value| value.parse::<f64>().ok());
+
+            match (longitude, latitude) {
+                (Some(longitude), Some(latitude)) => Ok(Coordinate::new(latitude, longitude)),
+                _ => Err(OsrmError::InvalidQuery(format!(
+                    "Query string malformed close to: {}",
+                    coordinate
+                ))),
+            }
+        })
+        .collect()
+}
+
+// AnnotationsType holds a single annotation or all of them, so a list is only accepted when it
+// names one annotation or every one.
+fn parse_annotations(value: &str) -> Result<(bool, AnnotationsType), OsrmError> {
+    let mut annotations: Vec<&str> = value.split(',').collect();
+    annotations.sort_unstable();
+    annotations.dedup();
+
+    let mut parsed = annotations
+        .iter()
+        .map(|annotation| {
+            Ok(match *annotation {
+                "true" => (true, AnnotationsType::All),
+                "false" => (false, AnnotationsType::None),
+                "duration" => (true, AnnotationsType::Duration),
+                "nodes" => (true, AnnotationsType::Nodes),
+                "distance" => (true, AnnotationsType::Distance),
+                "weight" => (true, AnnotationsType::Weight),
+                "datasources" => (true, AnnotationsType::Datasources),
+                "speed" => (true, AnnotationsType::Speed),
+                _ => return Err(invalid_value("annotations", value)),
+            })
+        })
+        .collect::<Result<Vec<(bool, AnnotationsType)>, OsrmError>>()?;
+    if parsed.len() == 1 {
+        return Ok(parsed.remove(0));
+    }
+    if annotations
+        == [
+            "datasources",
+            "distance",
+            "duration",
+            "nodes",
+            "speed",
+            "weight",
+        ]
+    {
+        return Ok((true, AnnotationsType::All));
+    }
+
+    Err(OsrmError::InvalidOptions(format!(
+        "Annotations {} cannot be combined; ask for one of them or for all",
+        value
+    )))
+}
+
+fn parse_geometries(value: &str) -> Result<GeometriesType, OsrmError> {
+    match value {
+        "polyline" => Ok(GeometriesType::Polyline),
+        "polyline6" => Ok(GeometriesType::Polyline6),
+        "geojson" => Ok(GeometriesType::GeoJSON),
+        _ => Err(invalid_value("geometries", value)),
+    }
+}
+
+fn parse_overview(value: &str) -> Result<OverviewType, OsrmError> {
+    match value {
+        "simplified" => Ok(OverviewType::Simplified),
+        "full" => Ok(OverviewType::Full),
+        "false" => Ok(OverviewType::False),
+        _ => Err(invalid_value("overview", value)),
+    }
+}
+
+fn parse_index_list(key: &str, value: &str) -> Result<Option<Vec<i32>>, OsrmError> {
+    if value == "all" {
+        Ok(None)
+    } else {
+        parse_list(key, value).map(Some)
+    }
+}
+
+fn parse_list<T: FromStr>(key: &str, value: &str) -> Result<Vec<T>, OsrmError> {
+    value
+        .split(';')
+        .map(|item| parse_number(key, item))
+        .collect()
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, OsrmError> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(invalid_value(key, value)),
+    }
+}
+
+fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T, OsrmError> {
+    value.parse().map_err(|_| invalid_value(key, value))
+}
+
+fn invalid_value(key: &str, value: &str) -> OsrmError {
+    OsrmError::InvalidValue(format!("Invalid value for {}: {}", key, value))
+}
+
+fn unknown_option(key: &str) -> OsrmError {
+    OsrmError::InvalidQuery(format!("Query string malformed close to: {}", key))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let high = (bytes[index + 1] as char).to_digit(16);
+            let low = (bytes[index + 2] as char).to_digit(16);
+            if let (Some(high), Some(low)) = (high, low) {
+                decoded.push((high * 16 + low) as u8);
+                index += 3;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,492 @@
+use std::{
+    collections::HashSet,
+    ffi::CString,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{backend::RoutingBackend, general::rs_structs::waypoint::Waypoint, osrm_error::OsrmError};
+
+use super::{matrix::Matrix, table_request::TableRequest, table_result::TableResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableBlock {
+    pub row: usize,
+    pub column: usize,
+    pub source_offset: usize,
+    pub destination_offset: usize,
+    pub sources: Vec<usize>,
+    pub destinations: Vec<usize>,
+}
+
+pub struct LargeTable {
+    pub request: TableRequest,
+    pub max_locations: i32,
+    pub threads: usize,
+}
+
+impl LargeTable {
+    pub fn new(request: TableRequest, max_locations_distance_table: i32) -> LargeTable {
+        LargeTable {
+            request,
+            max_locations: max_locations_distance_table,
+            threads: 1,
+        }
+    }
+
+    pub fn blocks(&self) -> Result<Vec<TableBlock>, OsrmError> {
+        let (sources, destinations) = self.indices()?;
+        let max_locations = self.max_locations.max(0) as usize;
+        let limit = max_locations * max_locations;
+
+        if limit == 0 || sources.len() * destinations.len() <= limit {
+            return Ok(vec![TableBlock {
+                row: 0,
+                column: 0,
+                source_offset: 0,
+                destination_offset: 0,
+                sources,
+                destinations,
+            }]);
+        }
+
+        let source_size = sources.len().min(max_locations);
+        let destination_size = limit / source_size;
+
+        let mut blocks = Vec::new();
+        for (row, source_chunk) in sources.chunks(source_size).enumerate() {
+            for (column, destination_chunk) in destinations.chunks(destination_size).enumerate() {
+                blocks.push(TableBlock {
+                    row,
+                    column,
+                    source_offset: row * source_size,
+                    destination_offset: column * destination_size,
+                    sources: source_chunk.to_vec(),
+                    destinations: destination_chunk.to_vec(),
+                });
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    pub fn run<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+    ) -> Result<TableResult, OsrmError> {
+        let blocks = self.blocks()?;
+        if blocks.len() == 1 {
+            return self.request.clone().run(backend);
+        }
+
+        let options = &self.request.general_options;
+        let mut hints: Vec<Option<CString>> = (0..options.coordinate.len())
+            .map(|index| {
+                options
+                    .hints
+                    .as_ref()
+                    .and_then(|hints| hints.get(index))
+                    .filter(|hint| !hint.as_bytes().is_empty())
+                    .cloned()
+            })
+            .collect();
+
+        // The first block snaps its coordinates, the first row and column then snap every
+        // remaining source and destination once, and all other blocks only reuse hints.
+        let mut results: Vec<Option<TableResult>> = blocks.iter().map(|_| None).collect();
+        for phase in 0..4 {
+            let indices: Vec<usize> = (0..blocks.len())
+                .filter(|&index| block_phase(&blocks[index]) == phase)
+                .collect();
+            let requests = indices
+                .iter()
+                .map(|&index| self.block_request(&blocks[index], &hints))
+                .collect();
+
+            for (&index, result) in indices
+                .iter()
+                .zip(run_blocks(requests, backend, self.threads))
+            {
+                let result = result?;
+                let block = &blocks[index];
+                collect_hints(&mut hints, &block.sources, &result.sources);
+                collect_hints(&mut hints, &block.destinations, &result.destinations);
+                results[index] = Some(result);
+            }
+        }
+
+        self.stitch(&blocks, results)
+    }
+
+    fn indices(&self) -> Result<(Vec<usize>, Vec<usize>), OsrmError> {
+        let number_of_coordinates = self.request.general_options.coordinate.len();
+        let indices = |selection: &Option<Vec<i32>>, name: &str| match selection {
+            Some(selection) => selection
+                .iter()
+                .map(|&index| match index >= 0 && (index as usize) < number_of_coordinates {
+                    true => Ok(index as usize),
+                    false => Err(OsrmError::InvalidValue(format!(
+                        "Table {} index {} is out of range for {} coordinates",
+                        name, index, number_of_coordinates
+                    ))),
+                })
+                .collect(),
+            None => Ok((0..number_of_coordinates).collect()),
+        };
+
+        Ok((
+            indices(&self.request.sources, "source")?,
+            indices(&self.request.destinations, "destination")?,
+        ))
+    }
+
+    fn block_request(&self, block: &TableBlock, hints: &[Option<CString>]) -> TableRequest {
+        let mut coordinates: Vec<usize> = Vec::new();
+        let sources = block
+            .sources
+            .iter()
+            .map(|&index| local_index(&mut coordinates, index))
+            .collect();
+        let destinations = block
+            .destinations
+            .iter()
+            .map(|&index| local_index(&mut coordinates, index))
+            .collect();
+
+        let options = &self.request.general_options;
+        let mut request = self.request.clone();
+        let general_options = &mut request.general_options;
+        general_options.coordinate = coordinates
+            .iter()
+            .map(|&index| options.coordinate[index].clone())
+            .collect();
+        general_options.bearings = options
+            .bearings
+            .as_ref()
+            .map(|bearings| select(bearings, &coordinates));
+        general_options.radiuses = options
+            .radiuses
+            .as_ref()
+            .map(|radiuses| select(radiuses, &coordinates));
+        general_options.approach = options
+            .approach
+            .as_ref()
+            .map(|approach| select(approach, &coordinates));
+        general_options.hints = match coordinates.iter().any(|&index| hints[index].is_some()) {
+            true => Some(
+                coordinates
+                    .iter()
+                    .map(|&index| hints[index].clone().unwrap_or_default())
+                    .collect(),
+            ),
+            false => None,
+        };
+        general_options.generate_hints = true;
+        general_options.skip_waypoints = false;
+
+        request.sources = Some(sources);
+        request.destinations = Some(destinations);
+        request
+    }
+
+    fn stitch(
+        &self,
+        blocks: &[TableBlock],
+        results: Vec<Option<TableResult>>,
+    ) -> Result<TableResult, OsrmError> {
+        let (sources, destinations) = self.indices()?;
+        let mut durations = None;
+        let mut distances = None;
+        let mut fallback_speed_cells: Option<HashSet<[usize; 2]>> = None;
+        let mut source_waypoints: Vec<Option<Waypoint>> = sources.iter().map(|_| None).collect();
+        let mut destination_waypoints: Vec<Option<Waypoint>> =
+            destinations.iter().map(|_| None).collect();
+
+        for (block, result) in blocks.iter().zip(results) {
+            let result = result
+                .ok_or_else(|| OsrmError::Ffi("table block was not run".to_string()))?;
+
+            if let Some(values) = result.durations {
+                stitch_matrix(&mut durations, block, values, sources.len(), destinations.len());
+            }
+            if let Some(values) = result.distances {
+                stitch_matrix(&mut distances, block, values, sources.len(), destinations.len());
+            }
+            if let Some(cells) = result.fallback_speed_cells {
+                fallback_speed_cells.get_or_insert_with(HashSet::new).extend(cells.iter().map(
+                    |cell| [block.source_offset + cell[0], block.destination_offset + cell[1]],
+                ));
+            }
+            if block.column == 0 {
+                for (offset, waypoint) in result.sources.into_iter().flatten().enumerate() {
+                    source_waypoints[block.source_offset + offset] = Some(waypoint);
+                }
+            }
+            if block.row == 0 {
+                for (offset, waypoint) in result.destinations.into_iter().flatten().enumerate() {
+                    destination_waypoints[block.destination_offset + offset] = Some(waypoint);
+                }
+            }
+        }
+
+        let options = &self.request.general_options;
+        let waypoints = |waypoints: Vec<Option<Waypoint>>| match options.skip_waypoints {
+            true => None,
+            false => waypoints
+                .into_iter()
+                .map(|waypoint| {
+                    waypoint.map(|mut waypoint| {
+                        if !options.generate_hints {
+                            waypoint.hint = None;
+                        }
+                        waypoint
+                    })
+                })
+                .collect(),
+        };
+
+        Ok(TableResult {
+            code: Some("Ok".to_string()),
+            message: None,
+            durations,
+            distances,
+            sources: waypoints(source_waypoints),
+            destinations: waypoints(destination_waypoints),
+            fallback_speed_cells,
+        })
+    }
+}
+
+fn block_phase(block: &TableBlock) -> usize {
+    match (block.row, block.column) {
+        (0, 0) => 0,
+        (0, _) => 1,
+        (_, 0) => 2,
+        _ => 3,
+    }
+}
+
+fn local_index(coordinates: &mut Vec<usize>, index: usize) -> i32 {
+    match coordinates.iter().position(|&coordinate| coordinate == index) {
+        Some(position) => position as i32,
+        None => {
+            coordinates.push(index);
+            (coordinates.len() - 1) as i32
+        }
+    }
+}
+
+fn select<T: Clone>(values: &[Option<T>], coordinates: &[usize]) -> Vec<Option<T>> {
+    coordinates
+        .iter()
+        .map(|&index| values.get(index).cloned().flatten())
+        .collect()
+}
+
+fn collect_hints(
+    hints: &mut [Option<CString>],
+    coordinates: &[usize],
+    waypoints: &Option<Vec<Waypoint>>,
+) {
+    for (&index, waypoint) in coordinates.iter().zip(waypoints.iter().flatten()) {
+        if hints[index].is_none() {
+            hints[index] = waypoint
+                .hint
+                .as_ref()
+                .and_then(|hint| CString::new(hint.as_str()).ok());
+        }
+    }
+}
+
+fn stitch_matrix(
+    matrix: &mut Option<Matrix>,
+    block: &TableBlock,
+    values: Matrix,
+    rows: usize,
+    columns: usize,
+) {
+    let matrix = matrix.get_or_insert_with(|| Matrix::filled(rows, columns, f64::NAN));
+    let start = block.destination_offset;
+    for (offset, row) in values.row_iter().enumerate() {
+        matrix.row_mut(block.source_offset + offset)[start..start + row.len()]
+            .copy_from_slice(row);
+    }
+}
+
+fn run_blocks<B: RoutingBackend + Sync + ?Sized>(
+    requests: Vec<TableRequest>,
+    backend: &B,
+    threads: usize,
+) -> Vec<Result<TableResult, OsrmError>> {
+    let requests: Vec<Mutex<Option<TableRequest>>> = requests
+        .into_iter()
+        .map(|request| Mutex::new(Some(request)))
+        .collect();
+    let results: Vec<Mutex<Option<Result<TableResult, OsrmError>>>> =
+        requests.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1).min(requests.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let mut request = match requests
+                    .get(index)
+                    .and_then(|request| request.lock().unwrap().take())
+                {
+                    Some(request) => request,
+                    None => break,
+                };
+
+                let result = request.run(backend);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| {
+            result
+                .into_inner()
+                .unwrap()
+                .unwrap_or_else(|| Err(OsrmError::Ffi("table block was not run".to_string())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        backend::fake_backend::FakeBackend,
+        general::rs_structs::{coordinate::Coordinate, waypoint::Waypoint},
+        osrm_error::OsrmError,
+        table_api::{
+            matrix::Matrix, table_request_builder::TableRequestBuilder, table_result::TableResult,
+        },
+    };
+
+    use super::LargeTable;
+
+    // Coordinates are identified by their latitude, durations are the id difference times ten
+    // and cells six ids apart are reported as estimated.
+    fn grid(max_locations: usize, snapped: &Mutex<Vec<usize>>) -> FakeBackend<'_> {
+        FakeBackend::new().on_table(move |request| {
+            let options = &request.general_options;
+            let ids: Vec<usize> = options
+                .coordinate
+                .iter()
+                .map(|coordinate| coordinate.latitude as usize)
+                .collect();
+            let select = |selection: &Option<Vec<i32>>| match selection {
+                Some(selection) => selection.iter().map(|&index| index as usize).collect(),
+                None => (0..ids.len()).collect::<Vec<usize>>(),
+            };
+            let (sources, destinations) = (select(&request.sources), select(&request.destinations));
+            if sources.len() * destinations.len() > max_locations * max_locations {
+                return Err(OsrmError::TooBig("Too many table coordinates".to_string()));
+            }
+
+            for (index, &id) in ids.iter().enumerate() {
+                let hint = options.hints.as_ref().and_then(|hints| hints.get(index));
+                match hint.filter(|hint| !hint.as_bytes().is_empty()) {
+                    Some(hint) => assert_eq!(hint.to_str().unwrap(), format!("hint{}", id)),
+                    None => snapped.lock().unwrap()[id] += 1,
+                }
+            }
+
+            let waypoints = |selection: &Vec<usize>| {
+                selection
+                    .iter()
+                    .map(|&index| Waypoint {
+                        hint: Some(format!("hint{}", ids[index])),
+                        distance: 0.0,
+                        name: String::new(),
+                        location: [0.5, ids[index] as f64],
+                    })
+                    .collect()
+            };
+
+            Ok(TableResult {
+                code: Some("Ok".to_string()),
+                message: None,
+                durations: Some(
+                    Matrix::new(
+                        sources.len(),
+                        destinations.len(),
+                        sources
+                            .iter()
+                            .flat_map(|&source| {
+                                let ids = &ids;
+                                destinations.iter().map(move |&destination| {
+                                    (ids[destination] as f64 - ids[source] as f64) * 10.0
+                                })
+                            })
+                            .collect(),
+                    )
+                    .unwrap(),
+                ),
+                distances: None,
+                sources: Some(waypoints(&sources)),
+                destinations: Some(waypoints(&destinations)),
+                fallback_speed_cells: Some(
+                    (0..sources.len())
+                        .flat_map(|row| (0..destinations.len()).map(move |column| [row, column]))
+                        .filter(|&[row, column]| {
+                            ids[sources[row]].abs_diff(ids[destinations[column]]) == 6
+                        })
+                        .collect(),
+                ),
+            })
+        })
+    }
+
+    #[test]
+    fn large_tables_are_split_and_stitched() {
+        let coordinates: Vec<Coordinate> =
+            (0..7).map(|id| Coordinate::new(id as f64, 0.5)).collect();
+        let snapped = Mutex::new(vec![0; 7]);
+        let backend = grid(2, &snapped);
+
+        let request = TableRequestBuilder::new(&coordinates).build().unwrap();
+        assert!(matches!(
+            request.clone().run(&backend),
+            Err(OsrmError::TooBig(_))
+        ));
+
+        let mut table = LargeTable::new(request, 2);
+        table.threads = 3;
+        assert_eq!(table.blocks().unwrap().len(), 16);
+        *snapped.lock().unwrap() = vec![0; 7];
+
+        let result = table.run(&backend).unwrap();
+        let durations = result.durations.as_ref().unwrap();
+        assert_eq!((durations.rows(), durations.columns()), (7, 7));
+        for (source, destination, duration) in durations.iter() {
+            assert_eq!(duration, Some((destination as f64 - source as f64) * 10.0));
+        }
+        assert!(result.is_estimated(0, 6) && result.is_estimated(6, 0));
+        assert!(!result.is_estimated(0, 5));
+        assert_eq!(result.fallback_speed_cells.as_ref().unwrap().len(), 2);
+        assert_eq!(result.sources.unwrap()[3].hint.as_deref(), Some("hint3"));
+        assert_eq!(result.destinations.unwrap()[6].location, [0.5, 6.0]);
+        assert_eq!(*snapped.lock().unwrap(), vec![1; 7]);
+
+        let mut subset = TableRequestBuilder::new(&coordinates);
+        subset
+            .set_sources(Some(vec![6]))
+            .set_destinations(Some(vec![0, 1, 2, 3, 4, 5]));
+        let result = LargeTable::new(subset.build().unwrap(), 2)
+            .run(&backend)
+            .unwrap();
+        assert_eq!(
+            result.durations.unwrap().row(0),
+            &[-60.0, -50.0, -40.0, -30.0, -20.0, -10.0]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::slice;
+
+use crate::osrm_error::OsrmError;
+
+// libosrm reports unreachable cells as INVALID_EDGE_WEIGHT / 10 (or larger for distances).
+const UNREACHABLE: f64 = 214_748_364.7;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawMatrix"))]
+pub struct Matrix {
+    rows: usize,
+    columns: usize,
+    #[cfg_attr(feature = "serde", serde(with = "nullable"))]
+    values: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn new(rows: usize, columns: usize, values: Vec<f64>) -> Result<Matrix, OsrmError> {
+        if rows.checked_mul(columns) != Some(values.len()) {
+            return Err(OsrmError::InvalidValue(format!(
+                "a {}x{} matrix cannot hold {} values",
+                rows,
+                columns,
+                values.len()
+            )));
+        }
+
+        Ok(Matrix {
+            rows,
+            columns,
+            values,
+        })
+    }
+
+    pub fn filled(rows: usize, columns: usize, value: f64) -> Matrix {
+        Matrix {
+            rows,
+            columns,
+            values: vec![value; rows * columns],
+        }
+    }
+
+    pub fn from_rows(rows: &[Vec<f64>]) -> Result<Matrix, OsrmError> {
+        let columns = rows.first().map(Vec::len).unwrap_or_default();
+        if rows.iter().any(|row| row.len() != columns) {
+            return Err(OsrmError::InvalidValue(
+                "matrix rows have different lengths".to_string(),
+            ));
+        }
+
+        Matrix::new(rows.len(), columns, rows.concat())
+    }
+
+    // libosrm frees its buffer together with the table result, so the values are copied once here
+    // and normalized in place.
+    pub(crate) unsafe fn from_c(values: *const f64, rows: usize, columns: usize) -> Matrix {
+        let mut values = slice::from_raw_parts(values, rows * columns).to_vec();
+        for value in values.iter_mut() {
+            if !value.is_finite() || *value < 0.0 || *value >= UNREACHABLE {
+                *value = f64::NAN;
+            }
+        }
+
+        Matrix {
+            rows,
+            columns,
+            values,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<f64> {
+        if row >= self.rows || column >= self.columns {
+            return None;
+        }
+
+        let value = self.values[row * self.columns + column];
+        match value.is_nan() {
+            true => None,
+            false => Some(value),
+        }
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, value: Option<f64>) {
+        assert!(row < self.rows && column < self.columns);
+        self.values[row * self.columns + column] = value.unwrap_or(f64::NAN);
+    }
+
+    pub fn row(&self, row: usize) -> &[f64] {
+        &self.values[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [f64] {
+        &mut self.values[row * self.columns..(row + 1) * self.columns]
+    }
+
+    pub fn column(&self, column: usize) -> impl Iterator<Item = f64> + '_ {
+        assert!(column < self.columns);
+        self.values.iter().skip(column).step_by(self.columns).copied()
+    }
+
+    pub fn row_iter(&self) -> impl Iterator<Item = &[f64]> {
+        (0..self.rows).map(move |row| self.row(row))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, Option<f64>)> + '_ {
+        self.values.iter().enumerate().map(move |(index, &value)| {
+            (
+                index / self.columns,
+                index % self.columns,
+                if value.is_nan() { None } else { Some(value) },
+            )
+        })
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn into_vec(self) -> Vec<f64> {
+        self.values
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<Option<f64>>> {
+        self.row_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&value| if value.is_nan() { None } else { Some(value) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "ndarray")]
+    pub fn view(&self) -> ndarray::ArrayView2<'_, f64> {
+        ndarray::ArrayView2::from_shape((self.rows, self.columns), &self.values)
+            .expect("matrix shape matches its values")
+    }
+
+    #[cfg(feature = "ndarray")]
+    pub fn into_array(self) -> ndarray::Array2<f64> {
+        ndarray::Array2::from_shape_vec((self.rows, self.columns), self.values)
+            .expect("matrix shape matches its values")
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.to_rows())
+    }
+}
+
+impl PartialEq for Matrix {
+    fn eq(&self, other: &Matrix) -> bool {
+        self.rows == other.rows
+            && self.columns == other.columns
+            && self
+                .values
+                .iter()
+                .zip(&other.values)
+                .all(|(a, b)| a == b || (a.is_nan() && b.is_nan()))
+    }
+}
+
+// Deserialized matrices go through `Matrix::new`, so a shape that does not match the values is an
+// error rather than a panic on the first `get`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawMatrix {
+    rows: usize,
+    columns: usize,
+    #[serde(with = "nullable")]
+    values: Vec<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<RawMatrix> for Matrix {
+    type Error = OsrmError;
+
+    fn try_from(raw: RawMatrix) -> Result<Matrix, OsrmError> {
+        Matrix::new(raw.rows, raw.columns, raw.values)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod nullable {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+        values
+            .iter()
+            .map(|&value| if value.is_nan() { None } else { Some(value) })
+            .collect::<Vec<Option<f64>>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+        Ok(Vec::<Option<f64>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matrix;
+
+    #[test]
+    fn matrices_are_row_major_with_explicit_nulls() {
+        let values = [0.0, 12.5, 214_748_364.7, 7.0, 0.0, 3.0];
+        let matrix = unsafe { Matrix::from_c(values.as_ptr(), 2, 3) };
+
+        assert_eq!((matrix.rows(), matrix.columns(), matrix.len()), (2, 3, 6));
+        assert_eq!(matrix.get(0, 1), Some(12.5));
+        assert_eq!(matrix.get(0, 2), None);
+        assert!(matrix.row(0)[2].is_nan());
+        assert_eq!(matrix.get(2, 0), None);
+        assert_eq!(matrix.row(1), &[7.0, 0.0, 3.0]);
+        assert_eq!(matrix.column(1).collect::<Vec<f64>>(), vec![12.5, 0.0]);
+        assert_eq!(matrix.row_iter().count(), 2);
+        assert_eq!(
+            matrix.to_rows(),
+            vec![
+                vec![Some(0.0), Some(12.5), None],
+                vec![Some(7.0), Some(0.0), Some(3.0)]
+            ]
+        );
+        assert_eq!(matrix.iter().nth(4), Some((1, 1, Some(0.0))));
+
+        assert!(Matrix::new(2, 2, vec![0.0; 3]).is_err());
+        assert!(Matrix::from_rows(&[vec![1.0], vec![1.0, 2.0]]).is_err());
+
+        #[cfg(feature = "ndarray")]
+        assert_eq!(matrix.view()[[1, 2]], 3.0);
+    }
+
+    #[cfg(all(feature = "serde", feature = "json"))]
+    #[test]
+    fn deserialized_shapes_are_checked() {
+        let matrix: Matrix =
+            serde_json::from_str(r#"{"rows":1,"columns":2,"values":[1.5,null]}"#).unwrap();
+        assert_eq!(matrix.get(0, 0), Some(1.5));
+        assert_eq!(matrix.get(0, 1), None);
+
+        assert!(
+            serde_json::from_str::<Matrix>(r#"{"rows":2,"columns":2,"values":[1.5]}"#).is_err()
+        );
+        assert!(serde_json::from_str::<Matrix>(
+            r#"{"rows":4294967296,"columns":4294967296,"values":[]}"#
+        )
+        .is_err());
+    }
+}
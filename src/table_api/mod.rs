@@ -4,6 +4,9 @@ use crate::Status;
 
 use self::{table_request::CTableRequest, table_result::CTableResult};
 
+pub mod large_table;
+pub mod matrix;
+mod table_export;
 pub mod table_request;
 pub mod table_request_builder;
 pub mod table_result;
@@ -21,6 +24,7 @@ extern "C" {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Annotations {
     NONE = 0,
     DURATION = 1,
@@ -30,6 +34,7 @@ pub enum Annotations {
 
 #[repr(C)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FallbackCoordinate {
     INPUT = 0,
     SNAPPED = 1,
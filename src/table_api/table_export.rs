@@ -0,0 +1,602 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::{general::rs_structs::waypoint::Waypoint, osrm_error::OsrmError};
+
+use super::{matrix::Matrix, table_result::TableResult};
+
+const SOURCE_INDEX: &str = "source_index";
+const DESTINATION_INDEX: &str = "destination_index";
+const DURATION: &str = "duration";
+const DISTANCE: &str = "distance";
+const ESTIMATED: &str = "estimated";
+const SOURCE_LONGITUDE: &str = "source_longitude";
+const SOURCE_LATITUDE: &str = "source_latitude";
+const DESTINATION_LONGITUDE: &str = "destination_longitude";
+const DESTINATION_LATITUDE: &str = "destination_latitude";
+
+// One row per (source, destination) cell; optional columns are absent when the result lacks them.
+#[derive(Default)]
+struct LongTable {
+    source_index: Vec<u32>,
+    destination_index: Vec<u32>,
+    duration: Option<Vec<Option<f64>>>,
+    distance: Option<Vec<Option<f64>>>,
+    estimated: Option<Vec<bool>>,
+    source_location: Option<Vec<[f64; 2]>>,
+    destination_location: Option<Vec<[f64; 2]>>,
+}
+
+impl LongTable {
+    fn from_result(result: &TableResult) -> LongTable {
+        let rows = result
+            .durations
+            .as_ref()
+            .or(result.distances.as_ref())
+            .map(Matrix::rows)
+            .or_else(|| result.sources.as_ref().map(Vec::len))
+            .unwrap_or_default();
+        let columns = result
+            .durations
+            .as_ref()
+            .or(result.distances.as_ref())
+            .map(Matrix::columns)
+            .or_else(|| result.destinations.as_ref().map(Vec::len))
+            .unwrap_or_default();
+
+        let cells: Vec<(usize, usize)> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (row, column)))
+            .collect();
+        let values = |matrix: &Option<Matrix>| {
+            matrix.as_ref().map(|matrix| {
+                cells
+                    .iter()
+                    .map(|&(row, column)| matrix.get(row, column))
+                    .collect()
+            })
+        };
+        let locations = |waypoints: &Option<Vec<Waypoint>>, source: bool| {
+            waypoints.as_ref().map(|waypoints| {
+                cells
+                    .iter()
+                    .map(|&(row, column)| {
+                        let index = if source { row } else { column };
+                        waypoints
+                            .get(index)
+                            .map(|waypoint| waypoint.location)
+                            .unwrap_or([f64::NAN, f64::NAN])
+                    })
+                    .collect()
+            })
+        };
+
+        LongTable {
+            source_index: cells.iter().map(|&(row, _)| row as u32).collect(),
+            destination_index: cells.iter().map(|&(_, column)| column as u32).collect(),
+            duration: values(&result.durations),
+            distance: values(&result.distances),
+            estimated: result.fallback_speed_cells.as_ref().map(|estimated| {
+                cells
+                    .iter()
+                    .map(|&(row, column)| estimated.contains(&[row, column]))
+                    .collect()
+            }),
+            source_location: locations(&result.sources, true),
+            destination_location: locations(&result.destinations, false),
+        }
+    }
+
+    fn append(&mut self, other: LongTable) {
+        fn extend<T>(values: &mut Option<Vec<T>>, other: Option<Vec<T>>) {
+            if let Some(other) = other {
+                values.get_or_insert_with(Vec::new).extend(other);
+            }
+        }
+
+        self.source_index.extend(other.source_index);
+        self.destination_index.extend(other.destination_index);
+        extend(&mut self.duration, other.duration);
+        extend(&mut self.distance, other.distance);
+        extend(&mut self.estimated, other.estimated);
+        extend(&mut self.source_location, other.source_location);
+        extend(&mut self.destination_location, other.destination_location);
+    }
+
+    fn into_result(self) -> Result<TableResult, OsrmError> {
+        let length = self.source_index.len();
+        let lengths = [
+            self.destination_index.len(),
+            self.duration.as_ref().map_or(length, Vec::len),
+            self.distance.as_ref().map_or(length, Vec::len),
+            self.estimated.as_ref().map_or(length, Vec::len),
+            self.source_location.as_ref().map_or(length, Vec::len),
+            self.destination_location.as_ref().map_or(length, Vec::len),
+        ];
+        if lengths.iter().any(|&other| other != length) {
+            return Err(OsrmError::InvalidValue(
+                "table columns have different lengths".to_string(),
+            ));
+        }
+
+        let rows = self
+            .source_index
+            .iter()
+            .max()
+            .map_or(0, |&max| max as usize + 1);
+        let columns = self
+            .destination_index
+            .iter()
+            .max()
+            .map_or(0, |&max| max as usize + 1);
+        // Every cell of the matrix has its own record, so indices beyond the record count come
+        // from a damaged file; refuse them instead of allocating a matrix for them.
+        if rows.saturating_mul(columns) > length {
+            return Err(OsrmError::InvalidValue(format!(
+                "table indices span {}x{} cells but only {} are given",
+                rows, columns, length
+            )));
+        }
+        let cells: Vec<(usize, usize)> = self
+            .source_index
+            .iter()
+            .zip(&self.destination_index)
+            .map(|(&row, &column)| (row as usize, column as usize))
+            .collect();
+
+        let matrix = |values: Option<Vec<Option<f64>>>| {
+            values.map(|values| {
+                let mut matrix = Matrix::filled(rows, columns, f64::NAN);
+                for (&(row, column), value) in cells.iter().zip(values) {
+                    matrix.set(row, column, value);
+                }
+                matrix
+            })
+        };
+        let waypoints = |locations: Option<Vec<[f64; 2]>>, count: usize, source: bool| {
+            let locations = locations?;
+            let mut waypoints: Vec<Option<[f64; 2]>> = vec![None; count];
+            for (&(row, column), location) in cells.iter().zip(locations) {
+                waypoints[if source { row } else { column }] = Some(location);
+            }
+            waypoints
+                .into_iter()
+                .map(|location| {
+                    location.map(|location| Waypoint {
+                        hint: None,
+                        distance: 0.0,
+                        name: String::new(),
+                        location,
+                    })
+                })
+                .collect()
+        };
+
+        Ok(TableResult {
+            code: Some("Ok".to_string()),
+            message: None,
+            sources: waypoints(self.source_location, rows, true),
+            destinations: waypoints(self.destination_location, columns, false),
+            fallback_speed_cells: self.estimated.map(|estimated| {
+                cells
+                    .iter()
+                    .zip(estimated)
+                    .filter(|(_, estimated)| *estimated)
+                    .map(|(&(row, column), _)| [row, column])
+                    .collect()
+            }),
+            durations: matrix(self.duration),
+            distances: matrix(self.distance),
+        })
+    }
+}
+
+impl TableResult {
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), OsrmError> {
+        let table = LongTable::from_result(self);
+        let mut writer = std::io::BufWriter::new(writer);
+
+        let mut header = vec![SOURCE_INDEX, DESTINATION_INDEX];
+        if table.duration.is_some() {
+            header.push(DURATION);
+        }
+        if table.distance.is_some() {
+            header.push(DISTANCE);
+        }
+        if table.estimated.is_some() {
+            header.push(ESTIMATED);
+        }
+        if table.source_location.is_some() {
+            header.extend([SOURCE_LONGITUDE, SOURCE_LATITUDE]);
+        }
+        if table.destination_location.is_some() {
+            header.extend([DESTINATION_LONGITUDE, DESTINATION_LATITUDE]);
+        }
+        writeln!(writer, "{}", header.join(",")).map_err(io_error)?;
+
+        let number = |value: f64| match value.is_nan() {
+            true => String::new(),
+            false => value.to_string(),
+        };
+        for index in 0..table.source_index.len() {
+            let mut line = vec![
+                table.source_index[index].to_string(),
+                table.destination_index[index].to_string(),
+            ];
+            for values in [&table.duration, &table.distance].iter().copied().flatten() {
+                line.push(values[index].map(number).unwrap_or_default());
+            }
+            if let Some(estimated) = &table.estimated {
+                line.push(estimated[index].to_string());
+            }
+            for locations in [&table.source_location, &table.destination_location]
+                .iter()
+                .copied()
+                .flatten()
+            {
+                line.push(number(locations[index][0]));
+                line.push(number(locations[index][1]));
+            }
+            writeln!(writer, "{}", line.join(",")).map_err(io_error)?;
+        }
+
+        writer.flush().map_err(io_error)
+    }
+
+    pub fn read_csv<R: Read>(reader: R) -> Result<TableResult, OsrmError> {
+        let mut lines = BufReader::new(reader).lines();
+        let header = match lines.next() {
+            Some(header) => header.map_err(io_error)?,
+            None => return Err(OsrmError::InvalidValue("table CSV is empty".to_string())),
+        };
+        let header: Vec<String> = header
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .collect();
+        let column = |name: &str| header.iter().position(|column| column == name);
+
+        let source_index = column(SOURCE_INDEX);
+        let destination_index = column(DESTINATION_INDEX);
+        let (source_index, destination_index) = match (source_index, destination_index) {
+            (Some(source_index), Some(destination_index)) => (source_index, destination_index),
+            _ => {
+                return Err(OsrmError::InvalidValue(format!(
+                    "table CSV needs `{}` and `{}` columns",
+                    SOURCE_INDEX, DESTINATION_INDEX
+                )))
+            }
+        };
+        let duration = column(DURATION);
+        let distance = column(DISTANCE);
+        let estimated = column(ESTIMATED);
+        let source_location = column(SOURCE_LONGITUDE).zip(column(SOURCE_LATITUDE));
+        let destination_location = column(DESTINATION_LONGITUDE).zip(column(DESTINATION_LATITUDE));
+
+        let mut table = LongTable {
+            duration: duration.map(|_| Vec::new()),
+            distance: distance.map(|_| Vec::new()),
+            estimated: estimated.map(|_| Vec::new()),
+            source_location: source_location.map(|_| Vec::new()),
+            destination_location: destination_location.map(|_| Vec::new()),
+            ..LongTable::default()
+        };
+
+        for (line_number, line) in lines.enumerate() {
+            let line = line.map_err(io_error)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let invalid = |name: &str| {
+                OsrmError::InvalidValue(format!(
+                    "invalid `{}` on line {} of table CSV",
+                    name,
+                    line_number + 2
+                ))
+            };
+            let field =
+                |index: usize, name: &str| fields.get(index).copied().ok_or_else(|| invalid(name));
+            let number = |index: usize, name: &str| -> Result<Option<f64>, OsrmError> {
+                match field(index, name)? {
+                    "" => Ok(None),
+                    value => value.parse().map(Some).map_err(|_| invalid(name)),
+                }
+            };
+            let location = |(longitude, latitude): (usize, usize), name: &str| {
+                Ok::<[f64; 2], OsrmError>([
+                    number(longitude, name)?.unwrap_or(f64::NAN),
+                    number(latitude, name)?.unwrap_or(f64::NAN),
+                ])
+            };
+
+            table.source_index.push(
+                field(source_index, SOURCE_INDEX)?
+                    .parse()
+                    .map_err(|_| invalid(SOURCE_INDEX))?,
+            );
+            table.destination_index.push(
+                field(destination_index, DESTINATION_INDEX)?
+                    .parse()
+                    .map_err(|_| invalid(DESTINATION_INDEX))?,
+            );
+            if let (Some(index), Some(values)) = (duration, table.duration.as_mut()) {
+                values.push(number(index, DURATION)?);
+            }
+            if let (Some(index), Some(values)) = (distance, table.distance.as_mut()) {
+                values.push(number(index, DISTANCE)?);
+            }
+            if let (Some(index), Some(values)) = (estimated, table.estimated.as_mut()) {
+                values.push(
+                    field(index, ESTIMATED)?
+                        .parse()
+                        .map_err(|_| invalid(ESTIMATED))?,
+                );
+            }
+            if let (Some(indices), Some(values)) = (source_location, table.source_location.as_mut())
+            {
+                values.push(location(indices, SOURCE_LONGITUDE)?);
+            }
+            if let (Some(indices), Some(values)) =
+                (destination_location, table.destination_location.as_mut())
+            {
+                values.push(location(indices, DESTINATION_LONGITUDE)?);
+            }
+        }
+
+        table.into_result()
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> Result<arrow_array::RecordBatch, OsrmError> {
+        use std::sync::Arc;
+
+        use arrow_array::{ArrayRef, BooleanArray, Float64Array, UInt32Array};
+        use arrow_schema::{DataType, Field, Schema};
+
+        let table = LongTable::from_result(self);
+        let mut fields = vec![
+            Field::new(SOURCE_INDEX, DataType::UInt32, false),
+            Field::new(DESTINATION_INDEX, DataType::UInt32, false),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from(table.source_index)),
+            Arc::new(UInt32Array::from(table.destination_index)),
+        ];
+
+        for (name, values) in [(DURATION, table.duration), (DISTANCE, table.distance)] {
+            if let Some(values) = values {
+                fields.push(Field::new(name, DataType::Float64, true));
+                columns.push(Arc::new(Float64Array::from(values)));
+            }
+        }
+        if let Some(estimated) = table.estimated {
+            fields.push(Field::new(ESTIMATED, DataType::Boolean, false));
+            columns.push(Arc::new(BooleanArray::from(estimated)));
+        }
+        for (longitude, latitude, locations) in [
+            (SOURCE_LONGITUDE, SOURCE_LATITUDE, table.source_location),
+            (
+                DESTINATION_LONGITUDE,
+                DESTINATION_LATITUDE,
+                table.destination_location,
+            ),
+        ] {
+            if let Some(locations) = locations {
+                fields.push(Field::new(longitude, DataType::Float64, false));
+                fields.push(Field::new(latitude, DataType::Float64, false));
+                columns.push(Arc::new(Float64Array::from_iter_values(
+                    locations.iter().map(|location| location[0]),
+                )));
+                columns.push(Arc::new(Float64Array::from_iter_values(
+                    locations.iter().map(|location| location[1]),
+                )));
+            }
+        }
+
+        arrow_array::RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+            .map_err(|e| OsrmError::InvalidValue(e.to_string()))
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn from_record_batches(
+        batches: &[arrow_array::RecordBatch],
+    ) -> Result<TableResult, OsrmError> {
+        let mut table = LongTable::default();
+        for batch in batches {
+            table.append(long_table_from_batch(batch)?);
+        }
+
+        table.into_result()
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn write_parquet<W: Write + Send>(&self, writer: W) -> Result<(), OsrmError> {
+        let batch = self.to_record_batch()?;
+        let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)
+            .map_err(parquet_error)?;
+        writer.write(&batch).map_err(parquet_error)?;
+        writer.close().map_err(parquet_error)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn read_parquet<R: parquet::file::reader::ChunkReader + 'static>(
+        reader: R,
+    ) -> Result<TableResult, OsrmError> {
+        let batches =
+            parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(reader)
+                .map_err(parquet_error)?
+                .build()
+                .map_err(parquet_error)?
+                .collect::<Result<Vec<arrow_array::RecordBatch>, _>>()
+                .map_err(|e| OsrmError::InvalidValue(e.to_string()))?;
+
+        TableResult::from_record_batches(&batches)
+    }
+}
+
+#[cfg(feature = "arrow")]
+fn long_table_from_batch(batch: &arrow_array::RecordBatch) -> Result<LongTable, OsrmError> {
+    use arrow_array::{Array, BooleanArray, Float64Array, UInt32Array};
+
+    fn column<'a, T: 'static>(
+        batch: &'a arrow_array::RecordBatch,
+        name: &str,
+    ) -> Result<Option<&'a T>, OsrmError> {
+        match batch.column_by_name(name) {
+            Some(column) => column
+                .as_any()
+                .downcast_ref::<T>()
+                .map(Some)
+                .ok_or_else(|| {
+                    OsrmError::InvalidValue(format!(
+                        "table column `{}` has an unexpected type",
+                        name
+                    ))
+                }),
+            None => Ok(None),
+        }
+    }
+    let required = |name: &str| {
+        column::<UInt32Array>(batch, name)?
+            .map(|values| values.values().to_vec())
+            .ok_or_else(|| OsrmError::InvalidValue(format!("table is missing `{}`", name)))
+    };
+    let numbers = |name: &str| {
+        Ok::<_, OsrmError>(
+            column::<Float64Array>(batch, name)?
+                .map(|values| values.iter().collect::<Vec<Option<f64>>>()),
+        )
+    };
+    let locations = |longitude: &str, latitude: &str| {
+        Ok::<_, OsrmError>(numbers(longitude)?.zip(numbers(latitude)?).map(
+            |(longitudes, latitudes)| {
+                longitudes
+                    .into_iter()
+                    .zip(latitudes)
+                    .map(|(longitude, latitude)| {
+                        [longitude.unwrap_or(f64::NAN), latitude.unwrap_or(f64::NAN)]
+                    })
+                    .collect()
+            },
+        ))
+    };
+
+    Ok(LongTable {
+        source_index: required(SOURCE_INDEX)?,
+        destination_index: required(DESTINATION_INDEX)?,
+        duration: numbers(DURATION)?,
+        distance: numbers(DISTANCE)?,
+        estimated: column::<BooleanArray>(batch, ESTIMATED)?.map(|values| {
+            (0..values.len())
+                .map(|index| values.is_valid(index) && values.value(index))
+                .collect()
+        }),
+        source_location: locations(SOURCE_LONGITUDE, SOURCE_LATITUDE)?,
+        destination_location: locations(DESTINATION_LONGITUDE, DESTINATION_LATITUDE)?,
+    })
+}
+
+fn io_error(error: std::io::Error) -> OsrmError {
+    OsrmError::Io(error.to_string())
+}
+
+#[cfg(feature = "arrow")]
+fn parquet_error(error: parquet::errors::ParquetError) -> OsrmError {
+    OsrmError::Io(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    #[cfg(feature = "arrow")]
+    use std::{env, fs, fs::File, process};
+
+    use crate::{
+        general::rs_structs::waypoint::Waypoint,
+        table_api::{matrix::Matrix, table_result::TableResult},
+    };
+
+    fn result() -> TableResult {
+        let waypoint = |longitude: f64, latitude: f64| Waypoint {
+            hint: None,
+            distance: 0.0,
+            name: String::new(),
+            location: [longitude, latitude],
+        };
+
+        TableResult {
+            code: Some("Ok".to_string()),
+            message: None,
+            durations: Some(
+                Matrix::new(2, 3, vec![0.0, 1286.7, f64::NAN, 1297.3, 0.0, 1514.2]).unwrap(),
+            ),
+            distances: Some(
+                Matrix::new(2, 3, vec![0.0, 10832.5, f64::NAN, 10902.8, 0.0, 12701.3]).unwrap(),
+            ),
+            sources: Some(vec![
+                waypoint(13.38886, 52.517037),
+                waypoint(13.397631, 52.529432),
+            ]),
+            destinations: Some(vec![
+                waypoint(13.38886, 52.517037),
+                waypoint(13.397631, 52.529432),
+                waypoint(13.42835, 52.523287),
+            ]),
+            fallback_speed_cells: Some(HashSet::from([[1, 2]])),
+        }
+    }
+
+    fn assert_same(read: &TableResult, written: &TableResult) {
+        assert_eq!(read.durations, written.durations);
+        assert_eq!(read.distances, written.distances);
+        assert_eq!(read.fallback_speed_cells, written.fallback_speed_cells);
+        let locations = |waypoints: &Option<Vec<Waypoint>>| {
+            waypoints.as_ref().map(|waypoints| {
+                waypoints
+                    .iter()
+                    .map(|waypoint| waypoint.location)
+                    .collect::<Vec<[f64; 2]>>()
+            })
+        };
+        assert_eq!(locations(&read.sources), locations(&written.sources));
+        assert_eq!(
+            locations(&read.destinations),
+            locations(&written.destinations)
+        );
+    }
+
+    #[test]
+    fn tables_round_trip_through_csv() {
+        let result = result();
+        let mut csv = Vec::new();
+        result.write_csv(&mut csv).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "source_index,destination_index,duration,distance,estimated,source_longitude,source_latitude,destination_longitude,destination_latitude");
+        assert_eq!(
+            lines[3],
+            "0,2,,,false,13.38886,52.517037,13.42835,52.523287"
+        );
+
+        assert_same(&TableResult::read_csv(csv.as_bytes()).unwrap(), &result);
+        assert!(TableResult::read_csv("source_index,duration\n0,1".as_bytes()).is_err());
+        assert!(TableResult::read_csv(
+            "source_index,destination_index,duration\n0,0,1\n4000000000,4000000000,2".as_bytes()
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn tables_round_trip_through_parquet() {
+        let path = env::temp_dir().join(format!("rs_osrm_table_{}.parquet", process::id()));
+        let result = result();
+        result.write_parquet(File::create(&path).unwrap()).unwrap();
+
+        let read = TableResult::read_parquet(File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_same(&read, &result);
+    }
+}
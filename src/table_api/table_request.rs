@@ -1,6 +1,6 @@
 use std::os::raw::{c_double, c_int};
 
-use crate::{engine_config::engine_config_builder::EngineConfigBuilder, general::{c_structs::c_general_options::CGeneralOptions, rs_structs::{coordinate::Coordinate, general_options::GeneralOptions}}, route_api::{route_request::RouteRequest, route_request_builder::RouteRequestBuilder}, Algorithm, Osrm, Status};
+use crate::{backend::RoutingBackend, engine_config::{engine_config_builder::EngineConfigBuilder, engine_limits::EngineLimits}, general::{c_structs::c_general_options::CGeneralOptions, rs_structs::{coordinate::Coordinate, general_options::GeneralOptions}, validation::ValidationError}, route_api::{route_request::RouteRequest, route_request_builder::RouteRequestBuilder}, Algorithm, Osrm, Status, osrm_error::OsrmError};
 
 use super::{Annotations, FallbackCoordinate, table_result::{CTableResult, TableResult}, table_result_destroy, osrm_table};
 
@@ -47,6 +47,8 @@ impl CTableRequest {
     }
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableRequest {
     pub(crate) general_options: GeneralOptions,
     pub(crate) sources: Option<Vec<i32>>,
@@ -58,7 +60,28 @@ pub struct TableRequest {
 }
 
 impl TableRequest {
-    pub fn run(&mut self, osrm: &Osrm) -> (Status, TableResult) {
+    pub fn run<B: RoutingBackend + ?Sized>(&mut self, backend: &B) -> Result<TableResult, OsrmError> {
+        backend.table(self)
+    }
+
+    pub(crate) fn check_limits(&self, limits: &EngineLimits) -> Result<(), ValidationError> {
+        let count = self.general_options.coordinate.len();
+        let sources = self.sources.as_ref().map_or(count, Vec::len);
+        let destinations = self.destinations.as_ref().map_or(count, Vec::len);
+        let limit = limits.max_locations_distance_table;
+        if limit > 0 && sources * destinations > limit as usize * limit as usize {
+            return Err(ValidationError::TableTooBig {
+                limit: limit as usize,
+                sources,
+                destinations,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn run_osrm(&mut self, osrm: &Osrm) -> Result<TableResult, OsrmError> {
+        self.check_limits(osrm.limits())?;
         unsafe {
             let mut result: *mut CTableResult = std::ptr::null_mut();
             let result_ptr: *mut *mut CTableResult = &mut result;
@@ -69,11 +92,21 @@ impl TableRequest {
                 result_ptr,
             );
 
-            let converted_result = TableResult::new(&(*result));
+            if result.is_null() {
+                return Err(OsrmError::Ffi("osrm_table returned no result".to_string()));
+            }
+
+            let converted_result = TableResult::new(&(*result), &osrm.string_decoding);
 
             table_result_destroy(result);
 
-            (status, converted_result)
+            let converted_result = converted_result?;
+
+            if status == Status::Ok {
+                Ok(converted_result)
+            } else {
+                Err(OsrmError::from_code(converted_result.code, converted_result.message))
+            }
         }
     }
 }
@@ -124,7 +157,7 @@ pub fn testI() {
     .build()
     .unwrap();
 
-    let (status, result) = request.build().unwrap().run(&osrm);
+    let result = request.build().unwrap().run(&osrm);
 
     print!("{:?}", result);
 
@@ -1,9 +1,10 @@
 use std::ffi::CString;
 
-use crate::general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate};
+use crate::{engine_config::engine_limits::EngineLimits, general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate, validation::{self, ValidationError}}};
 
 use super::{Annotations, FallbackCoordinate, table_request::TableRequest};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableRequestBuilder {
     general_options: GeneralOptions,
     sources: Option<Vec<i32>>,
@@ -12,10 +13,11 @@ pub struct TableRequestBuilder {
     fallback_speed: f64,
     fallback_coordinate: FallbackCoordinate,
     scale_factor: f64,
+    limits: EngineLimits,
 }
 
 impl TableRequestBuilder {
-    pub fn new(coordinates: &Vec<Coordinate>) -> TableRequestBuilder {
+    pub fn new<C: Clone + Into<Coordinate>>(coordinates: &[C]) -> TableRequestBuilder {
         TableRequestBuilder {
             general_options: GeneralOptions::new(coordinates),
             sources: None,
@@ -24,6 +26,7 @@ impl TableRequestBuilder {
             fallback_speed: std::f64::MAX,
             fallback_coordinate: FallbackCoordinate::INPUT,
             scale_factor: 1.0,
+            limits: EngineLimits::unlimited(),
         }
     }
 
@@ -57,18 +60,45 @@ impl TableRequestBuilder {
         self
     }
 
-    pub fn build(&self) -> Result<TableRequest, String> {
-        Ok(
-            TableRequest {
-                general_options: self.general_options.clone(),
-                sources: self.sources.clone(),
-                destinations: self.destinations.clone(),
-                annotations: self.annotations.clone(),
-                fallback_speed: self.fallback_speed,
-                fallback_coordinate: self.fallback_coordinate.clone(),
-                scale_factor: self.scale_factor,
+    pub fn set_limits<'a>(&'a mut self, limits: &EngineLimits) -> &'a mut Self {
+        self.limits = limits.clone();
+        self
+    }
+
+    pub fn build(&self) -> Result<TableRequest, ValidationError> {
+        validation::check_general_options(&self.general_options, 1)?;
+        let count = self.general_options.coordinate.len();
+        for (field, indices) in [("sources", &self.sources), ("destinations", &self.destinations)].iter() {
+            if let Some(indices) = indices {
+                let indices: Vec<i64> = indices.iter().map(|&index| index as i64).collect();
+                validation::check_indices(field, &indices, count)?;
             }
-        )
+        }
+        if self.fallback_speed.is_nan() || self.fallback_speed <= 0.0 {
+            return Err(ValidationError::InvalidValue {
+                field: "fallback_speed",
+                value: self.fallback_speed,
+            });
+        }
+        if self.scale_factor.is_nan() || self.scale_factor <= 0.0 {
+            return Err(ValidationError::InvalidValue {
+                field: "scale_factor",
+                value: self.scale_factor,
+            });
+        }
+
+        let request = TableRequest {
+            general_options: self.general_options.clone(),
+            sources: self.sources.clone(),
+            destinations: self.destinations.clone(),
+            annotations: self.annotations.clone(),
+            fallback_speed: self.fallback_speed,
+            fallback_coordinate: self.fallback_coordinate.clone(),
+            scale_factor: self.scale_factor,
+        };
+        request.check_limits(&self.limits)?;
+
+        Ok(request)
     }
 }
 
@@ -1,6 +1,16 @@
-use std::{ffi::CStr, os::raw::{c_char, c_int}, slice};
+use std::{collections::HashSet, os::raw::{c_char, c_int}, slice};
 
-use crate::general::{c_structs::c_waypoint::CWaypoint, rs_structs::waypoint::Waypoint};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{
+        c_string_to_option_string, c_structs::c_waypoint::CWaypoint, rs_structs::waypoint::Waypoint,
+    },
+    osrm_error::OsrmError,
+    StringDecoding,
+};
+
+use super::matrix::Matrix;
 
 #[repr(C)]
 pub(crate) struct CTableResult {
@@ -14,84 +24,49 @@ pub(crate) struct CTableResult {
     number_of_destinations: c_int,
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableResult {
     pub code: Option<String>,
     pub message: Option<String>,
-    pub durations: Option<Vec<Vec<f64>>>,
-    pub distances: Option<Vec<Vec<f64>>>,
+    pub durations: Option<Matrix>,
+    pub distances: Option<Matrix>,
     pub sources: Option<Vec<Waypoint>>,
     pub destinations: Option<Vec<Waypoint>>,
+    pub fallback_speed_cells: Option<HashSet<[usize; 2]>>,
 }
 
 impl TableResult {
-    pub(crate) fn new(c_reasult: &CTableResult) -> TableResult {
-        let mut code: Option<String> = None;
-        if c_reasult.code != std::ptr::null_mut() {
-            let c_code_buf: *const c_char = c_reasult.code;
-            let c_code_str: &CStr = unsafe { CStr::from_ptr(c_code_buf) };
-            let code_str_slice: &str = c_code_str.to_str().unwrap();
-            code = Option::from(code_str_slice.to_owned());
-        }
-
-        let mut message: Option<String> = None;
-        if c_reasult.message != std::ptr::null_mut() {
-            let c_message_buf: *const c_char = c_reasult.message;
-            let c_message_str: &CStr = unsafe { CStr::from_ptr(c_message_buf) };
-            let message_str_slice: &str = c_message_str.to_str().unwrap();
-            message = Option::from(message_str_slice.to_owned());
-        }
-
-        let mut durations: Option<Vec<Vec<f64>>> = None;
-        if c_reasult.durations != std::ptr::null_mut() {
-            let durations_vec = unsafe {
-                slice::from_raw_parts(
-                    c_reasult.durations,
-                    (c_reasult.number_of_sources * c_reasult.number_of_destinations) as usize,
-                )
-            };
-
-            let mut rs_vec = Vec::new();
-            for i in 0..c_reasult.number_of_sources {
-                let mut rs_tmp_vec = Vec::new();
-                for j in 0..c_reasult.number_of_destinations {
-                    rs_tmp_vec.push(durations_vec[(i * c_reasult.number_of_destinations + j) as usize]);
-                }
-                rs_vec.push(rs_tmp_vec);
-            }
-
-            durations = Option::from(rs_vec);
-        }
+    pub(crate) fn new(
+        c_reasult: &CTableResult,
+        string_decoding: &StringDecoding,
+    ) -> Result<TableResult, OsrmError> {
+        let code = c_string_to_option_string(c_reasult.code, string_decoding)?;
+        let message = c_string_to_option_string(c_reasult.message, string_decoding)?;
 
-        let mut distances: Option<Vec<Vec<f64>>> = None;
-        if c_reasult.distances != std::ptr::null_mut() {
-            let distances_vec = unsafe {
-                slice::from_raw_parts(
-                    c_reasult.distances,
-                    (c_reasult.number_of_sources * c_reasult.number_of_destinations) as usize,
-                )
-            };
+        let rows = c_reasult.number_of_sources as usize;
+        let columns = c_reasult.number_of_destinations as usize;
 
-            let mut rs_vec = Vec::new();
-            for i in 0..c_reasult.number_of_sources {
-                let mut rs_tmp_vec = Vec::new();
-                for j in 0..c_reasult.number_of_destinations {
-                    rs_tmp_vec.push(distances_vec[(i * c_reasult.number_of_destinations + j) as usize]);
-                }
-                rs_vec.push(rs_tmp_vec);
-            }
+        let durations = if !c_reasult.durations.is_null() {
+            Some(unsafe { Matrix::from_c(c_reasult.durations, rows, columns) })
+        } else {
+            None
+        };
 
-            distances = Option::from(rs_vec);
-        }
+        let distances = if !c_reasult.distances.is_null() {
+            Some(unsafe { Matrix::from_c(c_reasult.distances, rows, columns) })
+        } else {
+            None
+        };
 
         let sources: Option<Vec<Waypoint>> = if c_reasult.sources != std::ptr::null_mut() {
             Some(
                 unsafe {
                     slice::from_raw_parts(c_reasult.sources, c_reasult.number_of_sources as usize)
-                        .to_vec()
                 }
                 .iter()
-                .map(|source| source.into())
-                .collect(),
+                .map(|source| Waypoint::new(source, string_decoding))
+                .collect::<Result<Vec<Waypoint>, OsrmError>>()?,
             )
         } else {
             None
@@ -105,23 +80,125 @@ impl TableResult {
                             c_reasult.destinations,
                             c_reasult.number_of_destinations as usize,
                         )
-                        .to_vec()
                     }
                     .iter()
-                    .map(|destination| destination.into())
-                    .collect(),
+                    .map(|destination| Waypoint::new(destination, string_decoding))
+                    .collect::<Result<Vec<Waypoint>, OsrmError>>()?,
                 )
             } else {
                 None
             };
 
-        TableResult {
+        Ok(TableResult {
             code,
             message,
             durations,
             distances,
             sources,
             destinations,
+            // The c_osrm table result does not expose the estimated cells yet.
+            fallback_speed_cells: None,
+        })
+    }
+
+    pub fn is_estimated(&self, source: usize, destination: usize) -> bool {
+        self.fallback_speed_cells
+            .as_ref()
+            .is_some_and(|cells| cells.contains(&[source, destination]))
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_osrm_json(response: &str) -> Result<TableResult, OsrmError> {
+        let value = json::parse(response)?;
+        json::check_code(&value)?;
+        TableResult::from_json(&value)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_osrm_json(&self) -> String {
+        json::render(&self.to_json())
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut result = serde_json::json!({
+            "code": self.code.clone().unwrap_or_else(|| "Ok".to_string()),
+        });
+        json::insert_opt_string(&mut result, "message", &self.message);
+        if let Some(durations) = &self.durations {
+            result["durations"] = durations.to_json();
+        }
+        if let Some(distances) = &self.distances {
+            result["distances"] = distances.to_json();
+        }
+        if let Some(sources) = &self.sources {
+            result["sources"] =
+                serde_json::Value::Array(sources.iter().map(Waypoint::to_json).collect());
+        }
+        if let Some(destinations) = &self.destinations {
+            result["destinations"] =
+                serde_json::Value::Array(destinations.iter().map(Waypoint::to_json).collect());
+        }
+        if let Some(fallback_speed_cells) = &self.fallback_speed_cells {
+            let mut fallback_speed_cells: Vec<&[usize; 2]> = fallback_speed_cells.iter().collect();
+            fallback_speed_cells.sort();
+            result["fallback_speed_cells"] = serde_json::json!(fallback_speed_cells);
         }
+        result
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<TableResult, OsrmError> {
+        let matrix = |name: &str| match json::opt_array_field(value, name) {
+            Some(rows) => Matrix::from_rows(
+                &rows
+                    .iter()
+                    .map(|row| row.as_array().map(|row| json::f64_array(row)).unwrap_or_default())
+                    .collect::<Vec<Vec<f64>>>(),
+            )
+            .map(Some)
+            .map_err(|e| OsrmError::Json(format!("field `{}`: {}", name, e.message()))),
+            None => Ok(None),
+        };
+        let waypoints = |name: &str| match json::opt_array_field(value, name) {
+            Some(waypoints) => waypoints
+                .iter()
+                .map(Waypoint::from_json)
+                .collect::<Result<Vec<Waypoint>, OsrmError>>()
+                .map(Some),
+            None => Ok(None),
+        };
+
+        Ok(TableResult {
+            code: json::opt_string_field(value, "code"),
+            message: json::opt_string_field(value, "message"),
+            durations: matrix("durations")?,
+            distances: matrix("distances")?,
+            sources: waypoints("sources")?,
+            destinations: waypoints("destinations")?,
+            fallback_speed_cells: match json::opt_array_field(value, "fallback_speed_cells") {
+                Some(cells) => Some(
+                    cells
+                        .iter()
+                        .map(fallback_speed_cell)
+                        .collect::<Result<HashSet<[usize; 2]>, OsrmError>>()?,
+                ),
+                None => None,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+fn fallback_speed_cell(cell: &serde_json::Value) -> Result<[usize; 2], OsrmError> {
+    let pair = cell.as_array().and_then(|pair| match pair.as_slice() {
+        [source, destination] => Some([source.as_u64()? as usize, destination.as_u64()? as usize]),
+        _ => None,
+    });
+    pair.ok_or_else(|| {
+        OsrmError::Json(format!(
+            "field `fallback_speed_cells` holds {}, not a [source, destination] pair",
+            cell
+        ))
+    })
+}
@@ -0,0 +1,297 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use rusqlite::{params, Connection};
+
+use crate::{backend::RoutingBackend, osrm_error::OsrmError};
+
+use super::tile_range::{validate_zoom, BoundingBox, TileRange};
+
+const SPEEDS_FIELDS: &str = "{\"speed\":\"Number\",\"is_small\":\"Boolean\",\"datasource\":\"String\",\"weight\":\"Number\",\"duration\":\"Number\",\"name\":\"String\"}";
+const TURNS_FIELDS: &str = "{\"bearing_in\":\"Number\",\"turn_angle\":\"Number\",\"cost\":\"Number\",\"weight\":\"Number\",\"turn_type\":\"String\",\"turn_modifier\":\"String\"}";
+
+#[derive(Debug, Clone)]
+pub struct MbtilesExport {
+    pub bbox: BoundingBox,
+    pub min_zoom: i32,
+    pub max_zoom: i32,
+    pub name: String,
+    pub description: String,
+    pub threads: usize,
+}
+
+impl MbtilesExport {
+    pub fn new(bbox: BoundingBox, min_zoom: i32, max_zoom: i32) -> MbtilesExport {
+        MbtilesExport {
+            bbox,
+            min_zoom,
+            max_zoom,
+            name: "osrm debug tiles".to_string(),
+            description: "OSRM speeds and turns debug layers".to_string(),
+            threads: 1,
+        }
+    }
+
+    pub fn ranges(&self) -> Result<Vec<TileRange>, OsrmError> {
+        validate_zoom(self.min_zoom)?;
+        validate_zoom(self.max_zoom)?;
+        if self.min_zoom > self.max_zoom {
+            return Err(OsrmError::InvalidValue(format!(
+                "Minimum zoom {} is above maximum zoom {}",
+                self.min_zoom, self.max_zoom
+            )));
+        }
+
+        (self.min_zoom..=self.max_zoom)
+            .map(|z| TileRange::new(&self.bbox, z))
+            .collect()
+    }
+
+    // Tiles are written to `<path>.partial` while they are fetched, and the file is only renamed
+    // to `path` once every tile is in, so a failed export leaves nothing behind.
+    pub fn export<B: RoutingBackend + Sync + ?Sized, P: AsRef<Path>>(
+        &self,
+        backend: &B,
+        path: P,
+    ) -> Result<usize, OsrmError> {
+        let ranges = self.ranges()?;
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(OsrmError::Io(format!("{} already exists", path.display())));
+        }
+
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        let _ = fs::remove_file(&partial);
+
+        let written = Connection::open(&partial)
+            .map_err(sqlite_error)
+            .and_then(|mut connection| self.write(backend, ranges, &mut connection))
+            .and_then(|written| {
+                fs::rename(&partial, path)
+                    .map(|_| written)
+                    .map_err(|e| OsrmError::Io(e.to_string()))
+            });
+        if written.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+
+        written
+    }
+
+    fn write<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+        ranges: Vec<TileRange>,
+        connection: &mut Connection,
+    ) -> Result<usize, OsrmError> {
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        transaction
+            .execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                 CREATE UNIQUE INDEX name ON metadata (name);
+                 CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+                 CREATE UNIQUE INDEX tile_index ON tiles (zoom_level, tile_column, tile_row);",
+            )
+            .map_err(sqlite_error)?;
+
+        for (name, value) in self.metadata() {
+            transaction
+                .execute(
+                    "INSERT INTO metadata (name, value) VALUES (?1, ?2)",
+                    params![name, value],
+                )
+                .map_err(sqlite_error)?;
+        }
+
+        let mut written = 0;
+        for range in ranges {
+            range.for_each_parallel(backend, self.threads, |tile, result| {
+                let data = result?.result;
+                if data.is_empty() {
+                    return Ok::<(), OsrmError>(());
+                }
+
+                transaction
+                    .execute(
+                        "INSERT INTO tiles (zoom_level, tile_column, tile_row, tile_data) VALUES (?1, ?2, ?3, ?4)",
+                        params![tile.z, tile.x, (1 << tile.z) - 1 - tile.y, gzip(&data)?],
+                    )
+                    .map_err(sqlite_error)?;
+                written += 1;
+                Ok(())
+            })?;
+        }
+
+        transaction.commit().map_err(sqlite_error)?;
+        Ok(written)
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        let south_west = &self.bbox.south_west;
+        let north_east = &self.bbox.north_east;
+
+        vec![
+            ("name", self.name.clone()),
+            ("description", self.description.clone()),
+            ("format", "pbf".to_string()),
+            ("type", "overlay".to_string()),
+            ("version", "1".to_string()),
+            (
+                "bounds",
+                format!(
+                    "{},{},{},{}",
+                    south_west.longitude,
+                    south_west.latitude,
+                    north_east.longitude,
+                    north_east.latitude
+                ),
+            ),
+            (
+                "center",
+                format!(
+                    "{},{},{}",
+                    (south_west.longitude + north_east.longitude) / 2.0,
+                    (south_west.latitude + north_east.latitude) / 2.0,
+                    self.min_zoom
+                ),
+            ),
+            ("minzoom", self.min_zoom.to_string()),
+            ("maxzoom", self.max_zoom.to_string()),
+            (
+                "json",
+                format!(
+                    "{{\"vector_layers\":[{},{}]}}",
+                    self.vector_layer("speeds", SPEEDS_FIELDS),
+                    self.vector_layer("turns", TURNS_FIELDS)
+                ),
+            ),
+        ]
+    }
+
+    fn vector_layer(&self, id: &str, fields: &str) -> String {
+        format!(
+            "{{\"id\":\"{}\",\"minzoom\":{},\"maxzoom\":{},\"fields\":{}}}",
+            id, self.min_zoom, self.max_zoom, fields
+        )
+    }
+}
+
+fn gzip(data: &[u8]) -> Result<Vec<u8>, OsrmError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| OsrmError::Io(e.to_string()))
+}
+
+fn sqlite_error(error: rusqlite::Error) -> OsrmError {
+    OsrmError::Io(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Read, process};
+
+    use flate2::read::GzDecoder;
+    use rusqlite::Connection;
+
+    use crate::{
+        backend::fake_backend::FakeBackend,
+        general::rs_structs::coordinate::Coordinate,
+        osrm_error::OsrmError,
+        tile_api::{tile_range::BoundingBox, tile_result::TileResult},
+    };
+
+    use super::MbtilesExport;
+
+    // Every tile holds its own z/x/y, except the empty column at x = 4401.
+    fn tile_numbers() -> FakeBackend<'static> {
+        FakeBackend::new().on_tile(|request| {
+            let result = if request.x == 4401 {
+                Vec::new()
+            } else {
+                format!("{}/{}/{}", request.z, request.x, request.y).into_bytes()
+            };
+
+            Ok(TileResult { result })
+        })
+    }
+
+    #[test]
+    fn exports_tiles_and_metadata() {
+        let path = env::temp_dir().join(format!("rs_osrm_{}.mbtiles", process::id()));
+        let _ = fs::remove_file(&path);
+
+        let bbox = BoundingBox::new(Coordinate::new(52.50, 13.35), Coordinate::new(52.53, 13.42));
+        let mut export = MbtilesExport::new(bbox, 13, 14);
+        export.threads = 2;
+        let backend = tile_numbers();
+        let written = export.export(&backend, &path).unwrap();
+        assert!(export.export(&backend, &path).is_err());
+
+        let connection = Connection::open(&path).unwrap();
+        let stored: i64 = connection
+            .query_row("SELECT COUNT(*) FROM tiles", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored as usize, written);
+        assert_eq!(written, 4 + export.ranges().unwrap()[1].len());
+
+        let data: Vec<u8> = connection
+            .query_row(
+                "SELECT tile_data FROM tiles WHERE zoom_level = 13 AND tile_column = 4399 AND tile_row = ?1",
+                [(1 << 13) - 1 - 2686],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let mut tile = String::new();
+        GzDecoder::new(&data[..]).read_to_string(&mut tile).unwrap();
+        assert_eq!(tile, "13/4399/2686");
+
+        let format: String = connection
+            .query_row(
+                "SELECT value FROM metadata WHERE name = 'format'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(format, "pbf");
+        let bounds: String = connection
+            .query_row(
+                "SELECT value FROM metadata WHERE name = 'bounds'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(bounds, "13.35,52.5,13.42,52.53");
+
+        drop(connection);
+        fs::remove_file(&path).unwrap();
+
+        let inverted = MbtilesExport::new(
+            BoundingBox::new(Coordinate::new(52.50, 13.35), Coordinate::new(52.53, 13.42)),
+            14,
+            13,
+        );
+        assert!(matches!(
+            inverted.export(&backend, &path),
+            Err(OsrmError::InvalidValue(_))
+        ));
+
+        let failing = FakeBackend::new().on_tile(|request| match request.x {
+            4400 => Err(OsrmError::Ffi("tile failed".to_string())),
+            _ => Ok(TileResult { result: vec![1] }),
+        });
+        assert!(matches!(
+            export.export(&failing, &path),
+            Err(OsrmError::Ffi(_))
+        ));
+        assert!(!path.exists());
+        assert!(!path.with_extension("mbtiles.partial").exists());
+    }
+}
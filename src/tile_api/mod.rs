@@ -4,6 +4,10 @@ use crate::Status;
 
 use self::{tile_request::CTileRequest, tile_result::CTileResult};
 
+#[cfg(feature = "mbtiles")]
+pub mod mbtiles;
+pub mod mvt;
+pub mod tile_range;
 pub mod tile_request;
 pub mod tile_result;
 
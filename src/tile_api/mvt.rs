@@ -0,0 +1,605 @@
+use std::f64::consts::PI;
+
+use crate::{general::rs_structs::coordinate::Coordinate, osrm_error::OsrmError};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TilePoint {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl TilePoint {
+    pub fn to_coordinate(&self, x: i32, y: i32, z: i32, extent: u32) -> Coordinate {
+        let tiles = 2f64.powi(z);
+        let tile_x = x as f64 + self.x as f64 / extent as f64;
+        let tile_y = y as f64 + self.y as f64 / extent as f64;
+
+        Coordinate::new(
+            (PI * (1.0 - 2.0 * tile_y / tiles))
+                .sinh()
+                .atan()
+                .to_degrees(),
+            tile_x / tiles * 360.0 - 180.0,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TileValue {
+    String(String),
+    Float(f32),
+    Double(f64),
+    Int(i64),
+    UInt(u64),
+    SInt(i64),
+    Bool(bool),
+}
+
+impl TileValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            TileValue::Float(value) => Some(*value as f64),
+            TileValue::Double(value) => Some(*value),
+            TileValue::Int(value) | TileValue::SInt(value) => Some(*value as f64),
+            TileValue::UInt(value) => Some(*value as f64),
+            TileValue::String(_) | TileValue::Bool(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TileValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            TileValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeedFeature {
+    pub speed: u32,
+    pub is_small: bool,
+    pub datasource: String,
+    pub weight: f64,
+    pub duration: f64,
+    pub name: Option<String>,
+    pub geometry: Vec<Vec<TilePoint>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TurnFeature {
+    pub bearing_in: i32,
+    pub turn_angle: i32,
+    pub cost: f64,
+    pub weight: f64,
+    pub turn_type: Option<String>,
+    pub turn_modifier: Option<String>,
+    pub location: TilePoint,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebugTile {
+    pub extent: u32,
+    pub speeds: Vec<SpeedFeature>,
+    pub turns: Vec<TurnFeature>,
+}
+
+pub fn decode(bytes: &[u8]) -> Result<DebugTile, OsrmError> {
+    let mut tile = DebugTile {
+        extent: 4096,
+        speeds: Vec::new(),
+        turns: Vec::new(),
+    };
+
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire_type)) = reader.next_field()? {
+        if field != 3 {
+            reader.skip(wire_type)?;
+            continue;
+        }
+
+        let layer = Layer::decode(reader.read_bytes()?)?;
+        tile.extent = layer.extent;
+        match layer.name.as_str() {
+            "speeds" => {
+                for feature in &layer.features {
+                    tile.speeds.push(SpeedFeature {
+                        speed: layer.number(feature, "speed")? as u32,
+                        is_small: layer
+                            .value(feature, "is_small")
+                            .and_then(TileValue::as_bool)
+                            .unwrap_or(false),
+                        datasource: layer.string(feature, "datasource").unwrap_or_default(),
+                        weight: layer.number(feature, "weight")?,
+                        duration: layer.number(feature, "duration")?,
+                        name: layer.string(feature, "name"),
+                        geometry: feature.lines()?,
+                    });
+                }
+            }
+            "turns" => {
+                for feature in &layer.features {
+                    tile.turns.push(TurnFeature {
+                        bearing_in: layer.number(feature, "bearing_in")? as i32,
+                        turn_angle: layer.number(feature, "turn_angle")? as i32,
+                        cost: layer.number(feature, "cost")?,
+                        weight: layer.number(feature, "weight")?,
+                        turn_type: layer.string(feature, "turn_type"),
+                        turn_modifier: layer.string(feature, "turn_modifier"),
+                        location: feature.lines()?.into_iter().flatten().next().ok_or_else(
+                            || OsrmError::Mvt("turn feature without a point".to_string()),
+                        )?,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(tile)
+}
+
+struct Feature {
+    tags: Vec<u32>,
+    geometry: Vec<u32>,
+}
+
+impl Feature {
+    fn lines(&self) -> Result<Vec<Vec<TilePoint>>, OsrmError> {
+        let mut lines: Vec<Vec<TilePoint>> = Vec::new();
+        let (mut x, mut y) = (0i32, 0i32);
+        let mut commands = self.geometry.iter();
+
+        while let Some(command) = commands.next() {
+            let (id, count) = (command & 0x7, command >> 3);
+            match id {
+                1 | 2 => {
+                    for _ in 0..count {
+                        let (dx, dy) = match (commands.next(), commands.next()) {
+                            (Some(dx), Some(dy)) => (zigzag(*dx as u64), zigzag(*dy as u64)),
+                            _ => {
+                                return Err(OsrmError::Mvt(
+                                    "geometry command is missing parameters".to_string(),
+                                ))
+                            }
+                        };
+                        let overflow = || {
+                            OsrmError::Mvt(
+                                "geometry runs outside the 32-bit tile space".to_string(),
+                            )
+                        };
+                        x = x.checked_add(dx as i32).ok_or_else(overflow)?;
+                        y = y.checked_add(dy as i32).ok_or_else(overflow)?;
+
+                        if id == 1 || lines.is_empty() {
+                            lines.push(Vec::new());
+                        }
+                        if let Some(line) = lines.last_mut() {
+                            line.push(TilePoint { x, y });
+                        }
+                    }
+                }
+                7 => {
+                    if let Some(first) = lines.last().and_then(|line| line.first()).cloned() {
+                        lines.last_mut().unwrap().push(first);
+                    }
+                }
+                _ => return Err(OsrmError::Mvt(format!("unknown geometry command {}", id))),
+            }
+        }
+
+        Ok(lines)
+    }
+}
+
+struct Layer {
+    name: String,
+    extent: u32,
+    keys: Vec<String>,
+    values: Vec<TileValue>,
+    features: Vec<Feature>,
+}
+
+impl Layer {
+    fn decode(bytes: &[u8]) -> Result<Layer, OsrmError> {
+        let mut layer = Layer {
+            name: String::new(),
+            extent: 4096,
+            keys: Vec::new(),
+            values: Vec::new(),
+            features: Vec::new(),
+        };
+
+        let mut reader = Reader::new(bytes);
+        while let Some((field, wire_type)) = reader.next_field()? {
+            match field {
+                1 => layer.name = reader.read_string()?,
+                2 => layer.features.push(decode_feature(reader.read_bytes()?)?),
+                3 => layer.keys.push(reader.read_string()?),
+                4 => layer.values.push(decode_value(reader.read_bytes()?)?),
+                5 => layer.extent = reader.read_varint()? as u32,
+                _ => reader.skip(wire_type)?,
+            }
+        }
+
+        Ok(layer)
+    }
+
+    fn value(&self, feature: &Feature, key: &str) -> Option<&TileValue> {
+        feature.tags.chunks(2).find_map(|tag| match tag {
+            [key_index, value_index]
+                if self.keys.get(*key_index as usize).map(String::as_str) == Some(key) =>
+            {
+                self.values.get(*value_index as usize)
+            }
+            _ => None,
+        })
+    }
+
+    fn number(&self, feature: &Feature, key: &str) -> Result<f64, OsrmError> {
+        self.value(feature, key)
+            .and_then(TileValue::as_f64)
+            .ok_or_else(|| {
+                OsrmError::Mvt(format!(
+                    "feature in layer `{}` has no numeric `{}`",
+                    self.name, key
+                ))
+            })
+    }
+
+    fn string(&self, feature: &Feature, key: &str) -> Option<String> {
+        self.value(feature, key)
+            .and_then(TileValue::as_str)
+            .map(|value| value.to_string())
+    }
+}
+
+fn decode_feature(bytes: &[u8]) -> Result<Feature, OsrmError> {
+    let mut feature = Feature {
+        tags: Vec::new(),
+        geometry: Vec::new(),
+    };
+
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire_type)) = reader.next_field()? {
+        match (field, wire_type) {
+            (2, 2) => feature.tags.extend(reader.read_packed()?),
+            (2, 0) => feature.tags.push(reader.read_varint()? as u32),
+            (4, 2) => feature.geometry.extend(reader.read_packed()?),
+            (4, 0) => feature.geometry.push(reader.read_varint()? as u32),
+            _ => reader.skip(wire_type)?,
+        }
+    }
+
+    Ok(feature)
+}
+
+fn decode_value(bytes: &[u8]) -> Result<TileValue, OsrmError> {
+    let mut reader = Reader::new(bytes);
+    let mut value = None;
+
+    while let Some((field, wire_type)) = reader.next_field()? {
+        value = Some(match field {
+            1 => TileValue::String(reader.read_string()?),
+            2 => TileValue::Float(f32::from_bits(reader.read_fixed32()?)),
+            3 => TileValue::Double(f64::from_bits(reader.read_fixed64()?)),
+            4 => TileValue::Int(reader.read_varint()? as i64),
+            5 => TileValue::UInt(reader.read_varint()?),
+            6 => TileValue::SInt(zigzag(reader.read_varint()?)),
+            7 => TileValue::Bool(reader.read_varint()? != 0),
+            _ => {
+                reader.skip(wire_type)?;
+                continue;
+            }
+        });
+    }
+
+    value.ok_or_else(|| OsrmError::Mvt("empty property value".to_string()))
+}
+
+fn zigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    fn next_field(&mut self) -> Result<Option<(u64, u64)>, OsrmError> {
+        if self.position >= self.bytes.len() {
+            return Ok(None);
+        }
+
+        let key = self.read_varint()?;
+        Ok(Some((key >> 3, key & 0x7)))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, OsrmError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .bytes
+                .get(self.position)
+                .ok_or_else(|| OsrmError::Mvt("truncated varint".to_string()))?;
+            self.position += 1;
+
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(OsrmError::Mvt("varint is too long".to_string()))
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], OsrmError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| OsrmError::Mvt("field runs past the end of the tile".to_string()))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], OsrmError> {
+        let length = self.read_varint()? as usize;
+        self.take(length)
+    }
+
+    fn read_string(&mut self) -> Result<String, OsrmError> {
+        String::from_utf8(self.read_bytes()?.to_vec())
+            .map_err(|e| OsrmError::InvalidUtf8(e.to_string()))
+    }
+
+    fn read_packed(&mut self) -> Result<Vec<u32>, OsrmError> {
+        let mut reader = Reader::new(self.read_bytes()?);
+        let mut values = Vec::new();
+        while reader.position < reader.bytes.len() {
+            values.push(reader.read_varint()? as u32);
+        }
+
+        Ok(values)
+    }
+
+    fn read_fixed32(&mut self) -> Result<u32, OsrmError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_fixed64(&mut self) -> Result<u64, OsrmError> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn skip(&mut self, wire_type: u64) -> Result<(), OsrmError> {
+        match wire_type {
+            0 => self.read_varint().map(|_| ()),
+            1 => self.take(8).map(|_| ()),
+            2 => self.read_bytes().map(|_| ()),
+            5 => self.take(4).map(|_| ()),
+            _ => Err(OsrmError::Mvt(format!(
+                "unsupported wire type {}",
+                wire_type
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osrm_error::OsrmError;
+
+    use super::{decode, Feature, TilePoint};
+
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn field(number: u64, bytes: &[u8], out: &mut Vec<u8>) {
+        varint(number << 3 | 2, out);
+        varint(bytes.len() as u64, out);
+        out.extend_from_slice(bytes);
+    }
+
+    fn packed(number: u64, values: &[u32], out: &mut Vec<u8>) {
+        let mut bytes = Vec::new();
+        values
+            .iter()
+            .for_each(|value| varint(*value as u64, &mut bytes));
+        field(number, &bytes, out);
+    }
+
+    fn zigzag(value: i32) -> u32 {
+        ((value << 1) ^ (value >> 31)) as u32
+    }
+
+    fn layer(name: &str, keys: &[&str], values: &[Vec<u8>], features: &[Vec<u8>]) -> Vec<u8> {
+        let mut layer = Vec::new();
+        varint(15 << 3, &mut layer);
+        varint(2, &mut layer);
+        field(1, name.as_bytes(), &mut layer);
+        features
+            .iter()
+            .for_each(|feature| field(2, feature, &mut layer));
+        keys.iter()
+            .for_each(|key| field(3, key.as_bytes(), &mut layer));
+        values.iter().for_each(|value| field(4, value, &mut layer));
+        varint(5 << 3, &mut layer);
+        varint(4096, &mut layer);
+        layer
+    }
+
+    fn feature(tags: &[u32], geometry: &[u32]) -> Vec<u8> {
+        let mut feature = Vec::new();
+        packed(2, tags, &mut feature);
+        varint(3 << 3, &mut feature);
+        varint(if geometry.len() == 3 { 1 } else { 2 }, &mut feature);
+        packed(4, geometry, &mut feature);
+        feature
+    }
+
+    fn string_value(value: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        field(1, value.as_bytes(), &mut bytes);
+        bytes
+    }
+
+    fn double_value(value: f64) -> Vec<u8> {
+        let mut bytes = vec![3 << 3 | 1];
+        bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+        bytes
+    }
+
+    fn float_value(value: f32) -> Vec<u8> {
+        let mut bytes = vec![2 << 3 | 5];
+        bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+        bytes
+    }
+
+    fn uint_value(value: u64) -> Vec<u8> {
+        let mut bytes = vec![5 << 3];
+        varint(value, &mut bytes);
+        bytes
+    }
+
+    fn sint_value(value: i32) -> Vec<u8> {
+        let mut bytes = vec![6 << 3];
+        varint(zigzag(value) as u64, &mut bytes);
+        bytes
+    }
+
+    fn bool_value(value: bool) -> Vec<u8> {
+        vec![7 << 3, value as u8]
+    }
+
+    #[test]
+    fn speeds_and_turns_layers_are_decoded() {
+        let speeds = layer(
+            "speeds",
+            &[
+                "speed",
+                "is_small",
+                "datasource",
+                "weight",
+                "duration",
+                "name",
+            ],
+            &[
+                uint_value(42),
+                bool_value(true),
+                string_value("lua profile"),
+                double_value(12.5),
+                double_value(11.3),
+                string_value("Unter den Linden"),
+            ],
+            &[feature(
+                &[0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5],
+                &[
+                    9,
+                    zigzag(100),
+                    zigzag(200),
+                    18,
+                    zigzag(10),
+                    zigzag(-5),
+                    zigzag(20),
+                    zigzag(0),
+                ],
+            )],
+        );
+        let turns = layer(
+            "turns",
+            &[
+                "bearing_in",
+                "turn_angle",
+                "cost",
+                "weight",
+                "turn_type",
+                "turn_modifier",
+            ],
+            &[
+                uint_value(90),
+                sint_value(-45),
+                float_value(2.5),
+                double_value(3.0),
+                string_value("turn"),
+                string_value("left"),
+            ],
+            &[feature(
+                &[0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5],
+                &[9, zigzag(4096), zigzag(0)],
+            )],
+        );
+        let mut bytes = Vec::new();
+        field(3, &speeds, &mut bytes);
+        field(3, &turns, &mut bytes);
+
+        let tile = decode(&bytes).unwrap();
+
+        assert_eq!(tile.extent, 4096);
+        assert_eq!(tile.speeds.len(), 1);
+        let speed = &tile.speeds[0];
+        assert_eq!(speed.speed, 42);
+        assert!(speed.is_small);
+        assert_eq!(speed.datasource, "lua profile");
+        assert_eq!(speed.weight, 12.5);
+        assert_eq!(speed.duration, 11.3);
+        assert_eq!(speed.name.as_deref(), Some("Unter den Linden"));
+        assert_eq!(
+            speed.geometry,
+            vec![vec![
+                TilePoint { x: 100, y: 200 },
+                TilePoint { x: 110, y: 195 },
+                TilePoint { x: 130, y: 195 },
+            ]]
+        );
+
+        let turn = &tile.turns[0];
+        assert_eq!(turn.bearing_in, 90);
+        assert_eq!(turn.turn_angle, -45);
+        assert_eq!(turn.cost, 2.5);
+        assert_eq!(turn.weight, 3.0);
+        assert_eq!(turn.turn_type.as_deref(), Some("turn"));
+        assert_eq!(turn.turn_modifier.as_deref(), Some("left"));
+
+        let coordinate = turn.location.to_coordinate(4400, 2686, 13, tile.extent);
+        assert!((coordinate.longitude - 13.4033203125).abs() < 1e-9);
+        assert!((coordinate.latitude - 52.536273041).abs() < 1e-9);
+
+        assert!(decode(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn geometries_leaving_the_tile_space_are_rejected() {
+        let feature = Feature {
+            tags: Vec::new(),
+            geometry: vec![9, zigzag(i32::MAX), 0, 10, zigzag(1), 0],
+        };
+        assert!(matches!(feature.lines(), Err(OsrmError::Mvt(_))));
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    convert::Infallible,
+    f64::consts::PI,
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+use crate::{
+    backend::RoutingBackend, general::rs_structs::coordinate::Coordinate, osrm_error::OsrmError,
+};
+
+use super::{tile_request::TileRequest, tile_result::TileResult};
+
+pub const MIN_ZOOM: i32 = 12;
+pub const MAX_ZOOM: i32 = 30;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox {
+    pub south_west: Coordinate,
+    pub north_east: Coordinate,
+}
+
+impl BoundingBox {
+    pub fn new(south_west: Coordinate, north_east: Coordinate) -> BoundingBox {
+        BoundingBox {
+            south_west,
+            north_east,
+        }
+    }
+}
+
+pub fn validate_zoom(z: i32) -> Result<(), OsrmError> {
+    if !(MIN_ZOOM..=MAX_ZOOM).contains(&z) {
+        return Err(OsrmError::InvalidValue(format!(
+            "Tile zoom must be between {} and {}, got {}",
+            MIN_ZOOM, MAX_ZOOM, z
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn tile_for_coordinate(coordinate: &Coordinate, z: i32) -> Result<(i32, i32), OsrmError> {
+    validate_zoom(z)?;
+
+    let tiles = 2f64.powi(z);
+    let latitude = coordinate
+        .latitude
+        .clamp(-85.0511287798, 85.0511287798)
+        .to_radians();
+    let x = (coordinate.longitude + 180.0) / 360.0 * tiles;
+    let y = (1.0 - latitude.tan().asinh() / PI) / 2.0 * tiles;
+
+    let last = tiles as i64 - 1;
+    Ok((
+        (x.floor() as i64).clamp(0, last) as i32,
+        (y.floor() as i64).clamp(0, last) as i32,
+    ))
+}
+
+pub fn tile_origin(x: i32, y: i32, z: i32) -> Coordinate {
+    let tiles = 2f64.powi(z);
+
+    Coordinate::new(
+        (PI * (1.0 - 2.0 * y as f64 / tiles))
+            .sinh()
+            .atan()
+            .to_degrees(),
+        x as f64 / tiles * 360.0 - 180.0,
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct TileRange {
+    pub z: i32,
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32,
+    next: usize,
+}
+
+impl TileRange {
+    pub fn new(bbox: &BoundingBox, z: i32) -> Result<TileRange, OsrmError> {
+        let (min_x, max_y) = tile_for_coordinate(&bbox.south_west, z)?;
+        let (max_x, min_y) = tile_for_coordinate(&bbox.north_east, z)?;
+        if min_x > max_x || min_y > max_y {
+            return Err(OsrmError::InvalidValue(
+                "Bounding box south west corner must be below and left of its north east corner"
+                    .to_string(),
+            ));
+        }
+
+        Ok(TileRange {
+            z,
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            next: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.width() * (self.max_y - self.min_y + 1) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn fetch<'a, B: RoutingBackend + ?Sized>(
+        self,
+        backend: &'a B,
+    ) -> impl Iterator<Item = (TileRequest, Result<TileResult, OsrmError>)> + 'a {
+        self.map(move |mut request| {
+            let result = request.run(backend);
+            (request, result)
+        })
+    }
+
+    pub fn fetch_parallel<B: RoutingBackend + Sync + ?Sized>(
+        self,
+        backend: &B,
+        threads: usize,
+    ) -> Vec<(TileRequest, Result<TileResult, OsrmError>)> {
+        let (min_x, min_y, width) = (self.min_x, self.min_y, self.width());
+        let mut tiles: Vec<Option<(TileRequest, Result<TileResult, OsrmError>)>> =
+            (0..self.len()).map(|_| None).collect();
+
+        self.for_each_parallel(backend, threads, |request, result| {
+            let index = (request.y - min_y) as usize * width + (request.x - min_x) as usize;
+            tiles[index] = Some((request, result));
+            Ok::<(), Infallible>(())
+        })
+        .unwrap_or_else(|never| match never {});
+
+        tiles.into_iter().flatten().collect()
+    }
+
+    // Hands each tile to `f` on the calling thread as soon as a worker has fetched it, so only
+    // about one tile per thread is held in memory. Tiles arrive in no particular order; an error
+    // returned by `f` stops the workers and is passed on.
+    pub fn for_each_parallel<B, F, E>(self, backend: &B, threads: usize, mut f: F) -> Result<(), E>
+    where
+        B: RoutingBackend + Sync + ?Sized,
+        F: FnMut(TileRequest, Result<TileResult, OsrmError>) -> Result<(), E>,
+    {
+        let threads = threads.max(1).min(self.len().max(1));
+        let requests = Mutex::new(self);
+        let (sender, receiver) = mpsc::sync_channel(threads);
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                let sender = sender.clone();
+                let requests = &requests;
+                scope.spawn(move || loop {
+                    let mut request = match requests.lock().unwrap().next() {
+                        Some(request) => request,
+                        None => break,
+                    };
+
+                    let result = request.run(backend);
+                    if sender.send((request, result)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            // The receiver is moved in here so that returning early drops it and unblocks the
+            // workers before the scope joins them.
+            let receiver = receiver;
+            for (request, result) in receiver {
+                f(request, result)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn width(&self) -> usize {
+        (self.max_x - self.min_x + 1) as usize
+    }
+}
+
+impl Iterator for TileRange {
+    type Item = TileRequest;
+
+    fn next(&mut self) -> Option<TileRequest> {
+        if self.next >= self.len() {
+            return None;
+        }
+
+        let index = self.next;
+        self.next += 1;
+
+        Some(TileRequest::new(
+            self.min_x + (index % self.width()) as i32,
+            self.min_y + (index / self.width()) as i32,
+            self.z,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::fake_backend::FakeBackend,
+        general::rs_structs::coordinate::Coordinate,
+        osrm_error::OsrmError,
+        tile_api::{tile_request::TileRequest, tile_result::TileResult},
+    };
+
+    use super::{tile_for_coordinate, tile_origin, BoundingBox, TileRange};
+
+    fn tile_numbers() -> FakeBackend<'static> {
+        FakeBackend::new().on_tile(|request| {
+            Ok(TileResult {
+                result: format!("{}/{}/{}", request.z, request.x, request.y).into_bytes(),
+            })
+        })
+    }
+
+    #[test]
+    fn coordinates_map_to_slippy_tiles() {
+        let brandenburg_gate = Coordinate::new(52.516275, 13.377704);
+        assert_eq!(
+            tile_for_coordinate(&brandenburg_gate, 13).unwrap(),
+            (4400, 2686)
+        );
+        assert_eq!(
+            tile_for_coordinate(&brandenburg_gate, 16).unwrap(),
+            (35203, 21493)
+        );
+        assert!(tile_for_coordinate(&brandenburg_gate, 11).is_err());
+
+        let origin = tile_origin(4400, 2686, 13);
+        assert!((origin.longitude - 13.359375).abs() < 1e-9);
+        assert!((origin.latitude - 52.536273041).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bounding_boxes_cover_every_tile() {
+        let bbox = BoundingBox::new(Coordinate::new(52.50, 13.35), Coordinate::new(52.53, 13.42));
+        let range = TileRange::new(&bbox, 13).unwrap();
+        assert_eq!((range.min_x, range.max_x), (4399, 4401));
+        assert_eq!((range.min_y, range.max_y), (2686, 2687));
+        assert_eq!(range.len(), 6);
+
+        let tiles: Vec<(i32, i32, i32)> = range.map(|tile| (tile.x, tile.y, tile.z)).collect();
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[0], (4399, 2686, 13));
+        assert_eq!(tiles[5], (4401, 2687, 13));
+
+        let inverted =
+            BoundingBox::new(Coordinate::new(52.53, 13.42), Coordinate::new(52.50, 13.35));
+        assert!(TileRange::new(&inverted, 13).is_err());
+    }
+
+    #[test]
+    fn sweeps_fetch_every_tile_in_order() {
+        let bbox = BoundingBox::new(Coordinate::new(52.50, 13.35), Coordinate::new(52.53, 13.42));
+        let backend = tile_numbers();
+
+        let sequential: Vec<Vec<u8>> = TileRange::new(&bbox, 13)
+            .unwrap()
+            .fetch(&backend)
+            .map(|(_, result)| result.unwrap().result)
+            .collect();
+        let parallel: Vec<Vec<u8>> = TileRange::new(&bbox, 13)
+            .unwrap()
+            .fetch_parallel(&backend, 4)
+            .into_iter()
+            .map(|(_, result)| result.unwrap().result)
+            .collect();
+
+        assert_eq!(sequential.len(), 6);
+        assert_eq!(sequential[0], b"13/4399/2686".to_vec());
+        assert_eq!(sequential, parallel);
+
+        assert!(matches!(
+            TileRequest::new(10, 10, 11).run(&backend),
+            Err(OsrmError::InvalidValue(_))
+        ));
+    }
+}
@@ -1,8 +1,8 @@
 use std::os::raw::c_int;
 
-use crate::{Osrm, Status};
+use crate::{Osrm, Status, backend::RoutingBackend, general::rs_structs::coordinate::Coordinate, osrm_error::OsrmError};
 
-use super::{tile_result::{CTileResult, TileResult}, tile_result_destroy, osrm_tile};
+use super::{tile_range, tile_result::{CTileResult, TileResult}, tile_result_destroy, osrm_tile};
 
 #[repr(C)]
 pub(crate) struct CTileRequest {
@@ -21,6 +21,8 @@ impl CTileRequest {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileRequest {
     pub x: i32,
     pub y: i32,
@@ -32,7 +34,31 @@ impl TileRequest {
         TileRequest { x, y, z }
     }
 
-    pub fn run(&mut self, osrm: &Osrm) -> (Status, TileResult) {
+    pub fn from_coordinate(coordinate: &Coordinate, z: i32) -> Result<TileRequest, OsrmError> {
+        let (x, y) = tile_range::tile_for_coordinate(coordinate, z)?;
+        Ok(TileRequest::new(x, y, z))
+    }
+
+    pub fn validate(&self) -> Result<(), OsrmError> {
+        tile_range::validate_zoom(self.z)?;
+
+        let tiles = 1i64 << self.z;
+        if self.x < 0 || self.y < 0 || self.x as i64 >= tiles || self.y as i64 >= tiles {
+            return Err(OsrmError::InvalidValue(format!(
+                "Tile {}/{}/{} is outside the map",
+                self.z, self.x, self.y
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn run<B: RoutingBackend + ?Sized>(&mut self, backend: &B) -> Result<TileResult, OsrmError> {
+        self.validate()?;
+        backend.tile(self)
+    }
+
+    pub(crate) fn run_osrm(&mut self, osrm: &Osrm) -> Result<TileResult, OsrmError> {
         unsafe {
             let mut result: *mut CTileResult = std::ptr::null_mut();
             let result_ptr: *mut *mut CTileResult = &mut result;
@@ -43,11 +69,22 @@ impl TileRequest {
                 result_ptr,
             );
 
+            if result.is_null() {
+                return Err(OsrmError::Ffi("osrm_tile returned no result".to_string()));
+            }
+
             let converted_result = TileResult::new(&(*result));
 
             tile_result_destroy(result);
 
-            (status, converted_result)
+            if status == Status::Ok {
+                Ok(converted_result)
+            } else {
+                Err(OsrmError::Ffi(format!(
+                    "osrm_tile failed for tile {}/{}/{}",
+                    self.z, self.x, self.y
+                )))
+            }
         }
     }
 }
\ No newline at end of file
@@ -1,11 +1,17 @@
 use std::os::raw::c_int;
 
+use crate::osrm_error::OsrmError;
+
+use super::mvt::{self, DebugTile};
+
 #[repr(C)]
 pub(crate) struct CTileResult {
     result: *const u8,
     string_length: c_int,
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileResult {
     pub result: Vec<u8>,
 }
@@ -27,4 +33,8 @@ impl TileResult {
 
         result
     }
+
+    pub fn decode(&self) -> Result<DebugTile, OsrmError> {
+        mvt::decode(&self.result)
+    }
 }
\ No newline at end of file
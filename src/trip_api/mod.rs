@@ -1,10 +1,14 @@
 use std::{ffi::c_void, os::raw::{c_char, c_double, c_int}};
 
-use crate::{Status, general::c_string_to_string};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{Status, StringDecoding, general::{c_string_to_option_string, c_string_to_string}, osrm_error::OsrmError};
 
 use self::{trip_request::CTripRequest, trip_result::CTripResult};
 
 pub mod trip_request;
+pub mod pickup_delivery_trip;
+pub mod time_window_trip;
 pub mod trip_result;
 pub mod trip_request_builder;
 
@@ -20,14 +24,16 @@ extern "C" {
 }
 
 #[repr(C)]
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum trip_start {
     StartAny,
     First,
 }
 
 #[repr(C)]
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum trip_end {
     EndAny,
     Last,
@@ -44,6 +50,8 @@ struct CTripWaypoint {
     waypoint_index: c_int,
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TripWaypoint {
     pub hint: Option<String>,
     pub distance: f64,
@@ -54,19 +62,47 @@ pub struct TripWaypoint {
 }
 
 impl TripWaypoint {
-    fn new(c_waypoints: &CTripWaypoint) -> TripWaypoint {
-        let mut hint: Option<String> = None;
-        if c_waypoints.hint != std::ptr::null() {
-            hint = Option::from(c_string_to_string(c_waypoints.hint));
-        }
-
-        TripWaypoint {
-            hint,
+    fn new(
+        c_waypoints: &CTripWaypoint,
+        string_decoding: &StringDecoding,
+    ) -> Result<TripWaypoint, OsrmError> {
+        Ok(TripWaypoint {
+            hint: c_string_to_option_string(c_waypoints.hint, string_decoding)?,
             distance: c_waypoints.distance,
-            name: c_string_to_string(c_waypoints.name),
+            name: c_string_to_string(c_waypoints.name, string_decoding)?,
             location: c_waypoints.location,
             trips_index: c_waypoints.trips_index,
             waypoint_index: c_waypoints.waypoint_index,
-        }
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut waypoint = serde_json::json!({
+            "waypoint_index": self.waypoint_index,
+            "trips_index": self.trips_index,
+        });
+        json::insert_opt_string(&mut waypoint, "hint", &self.hint);
+        waypoint["distance"] = serde_json::json!(self.distance);
+        waypoint["name"] = serde_json::json!(self.name);
+        waypoint["location"] = serde_json::json!(self.location);
+        waypoint
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<TripWaypoint, OsrmError> {
+        Ok(TripWaypoint {
+            hint: json::opt_string_field(value, "hint"),
+            distance: json::opt_f64_field(value, "distance").unwrap_or_default(),
+            name: json::opt_string_field(value, "name").unwrap_or_default(),
+            location: json::location_field(value, "location")?,
+            trips_index: json::i32_field(value, "trips_index")?,
+            waypoint_index: json::i32_field(value, "waypoint_index")?,
+        })
+    }
+
+    #[cfg(feature = "geo")]
+    pub fn point(&self) -> geo_types::Point<f64> {
+        geo_types::Point::new(self.location[0], self.location[1])
     }
 }
\ No newline at end of file
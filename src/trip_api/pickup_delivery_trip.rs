@@ -0,0 +1,386 @@
+use crate::{
+    backend::RoutingBackend,
+    general::rs_structs::coordinate::Coordinate,
+    osrm_error::OsrmError,
+    route_api::{route_request_builder::RouteRequestBuilder, OverviewType},
+    vrp::{
+        local_search::{self, Search},
+        CostMatrix, Rng, SolverOptions,
+    },
+};
+
+use super::trip_result::TripResult;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shipment {
+    pub pickup: Coordinate,
+    pub delivery: Coordinate,
+    pub size: u32,
+    pub pickup_service: f64,
+    pub delivery_service: f64,
+}
+
+impl Shipment {
+    pub fn new(pickup: Coordinate, delivery: Coordinate, size: u32) -> Shipment {
+        Shipment {
+            pickup,
+            delivery,
+            size,
+            pickup_service: 0.0,
+            delivery_service: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopKind {
+    Pickup,
+    Delivery,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItineraryStop {
+    pub shipment: usize,
+    pub kind: StopKind,
+    pub location: usize,
+    pub arrival: f64,
+    pub departure: f64,
+    pub load: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Itinerary {
+    pub stops: Vec<ItineraryStop>,
+    pub end: f64,
+    pub duration: f64,
+    pub distance: f64,
+    pub max_load: u32,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PickupDeliveryResult {
+    pub itinerary: Itinerary,
+    pub trip: TripResult,
+}
+
+// Every shipment is picked up before it is delivered and the vehicle never carries more than
+// `capacity`. Locations are the start followed by each shipment's pickup and delivery.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PickupDeliveryTrip {
+    pub start: Coordinate,
+    pub shipments: Vec<Shipment>,
+    pub capacity: Option<u32>,
+    pub start_time: f64,
+    pub roundtrip: bool,
+    pub options: SolverOptions,
+}
+
+impl PickupDeliveryTrip {
+    pub fn new(start: Coordinate, shipments: Vec<Shipment>) -> PickupDeliveryTrip {
+        PickupDeliveryTrip {
+            start,
+            shipments,
+            capacity: None,
+            start_time: 0.0,
+            roundtrip: false,
+            options: SolverOptions::default(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), OsrmError> {
+        if !self.start_time.is_finite() {
+            return Err(OsrmError::InvalidValue(
+                "trip start time must be finite".to_string(),
+            ));
+        }
+        for (index, shipment) in self.shipments.iter().enumerate() {
+            if shipment.pickup_service.is_nan()
+                || shipment.pickup_service < 0.0
+                || shipment.delivery_service.is_nan()
+                || shipment.delivery_service < 0.0
+            {
+                return Err(OsrmError::InvalidValue(format!(
+                    "Shipment {} has an invalid service time",
+                    index
+                )));
+            }
+            if self
+                .capacity
+                .is_some_and(|capacity| shipment.size > capacity)
+            {
+                return Err(OsrmError::InvalidValue(format!(
+                    "Shipment {} does not fit the vehicle capacity",
+                    index
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn locations(&self) -> Vec<Coordinate> {
+        std::iter::once(self.start.clone())
+            .chain(
+                self.shipments
+                    .iter()
+                    .flat_map(|shipment| vec![shipment.pickup.clone(), shipment.delivery.clone()]),
+            )
+            .collect()
+    }
+
+    pub fn pickup_location(&self, shipment: usize) -> usize {
+        1 + 2 * shipment
+    }
+
+    pub fn delivery_location(&self, shipment: usize) -> usize {
+        2 + 2 * shipment
+    }
+
+    pub fn cost_matrix<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+    ) -> Result<CostMatrix, OsrmError> {
+        CostMatrix::fetch(backend, &self.locations(), self.options.max_table_locations)
+    }
+
+    pub fn itinerary(&self, matrix: &CostMatrix) -> Result<Itinerary, OsrmError> {
+        self.validate()?;
+        if matrix.size() != 1 + 2 * self.shipments.len() {
+            return Err(OsrmError::InvalidValue(format!(
+                "cost matrix has {} locations, the trip has {}",
+                matrix.size(),
+                1 + 2 * self.shipments.len()
+            )));
+        }
+
+        let search = ShipmentSearch { trip: self, matrix };
+        local_search::solve(&search, &self.options)
+            .and_then(|order| search.evaluate(&order))
+            .ok_or_else(|| OsrmError::NoTrips("no itinerary reaches every stop".to_string()))
+    }
+
+    pub fn run<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+    ) -> Result<PickupDeliveryResult, OsrmError> {
+        let itinerary = self.itinerary(&self.cost_matrix(backend)?)?;
+        let locations = self.locations();
+
+        let mut positions = vec![0; locations.len()];
+        let mut coordinates = vec![self.start.clone()];
+        for stop in &itinerary.stops {
+            positions[stop.location] = coordinates.len();
+            coordinates.push(locations[stop.location].clone());
+        }
+        if self.roundtrip {
+            coordinates.push(self.start.clone());
+        }
+
+        let route = RouteRequestBuilder::new(&coordinates)
+            .set_overview(OverviewType::Full)
+            .build()?
+            .run(backend)?;
+
+        Ok(PickupDeliveryResult {
+            trip: TripResult::from_route(route, &positions)?,
+            itinerary,
+        })
+    }
+}
+
+struct ShipmentSearch<'a> {
+    trip: &'a PickupDeliveryTrip,
+    matrix: &'a CostMatrix,
+}
+
+impl<'a> ShipmentSearch<'a> {
+    // Orders hold location indices without the start; None if a pickup follows its delivery, the
+    // load exceeds the capacity or a leg is unreachable.
+    fn evaluate(&self, order: &[usize]) -> Option<Itinerary> {
+        let mut stops = Vec::with_capacity(order.len());
+        let mut picked_up = vec![false; self.trip.shipments.len()];
+        let mut time = self.trip.start_time;
+        let mut distance = 0.0;
+        let mut load = 0;
+        let mut max_load = 0;
+        let mut at = 0;
+        for &location in order {
+            let shipment = (location - 1) / 2;
+            let kind = if location % 2 == 1 {
+                StopKind::Pickup
+            } else {
+                StopKind::Delivery
+            };
+            let service = match kind {
+                StopKind::Pickup => {
+                    picked_up[shipment] = true;
+                    load += self.trip.shipments[shipment].size;
+                    self.trip.shipments[shipment].pickup_service
+                }
+                StopKind::Delivery => {
+                    if !picked_up[shipment] {
+                        return None;
+                    }
+                    load -= self.trip.shipments[shipment].size;
+                    self.trip.shipments[shipment].delivery_service
+                }
+            };
+            if self.trip.capacity.is_some_and(|capacity| load > capacity) {
+                return None;
+            }
+            max_load = max_load.max(load);
+
+            time += self.matrix.duration(at, location)?;
+            distance += self.matrix.distance(at, location);
+            at = location;
+            stops.push(ItineraryStop {
+                shipment,
+                kind,
+                location,
+                arrival: time,
+                departure: time + service,
+                load,
+            });
+            time += service;
+        }
+        if self.trip.roundtrip {
+            time += self.matrix.duration(at, 0)?;
+            distance += self.matrix.distance(at, 0);
+        }
+
+        Some(Itinerary {
+            stops,
+            end: time,
+            duration: time - self.trip.start_time,
+            distance,
+            max_load,
+        })
+    }
+
+    fn duration(&self, order: &[usize]) -> Option<f64> {
+        self.evaluate(order).map(|itinerary| itinerary.duration)
+    }
+}
+
+// Orders start right after the start location; item `i` is shipment `i`, which is removed and
+// reinserted with both of its stops.
+impl<'a> Search for ShipmentSearch<'a> {
+    type Solution = Vec<usize>;
+    type Cost = f64;
+
+    fn initial(&self, rng: &mut Rng) -> Vec<usize> {
+        let mut shipments: Vec<usize> = (0..self.trip.shipments.len()).collect();
+        rng.shuffle(&mut shipments);
+
+        let mut order = Vec::with_capacity(2 * shipments.len());
+        for shipment in shipments {
+            self.insert(&mut order, shipment);
+        }
+
+        order
+    }
+
+    fn cost(&self, order: &Vec<usize>) -> Option<f64> {
+        self.duration(order)
+    }
+
+    fn items(&self) -> usize {
+        self.trip.shipments.len()
+    }
+
+    fn remove(&self, order: &mut Vec<usize>, shipment: usize) {
+        order.retain(|&location| (location - 1) / 2 != shipment);
+    }
+
+    // Tries every pickup and delivery position pair; appending both is always feasible, so that
+    // is the fallback when every leg is unreachable.
+    fn insert(&self, order: &mut Vec<usize>, shipment: usize) {
+        let pickup = self.trip.pickup_location(shipment);
+        let delivery = self.trip.delivery_location(shipment);
+
+        let mut best: Option<(usize, usize, f64)> = None;
+        for pickup_position in 0..=order.len() {
+            for delivery_position in pickup_position + 1..=order.len() + 1 {
+                let mut candidate = order.clone();
+                candidate.insert(pickup_position, pickup);
+                candidate.insert(delivery_position, delivery);
+                if let Some(cost) = self.duration(&candidate) {
+                    if best.is_none_or(|(_, _, best)| cost < best) {
+                        best = Some((pickup_position, delivery_position, cost));
+                    }
+                }
+            }
+        }
+
+        let (pickup_position, delivery_position) = best
+            .map_or((order.len(), order.len() + 1), |(pickup, delivery, _)| {
+                (pickup, delivery)
+            });
+        order.insert(pickup_position, pickup);
+        order.insert(delivery_position, delivery);
+    }
+
+    fn improve_once(&self, order: &mut Vec<usize>, cost: &mut f64) -> bool {
+        local_search::improve_order(order, cost, 0, |order| self.duration(order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{general::rs_structs::coordinate::Coordinate, vrp::CostMatrix};
+
+    use super::{PickupDeliveryTrip, Shipment, StopKind};
+
+    #[test]
+    fn pickups_precede_deliveries_within_capacity() {
+        let at = |position: f64| Coordinate::new(position, 0.0);
+        let mut trip = PickupDeliveryTrip::new(
+            at(0.0),
+            vec![
+                Shipment::new(at(1.0), at(3.0), 1),
+                Shipment::new(at(2.0), at(4.0), 1),
+                Shipment::new(at(4.0), at(2.0), 1),
+            ],
+        );
+        let matrix = CostMatrix::line(&[0.0, 1.0, 3.0, 2.0, 4.0, 4.0, 2.0]);
+
+        let itinerary = trip.itinerary(&matrix).unwrap();
+        assert_eq!(itinerary.duration, 360.0);
+        assert_eq!(itinerary.stops.len(), 6);
+        for (index, stop) in itinerary.stops.iter().enumerate() {
+            if stop.kind == StopKind::Delivery {
+                assert!(
+                    itinerary.stops[..index]
+                        .iter()
+                        .any(|other| other.shipment == stop.shipment
+                            && other.kind == StopKind::Pickup)
+                );
+            }
+        }
+        assert_eq!(itinerary.stops.last().unwrap().load, 0);
+
+        trip.shipments.pop();
+        trip.capacity = Some(1);
+        let matrix = CostMatrix::line(&[0.0, 1.0, 3.0, 2.0, 4.0]);
+        let itinerary = trip.itinerary(&matrix).unwrap();
+        assert_eq!(
+            itinerary
+                .stops
+                .iter()
+                .map(|stop| stop.location)
+                .collect::<Vec<usize>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(itinerary.duration, 360.0);
+        assert_eq!(itinerary.max_load, 1);
+
+        trip.capacity = Some(0);
+        assert!(trip.itinerary(&matrix).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{
+    backend::RoutingBackend,
+    general::rs_structs::coordinate::Coordinate,
+    osrm_error::OsrmError,
+    route_api::{route_request_builder::RouteRequestBuilder, OverviewType},
+    vrp::{
+        local_search::{self, Cost, Search},
+        CostMatrix, Rng, SolverOptions,
+    },
+};
+
+use super::trip_result::TripResult;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeWindow {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TimeWindow {
+    pub fn new(start: f64, end: f64) -> TimeWindow {
+        TimeWindow { start, end }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TripStop {
+    pub location: Coordinate,
+    pub time_window: Option<TimeWindow>,
+    pub service_time: f64,
+}
+
+impl TripStop {
+    pub fn new(location: Coordinate) -> TripStop {
+        TripStop {
+            location,
+            time_window: None,
+            service_time: 0.0,
+        }
+    }
+
+    pub fn with_window(location: Coordinate, window: TimeWindow, service_time: f64) -> TripStop {
+        TripStop {
+            location,
+            time_window: Some(window),
+            service_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StopTiming {
+    pub stop: usize,
+    pub arrival: f64,
+    pub waiting: f64,
+    pub lateness: f64,
+    pub departure: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TripSchedule {
+    pub stops: Vec<StopTiming>,
+    pub end: f64,
+    pub duration: f64,
+    pub distance: f64,
+    pub waiting: f64,
+    pub lateness: f64,
+}
+
+impl TripSchedule {
+    pub fn order(&self) -> Vec<usize> {
+        self.stops.iter().map(|timing| timing.stop).collect()
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeWindowTripResult {
+    pub schedule: TripSchedule,
+    pub trip: TripResult,
+}
+
+// The first stop is where the trip starts; windows are soft, so arriving early means waiting and
+// arriving late is reported as lateness, which the ordering minimizes before the total duration.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeWindowTrip {
+    pub stops: Vec<TripStop>,
+    pub start_time: f64,
+    pub roundtrip: bool,
+    pub options: SolverOptions,
+}
+
+impl TimeWindowTrip {
+    pub fn new(stops: Vec<TripStop>) -> TimeWindowTrip {
+        TimeWindowTrip {
+            stops,
+            start_time: 0.0,
+            roundtrip: true,
+            options: SolverOptions::default(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), OsrmError> {
+        if self.stops.is_empty() {
+            return Err(OsrmError::InvalidValue(
+                "a trip needs at least one stop".to_string(),
+            ));
+        }
+        if !self.start_time.is_finite() {
+            return Err(OsrmError::InvalidValue(
+                "trip start time must be finite".to_string(),
+            ));
+        }
+        for (index, stop) in self.stops.iter().enumerate() {
+            if stop.service_time.is_nan() || stop.service_time < 0.0 {
+                return Err(OsrmError::InvalidValue(format!(
+                    "Stop {} has an invalid service time",
+                    index
+                )));
+            }
+            if let Some(window) = &stop.time_window {
+                if window.start.is_nan() || window.end.is_nan() || window.start > window.end {
+                    return Err(OsrmError::InvalidValue(format!(
+                        "Stop {} has an invalid time window",
+                        index
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        self.stops
+            .iter()
+            .map(|stop| stop.location.clone())
+            .collect()
+    }
+
+    pub fn cost_matrix<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+    ) -> Result<CostMatrix, OsrmError> {
+        CostMatrix::fetch(
+            backend,
+            &self.coordinates(),
+            self.options.max_table_locations,
+        )
+    }
+
+    pub fn schedule(&self, matrix: &CostMatrix) -> Result<TripSchedule, OsrmError> {
+        self.validate()?;
+        if matrix.size() != self.stops.len() {
+            return Err(OsrmError::InvalidValue(format!(
+                "cost matrix has {} locations, the trip has {}",
+                matrix.size(),
+                self.stops.len()
+            )));
+        }
+
+        let search = WindowSearch { trip: self, matrix };
+        local_search::solve(&search, &self.options)
+            .and_then(|order| search.evaluate(&order))
+            .ok_or_else(|| OsrmError::NoTrips("no stop order reaches every stop".to_string()))
+    }
+
+    pub fn run<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+    ) -> Result<TimeWindowTripResult, OsrmError> {
+        let schedule = self.schedule(&self.cost_matrix(backend)?)?;
+        let order = schedule.order();
+
+        let mut coordinates: Vec<Coordinate> = order
+            .iter()
+            .map(|&stop| self.stops[stop].location.clone())
+            .collect();
+        if self.roundtrip {
+            coordinates.push(self.stops[0].location.clone());
+        }
+        let mut positions = vec![0; order.len()];
+        for (position, &stop) in order.iter().enumerate() {
+            positions[stop] = position;
+        }
+
+        let route = RouteRequestBuilder::new(&coordinates)
+            .set_overview(OverviewType::Full)
+            .build()?
+            .run(backend)?;
+
+        Ok(TimeWindowTripResult {
+            trip: TripResult::from_route(route, &positions)?,
+            schedule,
+        })
+    }
+}
+
+struct WindowSearch<'a> {
+    trip: &'a TimeWindowTrip,
+    matrix: &'a CostMatrix,
+}
+
+impl<'a> WindowSearch<'a> {
+    // Every order starts with stop 0.
+    fn evaluate(&self, order: &[usize]) -> Option<TripSchedule> {
+        let mut stops = Vec::with_capacity(order.len());
+        let mut time = self.trip.start_time;
+        let mut distance = 0.0;
+        let mut at = order[0];
+        for &stop in order {
+            if stop != at {
+                time += self.matrix.duration(at, stop)?;
+                distance += self.matrix.distance(at, stop);
+                at = stop;
+            }
+
+            let arrival = time;
+            let (waiting, lateness) = match &self.trip.stops[stop].time_window {
+                Some(window) => (
+                    (window.start - arrival).max(0.0),
+                    (arrival - window.end).max(0.0),
+                ),
+                None => (0.0, 0.0),
+            };
+            time += waiting + self.trip.stops[stop].service_time;
+            stops.push(StopTiming {
+                stop,
+                arrival,
+                waiting,
+                lateness,
+                departure: time,
+            });
+        }
+        if self.trip.roundtrip && at != order[0] {
+            time += self.matrix.duration(at, order[0])?;
+            distance += self.matrix.distance(at, order[0]);
+        }
+
+        Some(TripSchedule {
+            waiting: stops.iter().map(|timing| timing.waiting).sum(),
+            lateness: stops.iter().map(|timing| timing.lateness).sum(),
+            stops,
+            end: time,
+            duration: time - self.trip.start_time,
+            distance,
+        })
+    }
+
+    fn rate(&self, order: &[usize]) -> Option<(f64, f64)> {
+        self.evaluate(order)
+            .map(|schedule| (schedule.lateness, schedule.duration))
+    }
+
+    fn deadline(&self, stop: usize) -> f64 {
+        self.trip.stops[stop]
+            .time_window
+            .as_ref()
+            .map_or(f64::INFINITY, |window| window.end)
+    }
+}
+
+// Stop 0 stays first; item `i` is stop `i + 1`. Orders are rated by lateness, then duration.
+impl<'a> Search for WindowSearch<'a> {
+    type Solution = Vec<usize>;
+    type Cost = (f64, f64);
+
+    // Start from the stops sorted by window end, which is feasible whenever the windows allow.
+    fn initial(&self, _: &mut Rng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.trip.stops.len()).collect();
+        order[1..].sort_by(|&a, &b| self.deadline(a).total_cmp(&self.deadline(b)));
+        order
+    }
+
+    fn cost(&self, order: &Vec<usize>) -> Option<(f64, f64)> {
+        self.rate(order)
+    }
+
+    fn items(&self) -> usize {
+        self.trip.stops.len() - 1
+    }
+
+    fn remove(&self, order: &mut Vec<usize>, item: usize) {
+        order.retain(|&stop| stop != item + 1);
+    }
+
+    fn insert(&self, order: &mut Vec<usize>, item: usize) {
+        let mut best: Option<(usize, (f64, f64))> = None;
+        for position in 1..=order.len() {
+            order.insert(position, item + 1);
+            if let Some(cost) = self.rate(order) {
+                if best.is_none_or(|(_, best)| cost.is_better_than(best)) {
+                    best = Some((position, cost));
+                }
+            }
+            order.remove(position);
+        }
+
+        order.insert(best.map_or(order.len(), |(position, _)| position), item + 1);
+    }
+
+    fn improve_once(&self, order: &mut Vec<usize>, cost: &mut (f64, f64)) -> bool {
+        local_search::improve_order(order, cost, 1, |order| self.rate(order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{general::rs_structs::coordinate::Coordinate, vrp::CostMatrix};
+
+    use super::{TimeWindow, TimeWindowTrip, TripStop};
+
+    #[test]
+    fn windows_reorder_stops_and_report_waiting_and_lateness() {
+        let stop = |position: f64, start: f64, end: f64| {
+            TripStop::with_window(
+                Coordinate::new(position, 0.0),
+                TimeWindow::new(start, end),
+                30.0,
+            )
+        };
+        let mut trip = TimeWindowTrip::new(vec![
+            TripStop::new(Coordinate::new(0.0, 0.0)),
+            stop(1.0, 600.0, 900.0),
+            stop(2.0, 0.0, 200.0),
+            stop(3.0, 0.0, 10.0),
+        ]);
+        let matrix = CostMatrix::line(&[0.0, 1.0, 2.0, 3.0]);
+
+        let schedule = trip.schedule(&matrix).unwrap();
+        assert_eq!(schedule.order(), vec![0, 2, 3, 1]);
+        assert_eq!(schedule.stops[1].arrival, 120.0);
+        assert_eq!(schedule.stops[1].lateness, 0.0);
+        assert_eq!(schedule.stops[2].arrival, 210.0);
+        assert_eq!(schedule.stops[2].lateness, 200.0);
+        assert_eq!(schedule.stops[3].arrival, 360.0);
+        assert_eq!(schedule.stops[3].waiting, 240.0);
+        assert_eq!(schedule.stops[3].departure, 630.0);
+        assert_eq!(schedule.lateness, 200.0);
+        assert_eq!(schedule.end, 690.0);
+
+        trip.stops[2].time_window = None;
+        trip.roundtrip = false;
+        let schedule = trip.schedule(&matrix).unwrap();
+        assert_eq!(schedule.order(), vec![0, 3, 2, 1]);
+        assert_eq!(schedule.lateness, 170.0);
+        assert_eq!(schedule.waiting, 240.0);
+        assert_eq!(schedule.end, 630.0);
+    }
+}
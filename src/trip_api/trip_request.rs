@@ -1,4 +1,4 @@
-use crate::{Boolean, Osrm, Status, general::{c_structs::c_general_options::CGeneralOptions, rs_structs::{general_options::GeneralOptions}}, route_api::{AnnotationsType, GeometriesType, OverviewType}};
+use crate::{Boolean, Osrm, Status, backend::RoutingBackend, engine_config::engine_limits::EngineLimits, general::{c_structs::c_general_options::CGeneralOptions, rs_structs::{general_options::GeneralOptions}, validation::{self, ValidationError}}, osrm_error::OsrmError, route_api::{AnnotationsType, GeometriesType, OverviewType}};
 
 use super::{trip_end, trip_result::{CTripResult, TripResult}, trip_result_destroy, trip_start,osrm_trip };
 
@@ -31,6 +31,7 @@ impl CTripRequest {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TripRequest {
     pub(crate) general_options: GeneralOptions,
     pub(crate) roundtrip: bool,
@@ -44,7 +45,16 @@ pub struct TripRequest {
 }
 
 impl TripRequest {
-    pub fn run(&mut self, osrm: &Osrm) -> (Status, TripResult) {
+    pub fn run<B: RoutingBackend + ?Sized>(&mut self, backend: &B) -> Result<TripResult, OsrmError> {
+        backend.trip(self)
+    }
+
+    pub(crate) fn check_limits(&self, limits: &EngineLimits) -> Result<(), ValidationError> {
+        validation::check_coordinate_limit(&self.general_options, limits.max_locations_trip)
+    }
+
+    pub(crate) fn run_osrm(&mut self, osrm: &Osrm) -> Result<TripResult, OsrmError> {
+        self.check_limits(osrm.limits())?;
         unsafe {
             let mut result: *mut CTripResult = std::ptr::null_mut();
             let result_ptr: *mut *mut CTripResult = &mut result;
@@ -55,11 +65,21 @@ impl TripRequest {
                 result_ptr,
             );
 
-            let converted_result = TripResult::new(&(*result));
+            if result.is_null() {
+                return Err(OsrmError::Ffi("osrm_trip returned no result".to_string()));
+            }
+
+            let converted_result = TripResult::new(&(*result), &osrm.string_decoding, &self.geometries);
 
             trip_result_destroy(result);
 
-            (status, converted_result)
+            let converted_result = converted_result?;
+
+            if status == Status::Ok {
+                Ok(converted_result)
+            } else {
+                Err(OsrmError::from_code(converted_result.code, converted_result.message))
+            }
         }
     }
 }
\ No newline at end of file
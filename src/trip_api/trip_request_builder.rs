@@ -1,9 +1,10 @@
 use std::ffi::CString;
 
-use crate::{general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate}, route_api::{AnnotationsType, GeometriesType, OverviewType}};
+use crate::{engine_config::engine_limits::EngineLimits, general::{c_structs::{c_approach::Approach, c_bearing::Bearing}, rs_structs::{coordinate::Coordinate, general_options::{GeneralOptions, GeneralOptionsTrait}}, to_vec_ccoordinate, validation::{self, ValidationError}}, route_api::{AnnotationsType, GeometriesType, OverviewType}};
 
 use super::{trip_end, trip_request::TripRequest, trip_start};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TripRequestBuilder {
     general_options: GeneralOptions,
     roundtrip: bool,
@@ -14,10 +15,11 @@ pub struct TripRequestBuilder {
     annotations_type: AnnotationsType,
     geometries: GeometriesType,
     overview: OverviewType,
+    limits: EngineLimits,
 }
 
 impl TripRequestBuilder {
-    pub fn new(coordinates: &Vec<Coordinate>) -> TripRequestBuilder {
+    pub fn new<C: Clone + Into<Coordinate>>(coordinates: &[C]) -> TripRequestBuilder {
         TripRequestBuilder {
             general_options: GeneralOptions::new(coordinates),
             roundtrip: true,
@@ -28,6 +30,7 @@ impl TripRequestBuilder {
             annotations_type: AnnotationsType::None,
             geometries: GeometriesType::Polyline,
             overview: OverviewType::Simplified,
+            limits: EngineLimits::unlimited(),
         }
     }
 
@@ -71,20 +74,37 @@ impl TripRequestBuilder {
         self
     }
 
-    pub fn build(&self) -> Result<TripRequest, String> {
-        Ok (
-            TripRequest {
-                general_options: self.general_options.clone(),
+    pub fn set_limits<'a>(&'a mut self, limits: &EngineLimits) -> &'a mut Self {
+        self.limits = limits.clone();
+        self
+    }
+
+    pub fn build(&self) -> Result<TripRequest, ValidationError> {
+        validation::check_general_options(&self.general_options, 2)?;
+        // The engine only solves roundtrips, or one-way trips from the first to the last coordinate.
+        let fixed_ends = self.source == trip_start::First && self.destination == trip_end::Last;
+        if !self.roundtrip && !fixed_ends {
+            return Err(ValidationError::UnsupportedTrip {
                 roundtrip: self.roundtrip,
                 source: self.source.clone(),
                 destination: self.destination.clone(),
-                steps: self.steps,
-                annotations: self.annotations,
-                annotations_type: self.annotations_type.clone(),
-                geometries: self.geometries.clone(),
-                overview: self.overview.clone(),
-            }
-        )
+            });
+        }
+
+        let request = TripRequest {
+            general_options: self.general_options.clone(),
+            roundtrip: self.roundtrip,
+            source: self.source.clone(),
+            destination: self.destination.clone(),
+            steps: self.steps,
+            annotations: self.annotations,
+            annotations_type: self.annotations_type.clone(),
+            geometries: self.geometries.clone(),
+            overview: self.overview.clone(),
+        };
+        request.check_limits(&self.limits)?;
+
+        Ok(request)
     }
 }
 
@@ -1,6 +1,13 @@
-use std::{ffi::CStr, os::raw::{c_char, c_int}, slice};
+use std::{os::raw::{c_char, c_int}, slice};
 
-use crate::general::{c_structs::c_route::COsrmRoute, rs_structs::route::Route};
+#[cfg(feature = "json")]
+use crate::general::json;
+use crate::{
+    general::{c_string_to_option_string, c_structs::c_route::COsrmRoute, rs_structs::route::Route},
+    osrm_error::OsrmError,
+    route_api::{route_result::RouteResult, GeometriesType},
+    StringDecoding,
+};
 
 use super::{CTripWaypoint, TripWaypoint};
 
@@ -14,6 +21,8 @@ pub(crate) struct CTripResult {
     number_of_trips: c_int,
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TripResult {
     pub code: Option<String>,
     pub message: Option<String>,
@@ -22,51 +31,133 @@ pub struct TripResult {
 }
 
 impl TripResult {
-    pub(crate) fn new(c_reasult: &CTripResult) -> TripResult {
-        let mut code: Option<String> = None;
-        if c_reasult.code != std::ptr::null_mut() {
-            let c_code_buf: *const c_char = c_reasult.code;
-            let c_code_str: &CStr = unsafe { CStr::from_ptr(c_code_buf) };
-            let code_str_slice: &str = c_code_str.to_str().unwrap();
-            code = Option::from(code_str_slice.to_owned());
-        }
-
-        let mut message: Option<String> = None;
-        if c_reasult.message != std::ptr::null_mut() {
-            let c_message_buf: *const c_char = c_reasult.message;
-            let c_message_str: &CStr = unsafe { CStr::from_ptr(c_message_buf) };
-            let message_str_slice: &str = c_message_str.to_str().unwrap();
-            message = Option::from(message_str_slice.to_owned());
-        }
+    pub(crate) fn new(
+        c_reasult: &CTripResult,
+        string_decoding: &StringDecoding,
+        geometries: &GeometriesType,
+    ) -> Result<TripResult, OsrmError> {
+        let code = c_string_to_option_string(c_reasult.code, string_decoding)?;
+        let message = c_string_to_option_string(c_reasult.message, string_decoding)?;
 
         let mut waypoints: Vec<TripWaypoint> = Vec::new();
         if c_reasult.waypoints != std::ptr::null_mut() {
             let waypoints_vec = unsafe {
                 slice::from_raw_parts(c_reasult.waypoints, c_reasult.number_of_waypoints as usize)
-                    .to_vec()
             };
 
-            for waypoint in &waypoints_vec {
-                waypoints.push(TripWaypoint::new(waypoint));
+            for waypoint in waypoints_vec {
+                waypoints.push(TripWaypoint::new(waypoint, string_decoding)?);
             }
         }
 
         let mut trips: Vec<Route> = Vec::new();
         if c_reasult.trips != std::ptr::null_mut() {
             let routes_vec = unsafe {
-                slice::from_raw_parts(c_reasult.trips, c_reasult.number_of_trips as usize).to_vec()
+                slice::from_raw_parts(c_reasult.trips, c_reasult.number_of_trips as usize)
             };
 
-            for route in routes_vec.iter() {
-                trips.push(Route::from(route));
+            for route in routes_vec {
+                trips.push(Route::new(route, string_decoding, geometries)?);
             }
         }
 
-        TripResult {
+        Ok(TripResult {
             code,
             message,
             waypoints,
             trips,
+        })
+    }
+
+    // `positions[i]` is where input location `i` appears in the routed visiting order.
+    pub(crate) fn from_route(
+        route: RouteResult,
+        positions: &[usize],
+    ) -> Result<TripResult, OsrmError> {
+        let waypoints = positions
+            .iter()
+            .map(|&position| {
+                let waypoint = route.waypoints.get(position).ok_or_else(|| {
+                    OsrmError::NoTrips("route response is missing waypoints".to_string())
+                })?;
+                Ok(TripWaypoint {
+                    hint: waypoint.hint.clone(),
+                    distance: waypoint.distance,
+                    name: waypoint.name.clone(),
+                    location: waypoint.location,
+                    trips_index: 0,
+                    waypoint_index: position as i32,
+                })
+            })
+            .collect::<Result<Vec<TripWaypoint>, OsrmError>>()?;
+        let trip = route
+            .routes
+            .into_iter()
+            .next()
+            .ok_or_else(|| OsrmError::NoTrips("route response has no routes".to_string()))?;
+
+        Ok(TripResult {
+            code: route.code,
+            message: route.message,
+            waypoints,
+            trips: vec![trip],
+        })
+    }
+
+    // `geometries` must be the value the response was requested with: polyline and polyline6
+    // strings look alike, and decoding one as the other is off by a factor of ten.
+    #[cfg(feature = "json")]
+    pub fn from_osrm_json(
+        response: &str,
+        geometries: &GeometriesType,
+    ) -> Result<TripResult, OsrmError> {
+        let value = json::parse(response)?;
+        json::check_code(&value)?;
+        TripResult::from_json(&value, geometries)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_osrm_json(&self) -> String {
+        json::render(&self.to_json())
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut result = serde_json::json!({
+            "code": self.code.clone().unwrap_or_else(|| "Ok".to_string()),
+        });
+        json::insert_opt_string(&mut result, "message", &self.message);
+        result["trips"] = serde_json::Value::Array(self.trips.iter().map(Route::to_json).collect());
+        if !self.waypoints.is_empty() {
+            result["waypoints"] = serde_json::Value::Array(
+                self.waypoints.iter().map(TripWaypoint::to_json).collect(),
+            );
         }
+        result
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn from_json(
+        value: &serde_json::Value,
+        geometries: &GeometriesType,
+    ) -> Result<TripResult, OsrmError> {
+        Ok(TripResult {
+            code: json::opt_string_field(value, "code"),
+            message: json::opt_string_field(value, "message"),
+            waypoints: match json::opt_array_field(value, "waypoints") {
+                Some(waypoints) => waypoints
+                    .iter()
+                    .map(TripWaypoint::from_json)
+                    .collect::<Result<Vec<TripWaypoint>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+            trips: match json::opt_array_field(value, "trips") {
+                Some(trips) => trips
+                    .iter()
+                    .map(|route| Route::from_json(route, geometries))
+                    .collect::<Result<Vec<Route>, OsrmError>>()?,
+                None => Vec::new(),
+            },
+        })
     }
-}
\ No newline at end of file
+}
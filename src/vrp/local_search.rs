@@ -0,0 +1,151 @@
+use super::{Rng, SolverOptions};
+
+pub(crate) const EPSILON: f64 = 1e-6;
+
+pub(crate) trait Cost: Copy {
+    fn is_better_than(self, other: Self) -> bool;
+}
+
+impl Cost for f64 {
+    fn is_better_than(self, other: f64) -> bool {
+        self < other - EPSILON
+    }
+}
+
+// Compared in order: the second value only decides when the first ones tie.
+impl Cost for (f64, f64) {
+    fn is_better_than(self, (first, second): (f64, f64)) -> bool {
+        self.0 < first - EPSILON || (self.0 <= first + EPSILON && self.1 < second - EPSILON)
+    }
+}
+
+// A solution that ruin and recreate can take apart and rebuild. Items are what gets removed and
+// reinserted as a whole: jobs, stops or shipments with both of their stops.
+pub(crate) trait Search {
+    type Solution: Clone;
+    type Cost: Cost;
+
+    fn initial(&self, rng: &mut Rng) -> Self::Solution;
+
+    fn cost(&self, solution: &Self::Solution) -> Option<Self::Cost>;
+
+    fn items(&self) -> usize;
+
+    fn remove(&self, solution: &mut Self::Solution, item: usize);
+
+    // Inserts the item where it costs least; an item that fits nowhere is kept in a feasible way
+    // or left out, so `cost` still rates the result.
+    fn insert(&self, solution: &mut Self::Solution, item: usize);
+
+    // Items the solution leaves out; every ruin retries them together with the removed ones.
+    fn unassigned(&self, _: &Self::Solution) -> Vec<usize> {
+        Vec::new()
+    }
+
+    // Applies the first improving move besides reinsertion and reports whether there was one.
+    fn improve_once(&self, solution: &mut Self::Solution, cost: &mut Self::Cost) -> bool;
+}
+
+pub(crate) fn solve<S: Search>(search: &S, options: &SolverOptions) -> Option<S::Solution> {
+    let mut rng = Rng::new(options.seed);
+
+    let mut best = search.initial(&mut rng);
+    let mut best_cost = search.cost(&best)?;
+    improve(search, &mut best, &mut best_cost);
+
+    // Ruin and recreate: pull a few random items out, reinsert them greedily and keep improvements.
+    for _ in 0..options.iterations {
+        if search.items() == 0 {
+            break;
+        }
+
+        let mut removed: Vec<usize> = (0..search.items()).collect();
+        rng.shuffle(&mut removed);
+        removed.truncate(1 + rng.below((removed.len() / 4).max(1)));
+        for item in search.unassigned(&best) {
+            if !removed.contains(&item) {
+                removed.push(item);
+            }
+        }
+        rng.shuffle(&mut removed);
+        let mut candidate = best.clone();
+        for &item in &removed {
+            search.remove(&mut candidate, item);
+        }
+        for &item in &removed {
+            search.insert(&mut candidate, item);
+        }
+
+        let mut candidate_cost = match search.cost(&candidate) {
+            Some(cost) => cost,
+            None => continue,
+        };
+        improve(search, &mut candidate, &mut candidate_cost);
+        if candidate_cost.is_better_than(best_cost) {
+            best = candidate;
+            best_cost = candidate_cost;
+        }
+    }
+
+    Some(best)
+}
+
+fn improve<S: Search>(search: &S, solution: &mut S::Solution, cost: &mut S::Cost) {
+    while reinsert(search, solution, cost) || search.improve_once(solution, cost) {}
+}
+
+fn reinsert<S: Search>(search: &S, solution: &mut S::Solution, cost: &mut S::Cost) -> bool {
+    for item in 0..search.items() {
+        let mut candidate = solution.clone();
+        search.remove(&mut candidate, item);
+        search.insert(&mut candidate, item);
+        match search.cost(&candidate) {
+            Some(candidate_cost) if candidate_cost.is_better_than(*cost) => {
+                *solution = candidate;
+                *cost = candidate_cost;
+                return true;
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+// 2-opt and swap moves on a single stop order; the first `fixed` positions keep their stops.
+pub(crate) fn improve_order<C: Cost>(
+    order: &mut Vec<usize>,
+    cost: &mut C,
+    fixed: usize,
+    evaluate: impl Fn(&[usize]) -> Option<C>,
+) -> bool {
+    let mut accept = |order: &mut Vec<usize>, candidate: Vec<usize>| match evaluate(&candidate) {
+        Some(candidate_cost) if candidate_cost.is_better_than(*cost) => {
+            *order = candidate;
+            *cost = candidate_cost;
+            true
+        }
+        _ => false,
+    };
+
+    for start in fixed..order.len() {
+        for end in start + 2..order.len() {
+            let mut candidate = order.clone();
+            candidate[start..=end].reverse();
+            if accept(order, candidate) {
+                return true;
+            }
+        }
+    }
+    for first in fixed..order.len() {
+        for second in first + 2..order.len() {
+            let mut candidate = order.clone();
+            candidate.swap(first, second);
+            if accept(order, candidate) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
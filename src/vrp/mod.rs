@@ -0,0 +1,136 @@
+use crate::{
+    backend::RoutingBackend,
+    general::rs_structs::coordinate::Coordinate,
+    osrm_error::OsrmError,
+    table_api::{
+        large_table::LargeTable, matrix::Matrix, table_request_builder::TableRequestBuilder,
+        Annotations,
+    },
+};
+
+pub(crate) mod local_search;
+pub mod problem;
+pub mod solution;
+pub mod solver;
+
+// Shared by the vrp solver and the time window and pickup and delivery trips. The seed drives the
+// ruin and recreate moves, so the same input always gives the same plan.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverOptions {
+    pub seed: u64,
+    pub iterations: usize,
+    pub max_table_locations: i32,
+}
+
+impl Default for SolverOptions {
+    fn default() -> SolverOptions {
+        SolverOptions {
+            seed: 1,
+            iterations: 200,
+            max_table_locations: -1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CostMatrix {
+    pub durations: Matrix,
+    pub distances: Option<Matrix>,
+}
+
+impl CostMatrix {
+    pub fn new(durations: Matrix, distances: Option<Matrix>) -> Result<CostMatrix, OsrmError> {
+        let square = |matrix: &Matrix| matrix.rows() == matrix.columns();
+        let same_shape = distances.as_ref().is_none_or(|distances| {
+            distances.rows() == durations.rows() && distances.columns() == durations.columns()
+        });
+        if !square(&durations) || !same_shape {
+            return Err(OsrmError::InvalidValue(
+                "cost matrices must be square and of the same size".to_string(),
+            ));
+        }
+
+        Ok(CostMatrix {
+            durations,
+            distances,
+        })
+    }
+
+    pub fn fetch<B: RoutingBackend + Sync + ?Sized>(
+        backend: &B,
+        coordinates: &[Coordinate],
+        max_table_locations: i32,
+    ) -> Result<CostMatrix, OsrmError> {
+        let request = TableRequestBuilder::new(coordinates)
+            .set_annotations(Annotations::ALL)
+            .build()?;
+        let result = LargeTable::new(request, max_table_locations).run(backend)?;
+
+        match result.durations {
+            Some(durations) => CostMatrix::new(durations, result.distances),
+            None => Err(OsrmError::NoTable(
+                "table response has no durations".to_string(),
+            )),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.durations.rows()
+    }
+
+    pub fn duration(&self, from: usize, to: usize) -> Option<f64> {
+        self.durations.get(from, to)
+    }
+
+    pub fn distance(&self, from: usize, to: usize) -> f64 {
+        self.distances
+            .as_ref()
+            .and_then(|distances| distances.get(from, to))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+impl CostMatrix {
+    // Locations on a line, one minute apart per unit of position.
+    pub(crate) fn line(positions: &[f64]) -> CostMatrix {
+        let values = positions
+            .iter()
+            .flat_map(|from| positions.iter().map(move |to| (from - to).abs() * 60.0))
+            .collect();
+        CostMatrix::new(
+            Matrix::new(positions.len(), positions.len(), values).unwrap(),
+            None,
+        )
+        .unwrap()
+    }
+}
+
+// xorshift64*, so solutions only depend on the seed and not on the platform.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, values: &mut [T]) {
+        for index in (1..values.len()).rev() {
+            let other = self.below(index + 1);
+            values.swap(index, other);
+        }
+    }
+}
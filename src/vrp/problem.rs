@@ -0,0 +1,139 @@
+use crate::{
+    backend::RoutingBackend, general::rs_structs::coordinate::Coordinate, osrm_error::OsrmError,
+};
+
+use super::{solution::Solution, solver, CostMatrix, SolverOptions};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vehicle {
+    pub start_depot: usize,
+    pub end_depot: usize,
+    pub capacity: u32,
+    pub shift_start: f64,
+    pub shift_end: f64,
+}
+
+impl Vehicle {
+    pub fn new(depot: usize, capacity: u32) -> Vehicle {
+        Vehicle {
+            start_depot: depot,
+            end_depot: depot,
+            capacity,
+            shift_start: 0.0,
+            shift_end: f64::INFINITY,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Job {
+    pub location: Coordinate,
+    pub demand: u32,
+    pub service_time: f64,
+}
+
+impl Job {
+    pub fn new(location: Coordinate, demand: u32, service_time: f64) -> Job {
+        Job {
+            location,
+            demand,
+            service_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Problem {
+    pub depots: Vec<Coordinate>,
+    pub vehicles: Vec<Vehicle>,
+    pub jobs: Vec<Job>,
+}
+
+impl Problem {
+    pub fn new(depots: Vec<Coordinate>, vehicles: Vec<Vehicle>, jobs: Vec<Job>) -> Problem {
+        Problem {
+            depots,
+            vehicles,
+            jobs,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), OsrmError> {
+        for (index, vehicle) in self.vehicles.iter().enumerate() {
+            if vehicle.start_depot >= self.depots.len() || vehicle.end_depot >= self.depots.len() {
+                return Err(OsrmError::InvalidValue(format!(
+                    "Vehicle {} uses a depot that does not exist",
+                    index
+                )));
+            }
+            if vehicle.shift_start.is_nan()
+                || vehicle.shift_end.is_nan()
+                || vehicle.shift_start > vehicle.shift_end
+            {
+                return Err(OsrmError::InvalidValue(format!(
+                    "Vehicle {} has an invalid shift",
+                    index
+                )));
+            }
+        }
+        for (index, job) in self.jobs.iter().enumerate() {
+            if job.service_time.is_nan() || job.service_time < 0.0 {
+                return Err(OsrmError::InvalidValue(format!(
+                    "Job {} has an invalid service time",
+                    index
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Depots come first, followed by the jobs in order.
+    pub fn locations(&self) -> Vec<Coordinate> {
+        self.depots
+            .iter()
+            .cloned()
+            .chain(self.jobs.iter().map(|job| job.location.clone()))
+            .collect()
+    }
+
+    pub fn job_location(&self, job: usize) -> usize {
+        self.depots.len() + job
+    }
+
+    pub fn cost_matrix<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+        options: &SolverOptions,
+    ) -> Result<CostMatrix, OsrmError> {
+        CostMatrix::fetch(backend, &self.locations(), options.max_table_locations)
+    }
+
+    pub fn solve<B: RoutingBackend + Sync + ?Sized>(
+        &self,
+        backend: &B,
+        options: &SolverOptions,
+    ) -> Result<Solution, OsrmError> {
+        self.solve_with_matrix(&self.cost_matrix(backend, options)?, options)
+    }
+
+    pub fn solve_with_matrix(
+        &self,
+        matrix: &CostMatrix,
+        options: &SolverOptions,
+    ) -> Result<Solution, OsrmError> {
+        self.validate()?;
+        if matrix.size() != self.depots.len() + self.jobs.len() {
+            return Err(OsrmError::InvalidValue(format!(
+                "cost matrix has {} locations, the problem has {}",
+                matrix.size(),
+                self.depots.len() + self.jobs.len()
+            )));
+        }
+
+        Ok(solver::solve(self, matrix, options))
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    backend::RoutingBackend,
+    general::rs_structs::coordinate::Coordinate,
+    osrm_error::OsrmError,
+    route_api::{
+        route_request::RouteRequest, route_request_builder::RouteRequestBuilder,
+        route_result::RouteResult, OverviewType,
+    },
+};
+
+use super::{problem::Problem, solver, CostMatrix};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stop {
+    pub job: usize,
+    pub arrival: f64,
+    pub departure: f64,
+    pub load: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VehicleRoute {
+    pub vehicle: usize,
+    pub stops: Vec<Stop>,
+    pub start: f64,
+    pub end: f64,
+    pub duration: f64,
+    pub distance: f64,
+    pub load: u32,
+}
+
+impl VehicleRoute {
+    pub fn coordinates(&self, problem: &Problem) -> Vec<Coordinate> {
+        let vehicle = &problem.vehicles[self.vehicle];
+
+        std::iter::once(problem.depots[vehicle.start_depot].clone())
+            .chain(
+                self.stops
+                    .iter()
+                    .map(|stop| problem.jobs[stop.job].location.clone()),
+            )
+            .chain(std::iter::once(problem.depots[vehicle.end_depot].clone()))
+            .collect()
+    }
+
+    pub fn route_request(&self, problem: &Problem) -> Result<RouteRequest, OsrmError> {
+        RouteRequestBuilder::new(&self.coordinates(problem))
+            .set_overview(OverviewType::Full)
+            .build()
+            .map_err(OsrmError::from)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    pub routes: Vec<VehicleRoute>,
+    pub unassigned: Vec<usize>,
+    pub duration: f64,
+    pub distance: f64,
+}
+
+impl Solution {
+    pub(crate) fn new(
+        problem: &Problem,
+        matrix: &CostMatrix,
+        routes: Vec<Vec<usize>>,
+        unassigned: Vec<usize>,
+    ) -> Solution {
+        let routes: Vec<VehicleRoute> = routes
+            .into_iter()
+            .enumerate()
+            .filter(|(_, jobs)| !jobs.is_empty())
+            .filter_map(|(vehicle, jobs)| {
+                let schedule = solver::schedule(problem, matrix, vehicle, &jobs)?;
+                let start = problem.vehicles[vehicle].shift_start;
+                let mut load = 0;
+
+                Some(VehicleRoute {
+                    vehicle,
+                    stops: jobs
+                        .iter()
+                        .zip(&schedule.arrivals)
+                        .map(|(&job, &arrival)| {
+                            load += problem.jobs[job].demand;
+                            Stop {
+                                job,
+                                arrival,
+                                departure: arrival + problem.jobs[job].service_time,
+                                load,
+                            }
+                        })
+                        .collect(),
+                    start,
+                    end: schedule.end,
+                    duration: schedule.end - start,
+                    distance: schedule.distance,
+                    load: schedule.load,
+                })
+            })
+            .collect();
+
+        Solution {
+            duration: routes.iter().map(|route| route.duration).sum(),
+            distance: routes.iter().map(|route| route.distance).sum(),
+            routes,
+            unassigned,
+        }
+    }
+
+    pub fn route_requests(&self, problem: &Problem) -> Result<Vec<RouteRequest>, OsrmError> {
+        self.routes
+            .iter()
+            .map(|route| route.route_request(problem))
+            .collect()
+    }
+
+    pub fn materialize<B: RoutingBackend + ?Sized>(
+        &self,
+        problem: &Problem,
+        backend: &B,
+    ) -> Result<Vec<RouteResult>, OsrmError> {
+        self.route_requests(problem)?
+            .iter_mut()
+            .map(|request| request.run(backend))
+            .collect()
+    }
+}
@@ -0,0 +1,307 @@
+use super::{
+    local_search::{self, Search, EPSILON},
+    problem::Problem,
+    solution::Solution,
+    CostMatrix, Rng, SolverOptions,
+};
+
+pub(crate) struct Schedule {
+    pub(crate) arrivals: Vec<f64>,
+    pub(crate) end: f64,
+    pub(crate) distance: f64,
+    pub(crate) load: u32,
+}
+
+pub(crate) fn schedule(
+    problem: &Problem,
+    matrix: &CostMatrix,
+    vehicle: usize,
+    jobs: &[usize],
+) -> Option<Schedule> {
+    let vehicle = &problem.vehicles[vehicle];
+    let load = jobs
+        .iter()
+        .map(|&job| problem.jobs[job].demand)
+        .sum::<u32>();
+    if load > vehicle.capacity {
+        return None;
+    }
+
+    let mut arrivals = Vec::with_capacity(jobs.len());
+    let mut time = vehicle.shift_start;
+    let mut distance = 0.0;
+    let mut at = vehicle.start_depot;
+    for &job in jobs {
+        let to = problem.job_location(job);
+        time += matrix.duration(at, to)?;
+        distance += matrix.distance(at, to);
+        arrivals.push(time);
+        time += problem.jobs[job].service_time;
+        at = to;
+    }
+    time += matrix.duration(at, vehicle.end_depot)?;
+    distance += matrix.distance(at, vehicle.end_depot);
+
+    if time > vehicle.shift_end {
+        return None;
+    }
+
+    Some(Schedule {
+        arrivals,
+        end: time,
+        distance,
+        load,
+    })
+}
+
+pub(crate) fn solve(problem: &Problem, matrix: &CostMatrix, options: &SolverOptions) -> Solution {
+    let search = FleetSearch { problem, matrix };
+    let mut best = local_search::solve(&search, options).unwrap_or_else(|| State {
+        unassigned: (0..problem.jobs.len()).collect(),
+        ..search.empty()
+    });
+
+    best.unassigned.sort_unstable();
+    Solution::new(problem, matrix, best.routes, best.unassigned)
+}
+
+#[derive(Clone)]
+struct State {
+    routes: Vec<Vec<usize>>,
+    costs: Vec<f64>,
+    unassigned: Vec<usize>,
+}
+
+impl State {
+    // Plans are rated by their unassigned jobs first and their summed route durations second.
+    fn cost(&self) -> (f64, f64) {
+        (self.unassigned.len() as f64, self.costs.iter().sum())
+    }
+}
+
+struct FleetSearch<'a> {
+    problem: &'a Problem,
+    matrix: &'a CostMatrix,
+}
+
+impl<'a> FleetSearch<'a> {
+    fn empty(&self) -> State {
+        State {
+            routes: vec![Vec::new(); self.problem.vehicles.len()],
+            costs: vec![0.0; self.problem.vehicles.len()],
+            unassigned: Vec::new(),
+        }
+    }
+
+    fn route_cost(&self, vehicle: usize, jobs: &[usize]) -> Option<f64> {
+        if jobs.is_empty() {
+            return Some(0.0);
+        }
+
+        schedule(self.problem, self.matrix, vehicle, jobs)
+            .map(|schedule| schedule.end - self.problem.vehicles[vehicle].shift_start)
+    }
+
+    fn best_insertion(&self, state: &State, job: usize) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (vehicle, route) in state.routes.iter().enumerate() {
+            for position in 0..=route.len() {
+                let mut candidate = route.clone();
+                candidate.insert(position, job);
+                if let Some(cost) = self.route_cost(vehicle, &candidate) {
+                    let delta = cost - state.costs[vehicle];
+                    if best.is_none_or(|(_, _, best)| delta < best) {
+                        best = Some((vehicle, position, delta));
+                    }
+                }
+            }
+        }
+
+        best.map(|(vehicle, position, _)| (vehicle, position))
+    }
+
+    fn two_opt(&self, state: &mut State) -> bool {
+        for vehicle in 0..state.routes.len() {
+            let length = state.routes[vehicle].len();
+            for start in 0..length {
+                for end in start + 1..length {
+                    let mut candidate = state.routes[vehicle].clone();
+                    candidate[start..=end].reverse();
+                    if let Some(cost) = self.route_cost(vehicle, &candidate) {
+                        if cost < state.costs[vehicle] - EPSILON {
+                            state.routes[vehicle] = candidate;
+                            state.costs[vehicle] = cost;
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    fn exchange(&self, state: &mut State) -> bool {
+        for first in 0..state.routes.len() {
+            for second in first + 1..state.routes.len() {
+                for i in 0..state.routes[first].len() {
+                    for j in 0..state.routes[second].len() {
+                        let mut first_route = state.routes[first].clone();
+                        let mut second_route = state.routes[second].clone();
+                        std::mem::swap(&mut first_route[i], &mut second_route[j]);
+
+                        let costs = self
+                            .route_cost(first, &first_route)
+                            .zip(self.route_cost(second, &second_route));
+                        if let Some((first_cost, second_cost)) = costs {
+                            if first_cost + second_cost
+                                < state.costs[first] + state.costs[second] - EPSILON
+                            {
+                                state.routes[first] = first_route;
+                                state.routes[second] = second_route;
+                                state.costs[first] = first_cost;
+                                state.costs[second] = second_cost;
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+}
+
+impl<'a> Search for FleetSearch<'a> {
+    type Solution = State;
+    type Cost = (f64, f64);
+
+    fn initial(&self, rng: &mut Rng) -> State {
+        let mut order: Vec<usize> = (0..self.problem.jobs.len()).collect();
+        rng.shuffle(&mut order);
+
+        let mut state = self.empty();
+        for job in order {
+            self.insert(&mut state, job);
+        }
+
+        state
+    }
+
+    fn cost(&self, state: &State) -> Option<(f64, f64)> {
+        Some(state.cost())
+    }
+
+    fn items(&self) -> usize {
+        self.problem.jobs.len()
+    }
+
+    fn remove(&self, state: &mut State, job: usize) {
+        for (vehicle, route) in state.routes.iter_mut().enumerate() {
+            if let Some(position) = route.iter().position(|&other| other == job) {
+                route.remove(position);
+                state.costs[vehicle] = self.route_cost(vehicle, route).unwrap_or(f64::INFINITY);
+                return;
+            }
+        }
+        state.unassigned.retain(|&other| other != job);
+    }
+
+    fn insert(&self, state: &mut State, job: usize) {
+        match self.best_insertion(state, job) {
+            Some((vehicle, position)) => {
+                state.routes[vehicle].insert(position, job);
+                state.costs[vehicle] = self
+                    .route_cost(vehicle, &state.routes[vehicle])
+                    .unwrap_or(f64::INFINITY);
+            }
+            None => state.unassigned.push(job),
+        }
+    }
+
+    fn unassigned(&self, state: &State) -> Vec<usize> {
+        state.unassigned.clone()
+    }
+
+    fn improve_once(&self, state: &mut State, cost: &mut (f64, f64)) -> bool {
+        let improved = self.two_opt(state) || self.exchange(state);
+        *cost = state.cost();
+        improved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        general::rs_structs::coordinate::Coordinate,
+        vrp::{
+            problem::{Job, Problem, Vehicle},
+            CostMatrix, SolverOptions,
+        },
+    };
+
+    #[test]
+    fn vehicles_split_jobs_by_capacity_and_shift() {
+        let positions = [0.0, -2.0, 1.0, -1.0, 2.0, 9.0];
+        let job =
+            |position: f64, demand: u32| Job::new(Coordinate::new(position, 0.0), demand, 30.0);
+        let mut short_shift = Vehicle::new(0, 2);
+        short_shift.shift_end = 600.0;
+        let problem = Problem::new(
+            vec![Coordinate::new(0.0, 0.0)],
+            vec![short_shift, Vehicle::new(0, 2)],
+            vec![
+                job(-2.0, 1),
+                job(1.0, 1),
+                job(-1.0, 1),
+                job(2.0, 1),
+                job(9.0, 1),
+            ],
+        );
+        let matrix = CostMatrix::line(&positions);
+
+        let solution = problem
+            .solve_with_matrix(&matrix, &SolverOptions::default())
+            .unwrap();
+        assert_eq!(solution.unassigned, vec![4]);
+        assert_eq!(solution.routes.len(), 2);
+
+        let mut sides: Vec<Vec<usize>> = solution
+            .routes
+            .iter()
+            .map(|route| {
+                let mut jobs: Vec<usize> = route.stops.iter().map(|stop| stop.job).collect();
+                jobs.sort_unstable();
+                jobs
+            })
+            .collect();
+        sides.sort();
+        assert_eq!(sides, vec![vec![0, 2], vec![1, 3]]);
+        assert_eq!(solution.duration, 2.0 * (240.0 + 60.0));
+        for route in &solution.routes {
+            assert!(route.load <= problem.vehicles[route.vehicle].capacity);
+            assert_eq!(route.stops[0].departure, route.stops[0].arrival + 30.0);
+        }
+
+        let again = problem
+            .solve_with_matrix(&matrix, &SolverOptions::default())
+            .unwrap();
+        assert_eq!(
+            again
+                .routes
+                .iter()
+                .map(|route| route.stops.iter().map(|stop| stop.job).collect())
+                .collect::<Vec<Vec<usize>>>(),
+            solution
+                .routes
+                .iter()
+                .map(|route| route.stops.iter().map(|stop| stop.job).collect())
+                .collect::<Vec<Vec<usize>>>()
+        );
+
+        let requests = solution.route_requests(&problem).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].general_options.coordinate.len(), 4);
+    }
+}